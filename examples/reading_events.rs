@@ -194,7 +194,7 @@ pub async fn filtering_out_system_events(client: &Client) -> Result<()> {
 pub async fn read_from_stream_resolving_link_tos(client: &Client) -> Result<()> {
     // region read-from-all-stream-resolving-link-Tos
     let options = ReadAllOptions::default().resolve_link_tos();
-    client.read_all(&options, All).await?;
+    let _ = client.read_all(&options, All).await?;
     // endregion read-from-all-stream-resolving-link-Tos
     Ok(())
 }
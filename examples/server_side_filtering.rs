@@ -13,6 +13,7 @@ use std::error::Error;
 use uuid::Uuid;
 
 #[derive(Serialize, Deserialize, Debug)]
+#[allow(dead_code)]
 struct TestEvent {
     pub id: String,
     pub important_data: String,
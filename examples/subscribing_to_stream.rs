@@ -30,12 +30,12 @@ pub async fn subscribe_to_stream(client: &Client) -> Result<()> {
     // region subscribe-to-stream-from-position
     let options = SubscribeToStreamOptions::default().position(StreamPosition::Position(20));
 
-    client.subscribe_to_stream("some-stream", &options).await?;
+    let _ = client.subscribe_to_stream("some-stream", &options).await?;
     // endregion subscribe-to-stream-from-position
 
     // region subscribe-to-stream-live
     let options = SubscribeToStreamOptions::default().position(StreamPosition::End);
-    client.subscribe_to_stream("some-stream", &options).await?;
+    let _ = client.subscribe_to_stream("some-stream", &options).await?;
     // endregion subscribe-to-stream-live
 
     // region subscribe-to-stream-resolving-linktos
@@ -43,7 +43,7 @@ pub async fn subscribe_to_stream(client: &Client) -> Result<()> {
         .position(StreamPosition::Start)
         .resolve_link_tos();
 
-    client
+    let _ = client
         .subscribe_to_stream("$et-myEventType", &options)
         .await?;
     // endregion subscribe-to-stream-resolving-linktos
@@ -80,12 +80,12 @@ pub async fn subscribe_to_all(client: &Client) -> Result<()> {
         prepare: 1_056,
     }));
 
-    client.subscribe_to_all(&options).await?;
+    let _ = client.subscribe_to_all(&options).await?;
     // endregion subscribe-to-all-from-position
 
     // region subscribe-to-all-live
     let options = SubscribeToAllOptions::default().position(StreamPosition::End);
-    client.subscribe_to_all(&options).await?;
+    let _ = client.subscribe_to_all(&options).await?;
     // endregion subscribe-to-all-live
 
     // region subscribe-to-all-subscription-dropped
@@ -108,7 +108,7 @@ pub async fn subscribe_to_filtered(client: &Client) -> Result<()> {
     let filter = SubscriptionFilter::on_stream_name().add_prefix("test-");
     let options = SubscribeToAllOptions::default().filter(filter);
 
-    client.subscribe_to_all(&options).await?;
+    let _ = client.subscribe_to_all(&options).await?;
     // endregion stream-prefix-filtered-subscription
 
     // region stream-regex-filtered-subscription
@@ -122,7 +122,7 @@ pub async fn overriding_user_credentials(client: &Client) -> Result<()> {
     // region overriding-user-credentials
     let options =
         SubscribeToAllOptions::default().authenticated(Credentials::new("admin", "changeit"));
-    client.subscribe_to_all(&options).await?;
+    let _ = client.subscribe_to_all(&options).await?;
     // endregion overriding-user-credentials
 
     Ok(())
@@ -0,0 +1,296 @@
+//! Catch-up subscription bounding its memory use while it replays history.
+//!
+//! The live subscription starts first, then historical events are read page
+//! by page, each page being read only once the consumer took every event of
+//! the previous one. Live events arriving meanwhile are kept aside up to the
+//! `live_buffer_size` of [`CatchUpOptions`]. Once the history is replayed, the
+//! events kept aside that weren't replayed are delivered, followed by the live
+//! ones. Events are delivered once, in stream order.
+use crate::commands;
+use crate::grpc::GrpcClient;
+use crate::options::catch_up::CatchUpOptions;
+use crate::options::read_stream::ReadStreamOptions;
+use crate::options::subscribe_to_stream::SubscribeToStreamOptions;
+use crate::types::{ReadResult, ResolvedEvent, StreamPosition, SubEvent};
+use futures::stream::{BoxStream, Stream, StreamExt, TryStreamExt};
+use futures::FutureExt;
+use std::collections::VecDeque;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll};
+
+/// Last revision known to exist and last revision delivered.
+#[derive(Debug, Default)]
+struct Progress {
+    head: Option<u64>,
+    delivered: Option<u64>,
+}
+
+impl Progress {
+    fn observed(&mut self, revision: u64) {
+        self.head = Some(self.head.map_or(revision, |head| head.max(revision)));
+    }
+
+    fn is_new(&self, revision: u64) -> bool {
+        match self.delivered {
+            Some(delivered) => revision > delivered,
+            None => true,
+        }
+    }
+
+    fn delivered(&mut self, revision: u64) {
+        self.observed(revision);
+        self.delivered = Some(revision);
+    }
+
+    fn lag(&self) -> u64 {
+        match (self.head, self.delivered) {
+            (Some(head), Some(delivered)) => head.saturating_sub(delivered),
+            (Some(head), None) => head + 1,
+            (None, _) => 0,
+        }
+    }
+}
+
+/// Catch-up subscription created by [`crate::Client::catch_up_stream`].
+pub struct CatchUpSubscription {
+    events: BoxStream<'static, crate::Result<SubEvent>>,
+    progress: Arc<Mutex<Progress>>,
+}
+
+impl CatchUpSubscription {
+    /// Number of events known to exist in the stream that weren't delivered
+    /// yet. It drops to zero once the subscription caught up.
+    pub fn lag(&self) -> u64 {
+        self.progress.lock().unwrap().lag()
+    }
+}
+
+impl Stream for CatchUpSubscription {
+    type Item = crate::Result<SubEvent>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.events.poll_next_unpin(cx)
+    }
+}
+
+fn revision(event: &ResolvedEvent) -> u64 {
+    event.get_original_event().revision
+}
+
+pub(crate) fn catch_up_stream(
+    client: GrpcClient,
+    stream: String,
+    options: SubscribeToStreamOptions,
+    catch_up: CatchUpOptions,
+) -> CatchUpSubscription {
+    let progress = Arc::new(Mutex::new(Progress::default()));
+    let shared = progress.clone();
+    let events = async_stream::stream! {
+        let live_options = SubscribeToStreamOptions {
+            credentials: options.credentials.clone(),
+            resolve_link_tos: options.resolve_link_tos,
+            ..Default::default()
+        }
+        .position(StreamPosition::End);
+
+        let live = commands::subscribe_to_stream(&client, stream.as_str(), &live_options).await;
+        let mut live = match live {
+            Ok(live) => live,
+            Err(e) => {
+                yield Err(e);
+                return;
+            }
+        };
+
+        // The last revision is only read once the server confirmed the live
+        // subscription, so an event written in between can't be missed.
+        loop {
+            match live.next().await {
+                Some(Ok(SubEvent::Confirmed(_))) => break,
+                Some(Ok(_)) => {}
+                Some(Err(e)) => {
+                    yield Err(e);
+                    return;
+                }
+                None => return,
+            }
+        }
+
+        let mut read_options = ReadStreamOptions::default();
+
+        if let Some(credentials) = options.credentials.clone() {
+            read_options = read_options.authenticated(credentials);
+        }
+
+        if options.resolve_link_tos {
+            read_options = read_options.resolve_link_tos();
+        }
+
+        let last_options = read_options.clone().position(StreamPosition::End);
+        let last = match commands::read_stream(&client, &last_options, stream.as_str(), 1).await {
+            Ok(ReadResult::Ok(events)) => match events.try_collect::<Vec<_>>().await {
+                Ok(events) => events.first().map(revision),
+                Err(e) => {
+                    yield Err(e);
+                    return;
+                }
+            },
+            Ok(ReadResult::StreamNotFound(_)) => None,
+            Err(e) => {
+                yield Err(e);
+                return;
+            }
+        };
+
+        if let Some(last) = last {
+            shared.lock().unwrap().observed(last);
+        }
+
+        let mut next = match options.position {
+            StreamPosition::Start => Some(0),
+            StreamPosition::Position(revision) => Some(revision + 1),
+            StreamPosition::End => None,
+        };
+
+        if let StreamPosition::Position(revision) = options.position {
+            shared.lock().unwrap().delivered = Some(revision);
+        }
+
+        let mut buffer: VecDeque<ResolvedEvent> = VecDeque::new();
+
+        while let Some(from) = next.filter(|from| matches!(last, Some(last) if *from <= last)) {
+            let page_options = read_options
+                .clone()
+                .forwards()
+                .position(StreamPosition::Position(from));
+            let page_size = catch_up.page_size as u64;
+            let page = match commands::read_stream(&client, &page_options, stream.as_str(), page_size).await {
+                Ok(ReadResult::Ok(events)) => events.try_collect::<Vec<_>>().await,
+                Ok(ReadResult::StreamNotFound(_)) => Ok(Vec::new()),
+                Err(e) => Err(e),
+            };
+
+            let page = match page {
+                Ok(page) => page,
+                Err(e) => {
+                    yield Err(e);
+                    return;
+                }
+            };
+
+            let complete = page.len() == catch_up.page_size;
+
+            next = None;
+
+            for event in page {
+                let number = revision(&event);
+
+                next = Some(number + 1);
+                shared.lock().unwrap().delivered(number);
+                yield Ok(SubEvent::EventAppeared(event));
+            }
+
+            // Keeps aside the live events that arrived while the page was
+            // consumed.
+            while let Some(item) = live.next().now_or_never() {
+                match item {
+                    Some(Ok(SubEvent::EventAppeared(event))) => {
+                        shared.lock().unwrap().observed(revision(&event));
+                        buffer.push_back(event);
+                    }
+
+                    Some(Ok(_)) => {}
+
+                    Some(Err(e)) => {
+                        yield Err(e);
+                        return;
+                    }
+
+                    None => return,
+                }
+
+                if buffer.len() > catch_up.live_buffer_size {
+                    error!("Catch-up of stream {} overflowed its live buffer", stream);
+
+                    yield Err(crate::Error::CatchUpBufferOverflow {
+                        stream: stream.clone(),
+                        capacity: catch_up.live_buffer_size,
+                    });
+                    return;
+                }
+            }
+
+            let reached_buffer = match (buffer.front(), next) {
+                (Some(front), Some(next)) => next >= revision(front),
+                _ => false,
+            };
+
+            if !complete || reached_buffer {
+                break;
+            }
+        }
+
+        for event in buffer {
+            let number = revision(&event);
+
+            if shared.lock().unwrap().is_new(number) {
+                shared.lock().unwrap().delivered(number);
+                yield Ok(SubEvent::EventAppeared(event));
+            }
+        }
+
+        if options.notify_caught_up {
+            yield Ok(SubEvent::CaughtUp);
+        }
+
+        while let Some(item) = live.next().await {
+            match item {
+                Ok(SubEvent::EventAppeared(event)) => {
+                    let number = revision(&event);
+
+                    if shared.lock().unwrap().is_new(number) {
+                        shared.lock().unwrap().delivered(number);
+                        yield Ok(SubEvent::EventAppeared(event));
+                    }
+                }
+
+                Ok(_) => {}
+
+                Err(e) => {
+                    yield Err(e);
+                    return;
+                }
+            }
+        }
+    };
+
+    CatchUpSubscription {
+        events: Box::pin(events),
+        progress,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Progress;
+
+    #[test]
+    fn lag_counts_undelivered_events() {
+        let mut progress = Progress::default();
+
+        assert_eq!(progress.lag(), 0);
+
+        progress.observed(9);
+        assert_eq!(progress.lag(), 10);
+
+        progress.delivered(3);
+        assert_eq!(progress.lag(), 6);
+        assert!(!progress.is_new(3));
+        assert!(progress.is_new(4));
+
+        progress.observed(12);
+        progress.delivered(12);
+        assert_eq!(progress.lag(), 0);
+    }
+}
@@ -0,0 +1,256 @@
+//! Coalescing of the checkpoints of a subscription, so that persisting them
+//! doesn't cost a write per event.
+//!
+//! The position of an event, or of a checkpoint, becomes pending once the
+//! consumer asked for the following item, which means it's done with it. The
+//! latest pending position is handed to the persist callback when enough
+//! positions are pending, when the flush interval elapsed or when the
+//! subscription ends. If the subscription is dropped with a position
+//! pending, the callback runs on a spawned task. The position of the item
+//! the consumer was handling when dropping the subscription isn't persisted,
+//! so that item is delivered again on restart.
+use crate::clock::Clock;
+use crate::options::checkpoint_flush::CheckpointFlushOptions;
+use crate::types::{Checkpoint, SubEvent};
+use futures::future::{self, BoxFuture, Either};
+use futures::stream::{BoxStream, StreamExt};
+use std::future::Future;
+use std::sync::Arc;
+
+struct Flusher<F> {
+    persist: F,
+    pending: Option<Checkpoint>,
+    count: usize,
+}
+
+impl<F, Fut> Flusher<F>
+where
+    F: FnMut(Checkpoint) -> Fut,
+{
+    fn flush(&mut self) -> Option<Fut> {
+        self.count = 0;
+        self.pending.take().map(&mut self.persist)
+    }
+}
+
+/// Persists the pending checkpoint of a flusher when dropped.
+struct FlushOnDrop<F, Fut>
+where
+    F: FnMut(Checkpoint) -> Fut,
+    Fut: Future<Output = ()> + Send + 'static,
+{
+    flusher: Flusher<F>,
+}
+
+impl<F, Fut> Drop for FlushOnDrop<F, Fut>
+where
+    F: FnMut(Checkpoint) -> Fut,
+    Fut: Future<Output = ()> + Send + 'static,
+{
+    fn drop(&mut self) {
+        if let Some(flush) = self.flusher.flush() {
+            match tokio::runtime::Handle::try_current() {
+                Ok(runtime) => {
+                    runtime.spawn(flush);
+                }
+
+                Err(_) => warn!("Subscription dropped outside a runtime, last checkpoint lost"),
+            }
+        }
+    }
+}
+
+fn checkpoint_of(event: &SubEvent) -> Option<Checkpoint> {
+    match event {
+        SubEvent::EventAppeared(event) => Some(Checkpoint::from_event(event)),
+        SubEvent::RawEventAppeared(raw) => Some(Checkpoint::from_event(&raw.event)),
+        SubEvent::Checkpoint(position) => Some(Checkpoint::new(*position)),
+        _ => None,
+    }
+}
+
+pub(crate) fn debounce<'a, F, Fut>(
+    clock: Arc<dyn Clock>,
+    mut subscription: BoxStream<'a, crate::Result<SubEvent>>,
+    options: CheckpointFlushOptions,
+    persist: F,
+) -> BoxStream<'a, crate::Result<SubEvent>>
+where
+    F: FnMut(Checkpoint) -> Fut + Send + 'a,
+    Fut: Future<Output = ()> + Send + 'static,
+{
+    let stream = async_stream::stream! {
+        let mut state = FlushOnDrop {
+            flusher: Flusher {
+                persist,
+                pending: None,
+                count: 0,
+            },
+        };
+        let mut timer: Option<BoxFuture<'static, ()>> = None;
+
+        loop {
+            let next = match timer.as_mut() {
+                Some(sleep) => match future::select(subscription.next(), sleep).await {
+                    Either::Left((item, _)) => Some(item),
+                    Either::Right(_) => None,
+                },
+                None => Some(subscription.next().await),
+            };
+
+            let item = match next {
+                Some(item) => item,
+                None => {
+                    timer = None;
+
+                    if let Some(flush) = state.flusher.flush() {
+                        flush.await;
+                    }
+
+                    continue;
+                }
+            };
+
+            let item = match item {
+                Some(item) => item,
+                None => break,
+            };
+
+            let checkpoint = item.as_ref().ok().and_then(checkpoint_of);
+
+            yield item;
+
+            // The consumer asked for the next item, so it's done with this
+            // one.
+            if checkpoint.is_some() {
+                state.flusher.pending = checkpoint;
+                state.flusher.count += 1;
+
+                if state.flusher.count >= options.max_pending {
+                    timer = None;
+
+                    if let Some(flush) = state.flusher.flush() {
+                        flush.await;
+                    }
+                } else if timer.is_none() {
+                    timer = Some(clock.sleep(options.flush_interval));
+                }
+            }
+        }
+
+        if let Some(flush) = state.flusher.flush() {
+            flush.await;
+        }
+    };
+
+    Box::pin(stream)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::debounce;
+    use crate::clock::TestClock;
+    use crate::options::checkpoint_flush::CheckpointFlushOptions;
+    use crate::types::{Checkpoint, Position, SubEvent};
+    use futures::stream::{self, StreamExt};
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+
+    fn checkpoints(count: u64) -> Vec<crate::Result<SubEvent>> {
+        (1..=count)
+            .map(|commit| {
+                Ok(SubEvent::Checkpoint(Position {
+                    commit,
+                    prepare: commit,
+                }))
+            })
+            .collect()
+    }
+
+    fn recorder() -> (
+        Arc<Mutex<Vec<u64>>>,
+        impl FnMut(Checkpoint) -> futures::future::Ready<()> + Send,
+    ) {
+        let persisted = Arc::new(Mutex::new(Vec::new()));
+        let recorded = persisted.clone();
+        let persist = move |checkpoint: Checkpoint| {
+            recorded.lock().unwrap().push(checkpoint.position.commit);
+            futures::future::ready(())
+        };
+
+        (persisted, persist)
+    }
+
+    #[tokio::test]
+    async fn every_n_positions_the_latest_is_persisted() {
+        let (persisted, persist) = recorder();
+        let options = CheckpointFlushOptions::default()
+            .checkpoint_flush_interval(Duration::from_secs(3600))
+            .max_pending(3);
+        let events = stream::iter(checkpoints(7)).boxed();
+        let sub = debounce(Arc::new(TestClock::new()), events, options, persist);
+
+        assert_eq!(sub.count().await, 7);
+        assert_eq!(*persisted.lock().unwrap(), vec![3, 6, 7]);
+    }
+
+    #[tokio::test]
+    async fn pending_position_is_persisted_after_the_interval() {
+        let (persisted, persist) = recorder();
+        let clock = TestClock::new();
+        let options = CheckpointFlushOptions::default()
+            .checkpoint_flush_interval(Duration::from_secs(1))
+            .max_pending(100);
+        let events = stream::iter(checkpoints(2))
+            .chain(stream::pending())
+            .boxed();
+        let mut sub = debounce(Arc::new(clock.clone()), events, options, persist);
+
+        sub.next().await;
+        sub.next().await;
+
+        let waiting = tokio::spawn(async move {
+            sub.next().await;
+        });
+
+        while clock.pending_sleeps() == 0 {
+            tokio::task::yield_now().await;
+        }
+
+        assert!(persisted.lock().unwrap().is_empty());
+
+        clock.advance(Duration::from_secs(1));
+
+        while persisted.lock().unwrap().is_empty() {
+            tokio::task::yield_now().await;
+        }
+
+        assert_eq!(*persisted.lock().unwrap(), vec![2]);
+
+        waiting.abort();
+    }
+
+    #[tokio::test]
+    async fn dropped_subscription_flushes_the_latest_handled() {
+        let (persisted, persist) = recorder();
+        let events = stream::iter(checkpoints(3))
+            .chain(stream::pending())
+            .boxed();
+        let mut sub = debounce(
+            Arc::new(TestClock::new()),
+            events,
+            Default::default(),
+            persist,
+        );
+
+        for _ in 0..3 {
+            sub.next().await;
+        }
+
+        // The consumer may have failed handling the third position.
+        drop(sub);
+        tokio::task::yield_now().await;
+
+        assert_eq!(*persisted.lock().unwrap(), vec![2]);
+    }
+}
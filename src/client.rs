@@ -1,22 +1,49 @@
+use crate::catch_up::{self, CatchUpSubscription};
+use crate::checkpoint_flush;
+use crate::event_store::client::streams::append_req::options::ExpectedStreamRevision;
+use crate::multi_stream::{
+    MultiStreamWrite, PartialWriteError, StreamWriteFailure, StreamWriteResult,
+};
+use crate::options::catch_up::CatchUpOptions;
+use crate::options::checkpoint_flush::CheckpointFlushOptions;
 use crate::options::persistent_subscription::PersistentSubscriptionOptions;
 use crate::options::read_all::ReadAllOptions;
 use crate::options::read_stream::ReadStreamOptions;
 use crate::options::subscribe_to_stream::SubscribeToStreamOptions;
+use crate::projection_client::ProjectionClient;
+use crate::read_cache::{ReadCache, ReadKey};
+use crate::stream_locks::StreamLocks;
+use crate::types::{
+    EnsurePersistentSubscriptionResult, PersistentSubscriptionCheckpoint,
+    PersistentSubscriptionConfig, PersistentSubscriptionGroupConfig,
+};
 use crate::{
-    commands, ConnectToPersistentSubscription, DeletePersistentSubscriptionOptions,
-    DeleteStreamOptions, Position, ReadResult, StreamMetadata, SubEvent, SubscribeToAllOptions,
-    SubscriptionRead, SubscriptionWrite, ToCount, WriteResult, WrongExpectedVersion,
+    commands, AckHandle, AppendOrCreateResult, Checkpoint, Clock, ClusterTopology,
+    ConnectToPersistentSubscription, Credentials, DeletePersistentSubscriptionOptions,
+    DeleteResult, DeleteStreamOptions, Endpoint, ExpectedRevision, NodeRole, OperationId,
+    OperationListener, OperationStats, Position, ReadAllEvent, ReadEventStatus, ReadResult,
+    ResolvedEvent, RetryPolicy, ServerTimeSkew, StreamAcl, StreamMetadata, StreamPosition,
+    SubEvent, SubmittedOperation, SubscribeToAllOptions, SubscriptionDropReason, SubscriptionId,
+    SubscriptionInfo, SubscriptionRead, SubscriptionWrite, SystemClock, SystemSettings,
+    ThrottleState, ToCount, WriteResult, WrongExpectedVersion,
 };
 use crate::{
     grpc::{ClientSettings, GrpcClient},
-    Single,
+    All, Single,
 };
 use crate::{
     options::append_to_stream::{AppendToStreamOptions, ToEvents},
     EventData,
 };
+use futures::future::{self, Either};
 use futures::stream::BoxStream;
-use futures::TryStreamExt;
+use futures::{StreamExt, TryStreamExt};
+use std::future::Future;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Page size used by [`Client::read_all_last`] when reading many events.
+const READ_ALL_BACKWARD_PAGE_SIZE: usize = 500;
 
 /// Represents a client to a single node. `Client` maintains a full duplex
 /// communication to EventStoreDB.
@@ -27,16 +54,315 @@ use futures::TryStreamExt;
 pub struct Client {
     client: GrpcClient,
     settings: ClientSettings,
+    read_cache: Arc<ReadCache>,
+    stream_locks: Arc<StreamLocks>,
+}
+
+/// Creates a [`Client`] combining a shared topology, a clock, a retry policy
+/// and a listener, see [`Client::builder`].
+///
+/// ```no_run
+/// # use eventstore::{Client, ClusterTopology, SettingsRetryPolicy};
+/// # use std::sync::Arc;
+/// # async fn doc() -> Result<(), Box<dyn std::error::Error>> {
+/// let settings: eventstore::ClientSettings = "esdb://node1:2113,node2:2113".parse()?;
+/// let client = Client::builder(settings.clone())
+///     .topology(ClusterTopology::new(settings))
+///     .retry_policy(Arc::new(SettingsRetryPolicy))
+///     .connect()
+///     .await?;
+/// # Ok(())
+/// # }
+/// ```
+pub struct ClientBuilder {
+    settings: ClientSettings,
+    topology: Option<ClusterTopology>,
+    clock: Option<Arc<dyn Clock>>,
+    retry_policy: Option<Arc<dyn RetryPolicy>>,
+    listener: Option<Arc<dyn OperationListener>>,
+}
+
+impl ClientBuilder {
+    /// Shares the cluster discovery with every other client created with the
+    /// same topology. The topology is only used when connecting to a cluster.
+    pub fn topology(self, topology: ClusterTopology) -> Self {
+        ClientBuilder {
+            topology: Some(topology),
+            ..self
+        }
+    }
+
+    /// Relies on the given clock for the timeout, backoff and reconnection
+    /// logic. Meant for tests, see [`TestClock`](crate::TestClock).
+    pub fn clock(self, clock: Arc<dyn Clock>) -> Self {
+        ClientBuilder {
+            clock: Some(clock),
+            ..self
+        }
+    }
+
+    /// Decides with the given policy whether the operations the server
+    /// didn't handle are sent again. By default, the client follows its
+    /// settings, see [`SettingsRetryPolicy`](crate::SettingsRetryPolicy).
+    pub fn retry_policy(self, policy: Arc<dyn RetryPolicy>) -> Self {
+        ClientBuilder {
+            retry_policy: Some(policy),
+            ..self
+        }
+    }
+
+    /// Notifies the given listener when the operations of the client are
+    /// issued, sent again and completed.
+    pub fn listener(self, listener: Arc<dyn OperationListener>) -> Self {
+        ClientBuilder {
+            listener: Some(listener),
+            ..self
+        }
+    }
+
+    /// Creates the client. The connection is established lazily, the first
+    /// operation waiting for the node discovery.
+    pub async fn build(self) -> Result<Client, Box<dyn std::error::Error>> {
+        let clock = self.clock.unwrap_or_else(|| Arc::new(SystemClock));
+        let mut client = GrpcClient::create(self.settings.clone(), self.topology, clock).await?;
+
+        if let Some(policy) = self.retry_policy {
+            client = client.with_retry_policy(policy);
+        }
+
+        if let Some(listener) = self.listener {
+            client = client.with_listener(listener);
+        }
+
+        Ok(Client::from_parts(client, self.settings))
+    }
+
+    /// Creates the client and waits for it to connect, see
+    /// [`Client::connect`].
+    pub async fn connect(self) -> Result<Client, Box<dyn std::error::Error>> {
+        let client = self.build().await?;
+
+        client.client.warm_up().await?;
+
+        Ok(client)
+    }
 }
 
 impl Client {
-    /// Creates a gRPC client to an EventStoreDB database.
+    fn from_parts(client: GrpcClient, settings: ClientSettings) -> Self {
+        let read_cache = ReadCache::new(settings.read_cache_size, settings.read_cache_ttl);
+
+        Client {
+            client,
+            settings,
+            read_cache: Arc::new(read_cache),
+            stream_locks: Arc::new(StreamLocks::default()),
+        }
+    }
+
+    /// Creates a gRPC client to an EventStoreDB database. The connection is
+    /// established lazily, the first operation waiting for the node discovery.
     pub async fn new(settings: ClientSettings) -> Result<Self, Box<dyn std::error::Error>> {
-        let client = GrpcClient::create(settings.clone()).await?;
+        Client::builder(settings).build().await
+    }
+
+    /// Starts configuring a client whose topology, clock, retry policy or
+    /// listener differ from the defaults. See [`ClientBuilder`].
+    pub fn builder(settings: ClientSettings) -> ClientBuilder {
+        ClientBuilder {
+            settings,
+            topology: None,
+            clock: None,
+            retry_policy: None,
+            listener: None,
+        }
+    }
+
+    /// Creates a gRPC client and waits for it to connect: the node discovery
+    /// runs and a first request is sent to the selected node before the
+    /// client is returned. Fails if that takes longer than the
+    /// `connectTimeout` setting.
+    pub async fn connect(settings: ClientSettings) -> Result<Self, Box<dyn std::error::Error>> {
+        Client::builder(settings).connect().await
+    }
+
+    /// Creates a gRPC client whose connection is established by the first
+    /// operation. Same as [`Client::new`].
+    pub async fn connect_lazy(
+        settings: ClientSettings,
+    ) -> Result<Self, Box<dyn std::error::Error>> {
+        Client::new(settings).await
+    }
+
+    /// Creates a gRPC client sharing its cluster discovery with every other
+    /// client created with the same topology. The topology is only used
+    /// when connecting to a cluster. See [`ClientBuilder::topology`].
+    pub async fn with_topology(
+        settings: ClientSettings,
+        topology: ClusterTopology,
+    ) -> Result<Self, Box<dyn std::error::Error>> {
+        Client::builder(settings).topology(topology).build().await
+    }
 
-        Ok(Client { client, settings })
+    /// Creates a gRPC client whose timeout and backoff logic relies on the
+    /// given clock. See [`ClientBuilder::clock`].
+    pub async fn with_clock(
+        settings: ClientSettings,
+        clock: Arc<dyn Clock>,
+    ) -> Result<Self, Box<dyn std::error::Error>> {
+        Client::builder(settings).clock(clock).build().await
     }
-    /// Sends events to a given stream.
+
+    /// Creates a gRPC client deciding with the given policy whether the
+    /// operations the server didn't handle are sent again. See
+    /// [`ClientBuilder::retry_policy`].
+    pub async fn with_retry_policy(
+        settings: ClientSettings,
+        policy: Arc<dyn RetryPolicy>,
+    ) -> Result<Self, Box<dyn std::error::Error>> {
+        Client::builder(settings).retry_policy(policy).build().await
+    }
+
+    /// Creates a gRPC client notifying the given listener when its
+    /// operations are issued, sent again and completed. See
+    /// [`ClientBuilder::listener`].
+    pub async fn with_listener(
+        settings: ClientSettings,
+        listener: Arc<dyn OperationListener>,
+    ) -> Result<Self, Box<dyn std::error::Error>> {
+        Client::builder(settings).listener(listener).build().await
+    }
+
+    /// Returns the current state of the adaptive throttling the client engages
+    /// when the server reports being too busy.
+    pub fn throttle_state(&self) -> ThrottleState {
+        self.client.throttle_state()
+    }
+
+    /// Returns the node the client is connected to, `None` while
+    /// disconnected. It changes on reconnection and failover.
+    pub fn current_endpoint(&self) -> Option<Endpoint> {
+        self.client.current_node().map(|node| node.endpoint)
+    }
+
+    /// Returns the role of the cluster node the client is connected to, as
+    /// last reported by gossip. `None` while disconnected, when connected to
+    /// a single node or when the node is in a transient state.
+    pub fn current_node_role(&self) -> Option<NodeRole> {
+        self.client.current_node().and_then(|node| node.role)
+    }
+
+    /// Lists the subscription sessions open through this client, persistent
+    /// ones included, ordered by opening.
+    pub fn subscriptions(&self) -> Vec<SubscriptionInfo> {
+        self.client.subscriptions().list(self.client.clock().now())
+    }
+
+    /// Cancels a subscription session: the server is unsubscribed from and
+    /// the subscription stream fails with [`crate::Error::Cancelled`]. The
+    /// write half of a persistent subscription is closed as well, dropping
+    /// the acks and nacks sent afterwards. Returns `false` if the session
+    /// isn't open anymore.
+    pub fn cancel_subscription(&self, id: SubscriptionId) -> bool {
+        self.client.subscriptions().cancel(id)
+    }
+
+    /// Cancels every open subscription session, see
+    /// [`Client::cancel_subscription`]. Returns how many sessions were
+    /// cancelled.
+    pub fn cancel_subscriptions(&self) -> usize {
+        self.client.subscriptions().cancel_all()
+    }
+
+    /// Measures the round-trip time to the node the client is connected to,
+    /// the leader on a cluster by default. The measure sends a gossip
+    /// request, which has no side effect on the server, and isn't accounted
+    /// in [`Client::operation_stats`].
+    pub async fn ping(&self) -> crate::Result<Duration> {
+        self.client.ping().await
+    }
+
+    /// Returns the last skew measured between the clock of the node the
+    /// client is connected to and the local one. `None` until measured or
+    /// when the `serverTimeSkewThreshold` setting is disabled.
+    pub fn server_time_skew(&self) -> Option<ServerTimeSkew> {
+        self.client.server_time_skew()
+    }
+
+    /// Submits an operation, returning it along with its identifier before
+    /// it completes. Every request the operation sends carries that
+    /// identifier in its `correlation-id` header and the client log lines
+    /// about the operation mention it.
+    ///
+    /// ```no_run
+    /// # use eventstore::{Client, EventData};
+    /// # async fn f(client: Client) -> Result<(), Box<dyn std::error::Error>> {
+    /// let event = EventData::binary("ping", Default::default());
+    /// let operation = client.submit(|client| async move {
+    ///     client
+    ///         .append_to_stream("pings", &Default::default(), event)
+    ///         .await
+    /// });
+    ///
+    /// println!("Appending to pings as operation {}", operation.id());
+    /// let _ = operation.await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn submit<F, Fut, A>(&self, operation: F) -> SubmittedOperation<A>
+    where
+        F: FnOnce(Client) -> Fut,
+        Fut: Future<Output = crate::Result<A>> + Send + 'static,
+    {
+        SubmittedOperation::new(operation(self.clone()))
+    }
+
+    /// Like [`Client::submit`], identifying the operation with the given id
+    /// instead of a generated one, to match the server logs with an id the
+    /// caller already traces.
+    pub fn submit_with_id<F, Fut, A>(
+        &self,
+        id: impl Into<OperationId>,
+        operation: F,
+    ) -> SubmittedOperation<A>
+    where
+        F: FnOnce(Client) -> Fut,
+        Fut: Future<Output = crate::Result<A>> + Send + 'static,
+    {
+        SubmittedOperation::with_id(id.into(), operation(self.clone()))
+    }
+
+    /// Returns a snapshot of the counters of every kind of operation issued
+    /// by the client so far.
+    pub fn operation_stats(&self) -> OperationStats {
+        self.client.operation_stats()
+    }
+
+    /// Sends a request built with the protocol messages of
+    /// [`crate::unstable`]. `action` gets a channel to the node the client is
+    /// connected to and the default credentials of the client, and may be
+    /// called again when the operation is retried, after a failover for
+    /// instance. See the caveats of [`crate::unstable`].
+    #[cfg(feature = "unstable")]
+    pub async fn execute_raw<F, Fut, A>(&self, action: F) -> crate::Result<A>
+    where
+        F: Fn(crate::unstable::Channel, Option<Credentials>) -> Fut + Send + Sync,
+        Fut: Future<Output = Result<A, crate::unstable::Status>> + Send,
+        A: Send,
+    {
+        let credentials = self.client.default_credentials();
+
+        self.client
+            .execute(crate::OperationKind::Raw, |handle| {
+                action(handle.channel.clone(), credentials.clone())
+            })
+            .await
+    }
+
+    /// Sends events to a given stream. What an append without any event does
+    /// depends on the `emptyAppend` setting, see [`crate::EmptyAppendBehavior`].
+    /// With the `serializeStreamWrites` setting, the append first waits for
+    /// the appends to the same stream this client issued before.
     pub async fn append_to_stream<StreamName, Events>(
         &self,
         stream_name: StreamName,
@@ -47,7 +373,183 @@ impl Client {
         StreamName: AsRef<str>,
         Events: ToEvents + 'static,
     {
-        commands::append_to_stream(&self.client, stream_name, options, events.into_events()).await
+        let stream = stream_name.as_ref();
+
+        if stream.starts_with('$') && !self.settings.allow_system_stream_writes {
+            warn!("Rejected append to system stream {}", stream);
+
+            return Err(crate::Error::SystemStreamWriteForbidden {
+                stream: stream.to_string(),
+            });
+        }
+
+        let _lock = if self.settings.serialize_stream_writes {
+            Some(self.stream_locks.lock(stream).await)
+        } else {
+            None
+        };
+
+        if options.verify_no_prior_incarnation
+            && matches!(options.version, ExpectedStreamRevision::NoStream(_))
+        {
+            self.verify_no_prior_incarnation(stream, options).await?;
+        }
+
+        self.read_cache.invalidate(stream);
+
+        let result = if Events::STREAMED {
+            commands::append_streamed_events(&self.client, stream, options, events.into_events())
+                .await
+        } else {
+            commands::append_to_stream(&self.client, stream, options, events.into_events()).await
+        };
+
+        // A read running concurrently may have cached the stream as it was
+        // before the write.
+        self.read_cache.invalidate(stream);
+
+        result
+    }
+
+    /// Issues the appends of a [`MultiStreamWrite`] one after the other,
+    /// stopping at the first one that fails. This isn't a transaction:
+    /// each append is atomic on its own, but the appends committed before a
+    /// failure stay committed. [`PartialWriteError`] reports which streams
+    /// were written, for the caller to compensate.
+    pub async fn append_to_streams(
+        &self,
+        write: MultiStreamWrite,
+    ) -> Result<Vec<StreamWriteResult>, PartialWriteError> {
+        let mut succeeded = Vec::with_capacity(write.writes.len());
+        let mut writes = write.writes.into_iter();
+
+        while let Some(next) = writes.next() {
+            let reason = match self
+                .append_to_stream(next.stream.as_str(), &next.options, next.events)
+                .await
+            {
+                Ok(Ok(result)) => {
+                    succeeded.push(StreamWriteResult {
+                        stream: next.stream,
+                        result,
+                    });
+
+                    continue;
+                }
+
+                Ok(Err(conflict)) => StreamWriteFailure::WrongExpectedVersion(conflict),
+                Err(e) => StreamWriteFailure::Error(e),
+            };
+
+            warn!(
+                "Multi-stream write failed on stream {} after {} stream(s) were written",
+                next.stream,
+                succeeded.len()
+            );
+
+            return Err(PartialWriteError {
+                succeeded,
+                failed: next.stream,
+                reason,
+                not_attempted: writes.map(|write| write.stream).collect(),
+            });
+        }
+
+        Ok(succeeded)
+    }
+
+    async fn verify_no_prior_incarnation(
+        &self,
+        stream: &str,
+        options: &AppendToStreamOptions,
+    ) -> crate::Result<()> {
+        let mut read_options = ReadStreamOptions::default().position(StreamPosition::End);
+
+        if let Some(credentials) = options.credentials.clone() {
+            read_options = read_options.authenticated(credentials);
+        }
+
+        // Bypasses the read cache, a stale metadata would defeat the check.
+        let result =
+            commands::read_stream(&self.client, &read_options, format!("$${}", stream), 1).await?;
+
+        let event = match result {
+            ReadResult::Ok(mut events) => events.try_next().await?,
+            ReadResult::StreamNotFound(_) => None,
+        };
+
+        let metadata = match event {
+            Some(event) => event
+                .get_original_event()
+                .as_json::<StreamMetadata>()
+                .map_err(|e| crate::Error::InternalParsingError(e.to_string()))?,
+            None => return Ok(()),
+        };
+
+        match metadata.truncate_before {
+            Some(truncate_before) if truncate_before > 0 => {
+                if truncate_before == i64::MAX as u64 {
+                    warn!("Stream {} has a prior incarnation, deleted", stream);
+                } else {
+                    warn!(
+                        "Stream {} has a prior incarnation, truncated before event {}",
+                        stream, truncate_before
+                    );
+                }
+
+                Err(crate::Error::PriorStreamIncarnation {
+                    stream: stream.to_string(),
+                    truncate_before,
+                })
+            }
+
+            _ => Ok(()),
+        }
+    }
+
+    /// Appends events to a stream that is expected not to exist yet, telling
+    /// if the write created it. If the stream already exists, the write fails
+    /// with a `WrongExpectedVersion` unless `append_if_exists` is set, in
+    /// which case the events are appended regardless of the stream revision.
+    /// The expected revision of `options` is ignored.
+    pub async fn append_or_create<StreamName, Events>(
+        &self,
+        stream_name: StreamName,
+        options: &AppendToStreamOptions,
+        events: Events,
+        append_if_exists: bool,
+    ) -> crate::Result<Result<AppendOrCreateResult, WrongExpectedVersion>>
+    where
+        StreamName: AsRef<str>,
+        Events: ToEvents + Clone + 'static,
+    {
+        let stream = stream_name.as_ref();
+        let create = options
+            .clone()
+            .expected_revision(ExpectedRevision::NoStream);
+
+        match self
+            .append_to_stream(stream, &create, events.clone())
+            .await?
+        {
+            Ok(result) => {
+                return Ok(Ok(AppendOrCreateResult {
+                    result,
+                    created: true,
+                }))
+            }
+
+            Err(e) if !append_if_exists => return Ok(Err(e)),
+            Err(_) => debug!("Stream {} already exists, appending to it", stream),
+        }
+
+        let append = options.clone().expected_revision(ExpectedRevision::Any);
+        let result = self.append_to_stream(stream, &append, events).await?;
+
+        Ok(result.map(|result| AppendOrCreateResult {
+            result,
+            created: false,
+        }))
     }
 
     // Sets a stream metadata.
@@ -63,8 +565,25 @@ impl Client {
         let event = EventData::json("$metadata", metadata)
             .map_err(|e| crate::Error::InternalParsingError(e.to_string()))?;
 
-        self.append_to_stream(format!("$${}", stream_name.as_ref()), options, event)
-            .await
+        let metadata_stream = format!("$${}", stream_name.as_ref());
+
+        self.read_cache.invalidate(stream_name.as_ref());
+        self.read_cache.invalidate(&metadata_stream);
+
+        let result = commands::append_to_stream(
+            &self.client,
+            metadata_stream.as_str(),
+            options,
+            event.into_events(),
+        )
+        .await;
+
+        // A read running concurrently may have cached the stream as it was
+        // before the write.
+        self.read_cache.invalidate(stream_name.as_ref());
+        self.read_cache.invalidate(&metadata_stream);
+
+        result
     }
 
     /// Reads events from a given stream. The reading can be done forward and
@@ -79,6 +598,10 @@ impl Client {
         StreamName: AsRef<str>,
         Count: ToCount<'static>,
     {
+        if self.read_cache.is_enabled() && count.to_count() != All.to_count() {
+            return self.read_stream_cached(stream_name, options, count).await;
+        }
+
         let result =
             commands::read_stream(&self.client, options, stream_name, count.to_count() as u64)
                 .await?;
@@ -90,58 +613,613 @@ impl Client {
                 Ok(ReadResult::Ok(stream))
             }
 
-            ReadResult::StreamNotFound(stream_name) => Ok(ReadResult::StreamNotFound(stream_name)),
-        }
+            ReadResult::StreamNotFound(stream_name) => Ok(ReadResult::StreamNotFound(stream_name)),
+        }
+    }
+
+    async fn read_stream_cached<StreamName, Count>(
+        &self,
+        stream_name: StreamName,
+        options: &ReadStreamOptions,
+        count: Count,
+    ) -> crate::Result<ReadResult<Count::Selection>>
+    where
+        StreamName: AsRef<str>,
+        Count: ToCount<'static>,
+    {
+        let key = ReadKey::new(stream_name.as_ref(), options, count.to_count());
+        let events = match self.read_cache.get(&key, self.client.clock().now()) {
+            Some(events) => events,
+            None => {
+                let result = commands::read_stream(
+                    &self.client,
+                    options,
+                    stream_name,
+                    count.to_count() as u64,
+                )
+                .await?;
+
+                let events: Vec<ResolvedEvent> = match result {
+                    ReadResult::Ok(stream) => stream.try_collect().await?,
+                    ReadResult::StreamNotFound(stream_name) => {
+                        return Ok(ReadResult::StreamNotFound(stream_name))
+                    }
+                };
+
+                self.read_cache
+                    .insert(key, &events, self.client.clock().now());
+
+                events
+            }
+        };
+
+        let stream: BoxStream<'static, crate::Result<ResolvedEvent>> =
+            Box::pin(futures::stream::iter(events.into_iter().map(Ok)));
+
+        Ok(ReadResult::Ok(count.select(stream).await?))
+    }
+
+    /// Reads events for the system stream `$all`. The reading can be done
+    /// forward and backward.
+    pub async fn read_all<Count>(
+        &self,
+        options: &ReadAllOptions,
+        count: Count,
+    ) -> crate::Result<Count::Selection>
+    where
+        Count: ToCount<'static>,
+    {
+        let stream = commands::read_all(&self.client, options, count.to_count() as u64).await?;
+
+        count.select(stream).await
+    }
+
+    /// Reads the `$all` stream with the server-side filter of the options,
+    /// reading up to `count` matching events. Along with the events, the
+    /// stream yields the checkpoints the server reports when the filter has
+    /// a [`crate::SubscriptionFilter::max`] search window, and the position
+    /// of the events skipped by [`ReadAllOptions::exclude_system_events`] or
+    /// [`ReadAllOptions::exclude_metadata`]. Starting a read
+    /// at the last position received, with [`ReadAllOptions::position`],
+    /// resumes a long filtered read without searching the same events again.
+    pub async fn read_all_filtered<'a>(
+        &self,
+        options: &ReadAllOptions,
+        count: u64,
+    ) -> crate::Result<BoxStream<'a, crate::Result<ReadAllEvent>>> {
+        commands::read_all_filtered(&self.client, options, count).await
+    }
+
+    /// Reads a stream backward from its end, newest events first, sending a
+    /// request every `page_size` events. Each page starts at the event number
+    /// preceding the last event delivered, and the read stops once the first
+    /// event of the stream is delivered. A stream that doesn't exist or is
+    /// deleted ends the read with [`crate::Error::StreamNotFound`] or
+    /// [`crate::Error::StreamDeleted`]. The position and direction of
+    /// `options` are ignored.
+    ///
+    /// Pages aren't cached, even if the read cache is enabled.
+    pub fn read_stream_backward<'a, StreamName>(
+        &self,
+        stream_name: StreamName,
+        options: &ReadStreamOptions,
+        page_size: usize,
+    ) -> BoxStream<'a, crate::Result<ResolvedEvent>>
+    where
+        StreamName: AsRef<str>,
+    {
+        let client = self.client.clone();
+        let stream_name = stream_name.as_ref().to_string();
+        let mut page_options = options.clone().position(StreamPosition::End);
+        let page_size = page_size.max(1);
+
+        let stream = async_stream::stream! {
+            loop {
+                let result = commands::read_stream(&client, &page_options, stream_name.as_str(), page_size as u64).await;
+                let mut page = match result {
+                    Ok(ReadResult::Ok(page)) => page,
+                    Ok(ReadResult::StreamNotFound(stream)) => {
+                        yield Err(crate::Error::StreamNotFound { stream });
+                        return;
+                    }
+                    Err(e) => {
+                        yield Err(e);
+                        return;
+                    }
+                };
+
+                let mut read = 0usize;
+                let mut last = None;
+
+                loop {
+                    let event = match page.try_next().await {
+                        Ok(Some(event)) => event,
+                        Ok(None) => break,
+                        Err(e) => {
+                            yield Err(e);
+                            return;
+                        }
+                    };
+
+                    read += 1;
+                    last = Some(event.get_original_event().revision);
+
+                    yield Ok(event);
+                }
+
+                match last {
+                    Some(revision) if read == page_size && revision > 0 => {
+                        page_options = page_options.position(StreamPosition::Position(revision - 1));
+                    }
+
+                    _ => break,
+                }
+            }
+        };
+
+        Box::pin(stream)
+    }
+
+    /// Reads `$all` backward from its end, sending a request every
+    /// `page_size` events. Each page starts where the previous one ended, so
+    /// an event is never skipped nor delivered twice. Unlike
+    /// [`Client::read_all`], the events excluded by the options are filtered
+    /// out after paging, so a page made of system events only doesn't end the
+    /// read early. The position of each event is given by
+    /// `get_original_event().position`. The position and direction of
+    /// `options` are ignored.
+    pub fn read_all_backward<'a>(
+        &self,
+        options: &ReadAllOptions,
+        page_size: usize,
+    ) -> BoxStream<'a, crate::Result<ResolvedEvent>> {
+        let client = self.client.clone();
+        let exclusion = options.exclusion;
+        let mut page_options = options.clone().position(StreamPosition::End).backwards();
+
+        page_options.exclusion = Default::default();
+
+        // A page of a single event could be the boundary event over and over.
+        let page_size = page_size.max(2);
+
+        let stream = async_stream::stream! {
+            let mut last: Option<Position> = None;
+
+            loop {
+                let mut page = match commands::read_all(&client, &page_options, page_size as u64).await {
+                    Ok(page) => page,
+                    Err(e) => {
+                        yield Err(e);
+                        return;
+                    }
+                };
+
+                let mut read = 0usize;
+
+                loop {
+                    let event = match page.try_next().await {
+                        Ok(Some(event)) => event,
+                        Ok(None) => break,
+                        Err(e) => {
+                            yield Err(e);
+                            return;
+                        }
+                    };
+
+                    read += 1;
+
+                    let position = event.get_original_event().position;
+
+                    // Depending on the server, a page can include the event it starts from.
+                    if matches!(last, Some(last) if position >= last) {
+                        continue;
+                    }
+
+                    last = Some(position);
+
+                    if !exclusion.excludes(&event) {
+                        yield Ok(event);
+                    }
+                }
+
+                match last {
+                    Some(position) if read == page_size => {
+                        page_options = page_options.position(StreamPosition::Position(position));
+                    }
+
+                    _ => break,
+                }
+            }
+        };
+
+        Box::pin(stream)
+    }
+
+    /// Reads the last `count` events of `$all`, most recent first. See
+    /// [`Client::read_all_backward`].
+    pub fn read_all_last<'a>(
+        &self,
+        options: &ReadAllOptions,
+        count: usize,
+    ) -> BoxStream<'a, crate::Result<ResolvedEvent>> {
+        let page_size = count.clamp(2, READ_ALL_BACKWARD_PAGE_SIZE);
+
+        Box::pin(self.read_all_backward(options, page_size).take(count))
+    }
+
+    /// Reads specific events scattered across streams, given as
+    /// `(stream, revision)` pairs. The reads are issued concurrently and the
+    /// results come back in input order. Each entry has its own result, so a
+    /// missing event doesn't fail the whole batch.
+    pub async fn read_events<StreamName>(
+        &self,
+        events: &[(StreamName, u64)],
+        options: &ReadStreamOptions,
+    ) -> Vec<crate::Result<ReadEventStatus<ResolvedEvent>>>
+    where
+        StreamName: AsRef<str>,
+    {
+        let reads = events
+            .iter()
+            .map(|(stream_name, revision)| self.read_event(stream_name, *revision, options));
+
+        futures::future::join_all(reads).await
+    }
+
+    /// Reads the event of a stream at the given revision. With
+    /// `resolve_link_tos` set in the options, a link event is resolved: the
+    /// `event` of the result is the linked event and `link` the link itself.
+    /// The position and direction of `options` are ignored.
+    pub async fn read_event<StreamName>(
+        &self,
+        stream_name: StreamName,
+        revision: u64,
+        options: &ReadStreamOptions,
+    ) -> crate::Result<ReadEventStatus<ResolvedEvent>>
+    where
+        StreamName: AsRef<str>,
+    {
+        let options = options
+            .clone()
+            .forwards()
+            .position(StreamPosition::Position(revision));
+
+        match self.read_stream(stream_name, &options, Single).await {
+            Ok(ReadResult::StreamNotFound(_)) => Ok(ReadEventStatus::NoStream),
+            Ok(ReadResult::Ok(Some(event))) if event.get_original_event().revision == revision => {
+                Ok(ReadEventStatus::Success(event))
+            }
+            Ok(ReadResult::Ok(_)) => Ok(ReadEventStatus::NotFound),
+            Err(crate::Error::StreamDeleted { .. }) => Ok(ReadEventStatus::Deleted),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Reads every event of the given category, for example `orders` for all
+    /// `orders-*` streams. It reads the `$ce-<category>` stream maintained by
+    /// the `$by_category` system projection and always resolves its links to
+    /// the underlying events.
+    ///
+    /// If the category stream doesn't exist, either no event was ever written
+    /// in that category or the `$by_category` projection isn't running. In
+    /// both cases, a [`crate::Error::SystemProjectionStreamNotFound`] error is
+    /// returned. With the `autoEnableSystemProjections` setting, the client
+    /// first enables the `$by_category` projection if it isn't running and reads
+    /// again, which finds the events the projection already processed.
+    pub async fn read_category<Category, Count>(
+        &self,
+        category: Category,
+        options: &ReadStreamOptions,
+        count: Count,
+    ) -> crate::Result<Count::Selection>
+    where
+        Category: AsRef<str>,
+        Count: ToCount<'static> + Copy,
+    {
+        self.read_system_projection_stream(
+            "$by_category",
+            format!("$ce-{}", category.as_ref()),
+            options,
+            count,
+        )
+        .await
+    }
+
+    /// Reads every event of the given type across all streams. It reads the
+    /// `$et-<event_type>` stream maintained by the `$by_event_type` system
+    /// projection and always resolves its links to the underlying events.
+    ///
+    /// If the event type stream doesn't exist, either no event of that type
+    /// was ever written or the `$by_event_type` projection isn't running. In
+    /// both cases, a [`crate::Error::SystemProjectionStreamNotFound`] error is
+    /// returned. With the `autoEnableSystemProjections` setting, the client
+    /// first enables the `$by_event_type` projection if it isn't running and reads
+    /// again, which finds the events the projection already processed.
+    pub async fn read_event_type<EventType, Count>(
+        &self,
+        event_type: EventType,
+        options: &ReadStreamOptions,
+        count: Count,
+    ) -> crate::Result<Count::Selection>
+    where
+        EventType: AsRef<str>,
+        Count: ToCount<'static> + Copy,
+    {
+        self.read_system_projection_stream(
+            "$by_event_type",
+            format!("$et-{}", event_type.as_ref()),
+            options,
+            count,
+        )
+        .await
+    }
+
+    /// Lists the ids of the existing streams. It reads the `$streams` stream
+    /// maintained by the `$streams` system projection, skipping metadata
+    /// streams and the streams that were deleted. `options` position allows
+    /// resuming the listing where a previous page stopped.
+    ///
+    /// If the `$streams` stream doesn't exist, the `$streams` projection isn't
+    /// running and a [`crate::Error::SystemProjectionStreamNotFound`] error is
+    /// returned.
+    pub async fn list_streams(
+        &self,
+        options: &ReadStreamOptions,
+    ) -> crate::Result<BoxStream<'static, crate::Result<String>>> {
+        let stream = self
+            .read_system_projection_stream("$streams", "$streams".to_string(), options, All)
+            .await?;
+
+        let stream = stream.try_filter_map(|event| async move {
+            let stream_id = match event.event {
+                Some(event) if !event.stream_id.starts_with("$$") => Some(event.stream_id),
+                _ => None,
+            };
+
+            Ok(stream_id)
+        });
+
+        Ok(Box::pin(stream))
+    }
+
+    async fn read_system_projection_stream<Count>(
+        &self,
+        projection: &str,
+        stream_name: String,
+        options: &ReadStreamOptions,
+        count: Count,
+    ) -> crate::Result<Count::Selection>
+    where
+        Count: ToCount<'static> + Copy,
+    {
+        let options = options.clone().resolve_link_tos();
+
+        if let ReadResult::Ok(selection) = self
+            .read_stream(stream_name.as_str(), &options, count)
+            .await?
+        {
+            return Ok(selection);
+        }
+
+        if self.settings.auto_enable_system_projections
+            && self.enable_system_projection(projection, &options).await?
+        {
+            if let ReadResult::Ok(selection) = self
+                .read_stream(stream_name.as_str(), &options, count)
+                .await?
+            {
+                return Ok(selection);
+            }
+        }
+
+        Err(crate::Error::SystemProjectionStreamNotFound {
+            projection: projection.to_string(),
+            stream: stream_name,
+        })
+    }
+
+    /// Enables a system projection that isn't running, returning `false` if
+    /// it was already running.
+    async fn enable_system_projection(
+        &self,
+        projection: &str,
+        options: &ReadStreamOptions,
+    ) -> crate::Result<bool> {
+        let credentials = match self.effective_credentials(options) {
+            Some(credentials) => credentials,
+            None => {
+                warn!(
+                    "Not enabling the {} system projection, it requires admin credentials",
+                    projection
+                );
+
+                return Ok(false);
+            }
+        };
+
+        let projections = ProjectionClient::from_parts(self.client.clone(), self.settings.clone());
+        let running = match projections
+            .get_status(projection, Some(credentials))
+            .await?
+        {
+            Some(status) => status.status.contains("Running"),
+            None => false,
+        };
+
+        if running {
+            return Ok(false);
+        }
+
+        projections.enable(projection, Some(credentials)).await?;
+        info!("Enabled the {} system projection", projection);
+
+        Ok(true)
+    }
+
+    /// Reads a stream metadata, that is the last event of its `$$` metadata
+    /// stream. A stream without metadata gets the default, empty, metadata.
+    /// The position and direction of `options` are ignored.
+    pub async fn get_stream_metadata<StreamName>(
+        &self,
+        stream_name: StreamName,
+        options: &ReadStreamOptions,
+    ) -> crate::Result<StreamMetadata>
+    where
+        StreamName: AsRef<str>,
+    {
+        let options = options.clone().backwards().position(StreamPosition::End);
+        let result = self
+            .read_stream(format!("$${}", stream_name.as_ref()), &options, Single)
+            .await?;
+
+        match result {
+            ReadResult::StreamNotFound(_) | ReadResult::Ok(None) => Ok(StreamMetadata::default()),
+            ReadResult::Ok(Some(event)) => event
+                .get_original_event()
+                .as_json::<StreamMetadata>()
+                .map_err(|e| crate::Error::InternalParsingError(e.to_string())),
+        }
+    }
+
+    /// Writes the default access control lists of the server to the
+    /// `$settings` stream. Writing `$settings` requires admin rights.
+    pub async fn set_default_acl(
+        &self,
+        options: &AppendToStreamOptions,
+        settings: SystemSettings,
+    ) -> crate::Result<Result<WriteResult, WrongExpectedVersion>> {
+        let event = EventData::json("$settings", settings)
+            .map_err(|e| crate::Error::InternalParsingError(e.to_string()))?;
+
+        let _lock = if self.settings.serialize_stream_writes {
+            Some(self.stream_locks.lock("$settings").await)
+        } else {
+            None
+        };
+
+        self.read_cache.invalidate("$settings");
+
+        let result =
+            commands::append_to_stream(&self.client, "$settings", options, event.into_events())
+                .await;
+
+        // A read running concurrently may have cached the access control
+        // lists as they were before the write.
+        self.read_cache.invalidate("$settings");
+
+        result
+    }
+
+    /// Reads the default access control lists of the server from the
+    /// `$settings` stream. If `$settings` was never written, an empty
+    /// `SystemSettings` is returned, meaning the server built-in defaults
+    /// apply.
+    pub async fn get_default_acl(
+        &self,
+        options: &ReadStreamOptions,
+    ) -> crate::Result<SystemSettings> {
+        let options = options.clone().position(StreamPosition::End);
+        let result = self.read_stream("$settings", &options, Single).await?;
+
+        match result {
+            ReadResult::Ok(Some(event)) => event
+                .get_original_event()
+                .as_json::<SystemSettings>()
+                .map_err(|e| crate::Error::InternalParsingError(e.to_string())),
+            ReadResult::Ok(None) | ReadResult::StreamNotFound(_) => Ok(SystemSettings::default()),
+        }
+    }
+
+    /// Returns the effective access control list of a stream. Role lists the
+    /// stream metadata doesn't define, or a missing metadata, resolve to the
+    /// default access control lists of the `$settings` stream. If
+    /// `$settings` can't be read, the server built-in defaults are used.
+    pub async fn stream_permissions<StreamName>(
+        &self,
+        stream_name: StreamName,
+        options: &ReadStreamOptions,
+    ) -> crate::Result<StreamAcl>
+    where
+        StreamName: AsRef<str>,
+    {
+        let stream = stream_name.as_ref();
+        let metadata = self.get_stream_metadata(stream, options).await?;
+        let settings = match self.get_default_acl(options).await {
+            Err(crate::Error::AccessDenied) => SystemSettings::default(),
+            other => other?,
+        };
+
+        Ok(settings.effective_acl(stream, metadata.acl))
     }
 
-    /// Reads events for the system stream `$all`. The reading can be done
-    /// forward and backward.
-    pub async fn read_all<Count>(
+    /// Checks if the credentials of `options`, or the default credentials of
+    /// the client, are allowed to read the stream. See [`StreamAcl::can_read`].
+    pub async fn can_read<StreamName>(
         &self,
-        options: &ReadAllOptions,
-        count: Count,
-    ) -> crate::Result<Count::Selection>
+        stream_name: StreamName,
+        options: &ReadStreamOptions,
+    ) -> crate::Result<bool>
     where
-        Count: ToCount<'static>,
+        StreamName: AsRef<str>,
     {
-        let stream = commands::read_all(&self.client, &options, count.to_count() as u64).await?;
+        let acl = self.stream_permissions(stream_name, options).await?;
 
-        count.select(stream).await
+        Ok(acl.can_read(self.effective_credentials(options)))
     }
 
-    /// Reads a stream metadata.
-    pub async fn get_stream_metadata<StreamName>(
+    /// Checks if the credentials of `options`, or the default credentials of
+    /// the client, are allowed to write to the stream. See
+    /// [`StreamAcl::can_write`].
+    pub async fn can_write<StreamName>(
         &self,
         stream_name: StreamName,
         options: &ReadStreamOptions,
-    ) -> crate::Result<StreamMetadata>
+    ) -> crate::Result<bool>
     where
         StreamName: AsRef<str>,
     {
-        let result = self
-            .read_stream(format!("$${}", stream_name.as_ref()), options, Single)
-            .await?;
+        let acl = self.stream_permissions(stream_name, options).await?;
 
-        match result {
-            ReadResult::StreamNotFound(_) => Ok(StreamMetadata::default()),
-            ReadResult::Ok(event) => event
-                .expect("to be defined")
-                .get_original_event()
-                .as_json::<StreamMetadata>()
-                .map_err(|e| crate::Error::InternalParsingError(e.to_string())),
-        }
+        Ok(acl.can_write(self.effective_credentials(options)))
+    }
+
+    fn effective_credentials<'a>(
+        &'a self,
+        options: &'a ReadStreamOptions,
+    ) -> Option<&'a Credentials> {
+        options
+            .credentials
+            .as_ref()
+            .or_else(|| self.settings.default_authenticated_user().as_ref())
     }
 
     /// Deletes a given stream. By default, the server performs a soft delete.
+    ///
+    /// Like a write, the delete honors the expected revision of the options,
+    /// a conflict being reported as a [`WrongExpectedVersion`]. Soft
+    /// deleting a stream that is already soft deleted succeeds with
+    /// `ExpectedRevision::Any` but fails with [`crate::Error::StreamDeleted`]
+    /// with `ExpectedRevision::StreamExists`, the stream no longer existing
+    /// from the server point of view.
     pub async fn delete_stream<StreamName>(
         &self,
         stream_name: StreamName,
         options: &DeleteStreamOptions,
-    ) -> crate::Result<Option<Position>>
+    ) -> crate::Result<Result<DeleteResult, WrongExpectedVersion>>
     where
         StreamName: AsRef<str>,
     {
-        commands::delete_stream(&self.client, stream_name, options).await
+        let stream = stream_name.as_ref();
+
+        self.read_cache.invalidate(stream);
+
+        let result = commands::delete_stream(&self.client, stream, options).await;
+
+        // A read running concurrently may have cached the stream as it was
+        // before the delete.
+        self.read_cache.invalidate(stream);
+
+        result
     }
 
     /// Subscribes to a given stream. This kind of subscription specifies a
@@ -166,12 +1244,14 @@ impl Client {
     where
         StreamName: AsRef<str>,
     {
-        match options.retry.as_ref().cloned() {
-            None => commands::subscribe_to_stream(&self.client, stream_name, options).await,
+        let name = stream_name.as_ref().to_string();
+        let subscription = match options.retry.as_ref().cloned() {
+            None => commands::subscribe_to_stream(&self.client, stream_name, options).await?,
             Some(retry) => {
-                let stream_name = stream_name.as_ref().to_string();
+                let stream_name = name.clone();
                 let mut attempt_count = 1usize;
                 let mut offset = options.position;
+                let mut boundary = Boundary::resuming_from(offset);
                 let client = self.client.clone();
                 let mut options = options.clone();
                 let result = async_stream::stream! {
@@ -187,27 +1267,73 @@ impl Client {
 
                                 error!("Subscription: attempt ({}/{}) failure, cause: {}", attempt_count, retry.limit, e);
                                 attempt_count += 1;
-                                tokio::time::sleep(retry.delay).await;
+                                client
+                                    .clock()
+                                    .sleep(client.settings().jittered(retry.delay))
+                                    .await;
                             }
                             Ok(mut stream) => {
                                 loop {
                                     match stream.try_next().await {
-                                        Ok(sub_event) => {
-                                            let sub_event = sub_event.expect("to be defined");
+                                        Ok(Some(sub_event)) => {
                                             match sub_event {
                                                 crate::types::SubEvent::EventAppeared(event) => {
-                                                    offset = crate::types::StreamPosition::Position(event.get_original_event().revision);
-                                                    yield Ok(crate::types::SubEvent::EventAppeared(event));
+                                                    let revision = event.get_original_event().revision;
+
+                                                    if boundary.delivered(revision) {
+                                                        offset = crate::types::StreamPosition::Position(revision);
+                                                        yield Ok(crate::types::SubEvent::EventAppeared(event));
+                                                    }
+                                                }
+
+                                                crate::types::SubEvent::RawEventAppeared(raw) => {
+                                                    let revision = raw.event.get_original_event().revision;
+
+                                                    if boundary.delivered(revision) {
+                                                        offset = crate::types::StreamPosition::Position(revision);
+                                                        yield Ok(crate::types::SubEvent::RawEventAppeared(raw));
+                                                    }
+                                                }
+
+                                                crate::types::SubEvent::CaughtUp => {
+                                                    // No need to track the catch-up phase again when resubscribing.
+                                                    options.notify_caught_up = false;
+                                                    yield Ok(crate::types::SubEvent::CaughtUp);
                                                 }
 
                                                 ignored => yield Ok(ignored),
                                             }
                                         }
+                                        Ok(None) => {
+                                            attempt_count = 1;
+                                            options = options.position(offset);
+
+                                            error!("Subscription ended by the server. Reconnecting");
+                                            yield Ok(crate::types::SubEvent::Resubscribing);
+                                            break;
+                                        }
+                                        Err(crate::Error::Cancelled) => {
+                                            yield Err(crate::Error::Cancelled);
+                                            return;
+                                        }
+                                        Err(crate::Error::SubscriptionDropped(reason)) if !reason.is_transient() => {
+                                            error!("Subscription dropped cause: {}. Giving up", reason);
+
+                                            yield Err(crate::Error::SubscriptionDropped(reason));
+                                            return;
+                                        }
+                                        Err(e) if e.ends_subscription() => {
+                                            error!("Subscription failed: {}. Giving up", e);
+
+                                            yield Err(e);
+                                            return;
+                                        }
                                         Err(e) => {
                                             attempt_count = 1;
                                             options = options.position(offset);
 
                                             error!("Subscription dropped cause: {}. Reconnecting", e);
+                                            yield Ok(crate::types::SubEvent::Resubscribing);
                                             break;
                                         }
                                     }
@@ -219,9 +1345,205 @@ impl Client {
 
                 let result: BoxStream<crate::Result<SubEvent>> = Box::pin(result);
 
-                Ok(result)
+                result
+            }
+        };
+
+        if !self.read_cache.is_enabled() {
+            return Ok(subscription);
+        }
+
+        let read_cache = self.read_cache.clone();
+        let subscription = subscription.inspect_ok(move |event| {
+            if let SubEvent::EventAppeared(_) | SubEvent::RawEventAppeared(_) = event {
+                read_cache.invalidate(&name);
+            }
+        });
+
+        Ok(Box::pin(subscription))
+    }
+
+    /// Subscribes to the events written to a stream from now on, without
+    /// replaying its history. The client reads the last event number of the
+    /// stream first, then subscribes from it: an event written in between is
+    /// delivered by the subscription catch-up, so none is missed nor
+    /// delivered twice. The position of `options` is ignored.
+    pub async fn subscribe_from_end<'a, StreamName>(
+        &self,
+        stream_name: StreamName,
+        options: &SubscribeToStreamOptions,
+    ) -> crate::Result<BoxStream<'a, crate::Result<SubEvent>>>
+    where
+        StreamName: AsRef<str>,
+    {
+        let stream = stream_name.as_ref();
+        let mut read_options = ReadStreamOptions::default().position(StreamPosition::End);
+
+        if let Some(credentials) = options.credentials.clone() {
+            read_options = read_options.authenticated(credentials);
+        }
+
+        // Bypasses the read cache, a stale head would replay events.
+        let head = match commands::read_stream(&self.client, &read_options, stream, 1).await? {
+            ReadResult::Ok(mut events) => events.try_next().await?,
+            ReadResult::StreamNotFound(_) => None,
+        };
+
+        // Every event of a stream created after the read is new.
+        let position = match head {
+            Some(event) => StreamPosition::Position(event.get_original_event().revision),
+            None => StreamPosition::Start,
+        };
+
+        debug!("Subscribing to stream {} from {:?}", stream, position);
+        self.subscribe_to_stream(stream, &options.clone().position(position))
+            .await
+    }
+
+    /// Subscribes to a stream from the position of `options`, replaying its
+    /// history with a bounded memory use whatever the number of events to
+    /// catch up with. Historical events are read page by page, a page being
+    /// read only once the previous one is consumed, while live events are
+    /// kept aside up to a limit, see [`CatchUpOptions`]. The retry policy
+    /// and raw delivery mode of `options` aren't supported.
+    ///
+    /// [`CatchUpSubscription::lag`] tells how many events are left to catch
+    /// up with.
+    pub fn catch_up_stream<StreamName>(
+        &self,
+        stream_name: StreamName,
+        options: &SubscribeToStreamOptions,
+        catch_up: &CatchUpOptions,
+    ) -> CatchUpSubscription
+    where
+        StreamName: AsRef<str>,
+    {
+        catch_up::catch_up_stream(
+            self.client.clone(),
+            stream_name.as_ref().to_string(),
+            options.clone(),
+            *catch_up,
+        )
+    }
+
+    /// Wraps a subscription so that its positions are persisted in batches.
+    /// The position of an event or a checkpoint is pending once the consumer
+    /// asks for the next item, and `persist` gets the latest pending
+    /// position when
+    /// [`CheckpointFlushOptions::max_pending`] positions are pending, when
+    /// the [`CheckpointFlushOptions::checkpoint_flush_interval`] elapsed or
+    /// when the subscription ends. Dropping the subscription persists the
+    /// pending position, not the one of the item being handled, which is
+    /// delivered again on restart. A restart replays at most the events
+    /// delivered since the last persisted position.
+    pub fn debounce_checkpoints<'a, F, Fut>(
+        &self,
+        subscription: BoxStream<'a, crate::Result<SubEvent>>,
+        options: &CheckpointFlushOptions,
+        persist: F,
+    ) -> BoxStream<'a, crate::Result<SubEvent>>
+    where
+        F: FnMut(Checkpoint) -> Fut + Send + 'a,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        checkpoint_flush::debounce(self.client.clock().clone(), subscription, *options, persist)
+    }
+
+    /// Waits until the stream holds the given event number on the node the
+    /// client is connected to, which is useful to read your own writes from a
+    /// follower. Fails with [`crate::Error::DeadlineExceeded`] if the event
+    /// isn't reachable before `timeout` elapses.
+    pub async fn wait_for_event<StreamName>(
+        &self,
+        stream_name: StreamName,
+        event_number: u64,
+        timeout: Duration,
+    ) -> crate::Result<()>
+    where
+        StreamName: AsRef<str>,
+    {
+        // Subscribing from a revision only delivers the events that follow it.
+        let position = match event_number.checked_sub(1) {
+            Some(revision) => StreamPosition::Position(revision),
+            None => StreamPosition::Start,
+        };
+        let options = SubscribeToStreamOptions::default().position(position);
+        let wait = async {
+            let mut sub =
+                commands::subscribe_to_stream(&self.client, stream_name, &options).await?;
+
+            while let Some(event) = sub.try_next().await? {
+                if let SubEvent::EventAppeared(event) = event {
+                    if event.get_original_event().revision >= event_number {
+                        return Ok(());
+                    }
+                }
             }
+
+            Err(crate::Error::SubscriptionDropped(
+                SubscriptionDropReason::Unsubscribed,
+            ))
+        };
+
+        futures::pin_mut!(wait);
+
+        match future::select(wait, self.client.clock().sleep(timeout)).await {
+            Either::Left((result, _)) => result,
+            Either::Right(_) => Err(crate::Error::DeadlineExceeded),
+        }
+    }
+
+    /// Subscribes to several streams at once, merging their events in the
+    /// order they arrive. Each event comes with the name of the stream it was
+    /// delivered by. Every subscription uses the same options, see
+    /// [`subscribe_to_stream`]. With raw delivery, the events are merged
+    /// without their transport information.
+    ///
+    /// The subscriptions form a single session: the merged stream ends with
+    /// the first error of any of them, and dropping it closes them all.
+    ///
+    /// [`subscribe_to_stream`]: #method.subscribe_to_stream
+    pub async fn subscribe_to_streams<'a, StreamNames, StreamName>(
+        &self,
+        stream_names: StreamNames,
+        options: &SubscribeToStreamOptions,
+    ) -> crate::Result<BoxStream<'a, crate::Result<(String, ResolvedEvent)>>>
+    where
+        StreamNames: IntoIterator<Item = StreamName>,
+        StreamName: AsRef<str>,
+    {
+        let mut subscriptions = Vec::new();
+
+        for stream_name in stream_names {
+            let name = stream_name.as_ref().to_string();
+            let subscription = self.subscribe_to_stream(name.as_str(), options).await?;
+            let events = subscription.try_filter_map(move |event| {
+                let event = match event {
+                    SubEvent::EventAppeared(event) => Some((name.clone(), event)),
+                    SubEvent::RawEventAppeared(raw) => Some((name.clone(), raw.event)),
+                    _ => None,
+                };
+
+                future::ready(Ok(event))
+            });
+
+            subscriptions.push(events.boxed());
         }
+
+        let mut merged = futures::stream::select_all(subscriptions);
+        let stream = async_stream::stream! {
+            while let Some(event) = merged.next().await {
+                let failed = event.is_err();
+
+                yield event;
+
+                if failed {
+                    break;
+                }
+            }
+        };
+
+        Ok(Box::pin(stream))
     }
 
     /// Like [`subscribe_to_stream`] but specific to system `$all` stream.
@@ -236,6 +1558,7 @@ impl Client {
             Some(retry) => {
                 let mut attempt_count = 1usize;
                 let mut offset = options.position;
+                let mut boundary = Boundary::resuming_from(offset);
                 let client = self.client.clone();
                 let mut options = options.clone();
                 let result = async_stream::stream! {
@@ -251,27 +1574,81 @@ impl Client {
 
                                 error!("Subscription: attempt ({}/{}) failure, cause: {}", attempt_count, retry.limit, e);
                                 attempt_count += 1;
-                                tokio::time::sleep(retry.delay).await;
+                                client
+                                    .clock()
+                                    .sleep(client.settings().jittered(retry.delay))
+                                    .await;
                             }
                             Ok(mut stream) => {
                                 loop {
                                     match stream.try_next().await {
-                                        Ok(sub_event) => {
-                                            let sub_event = sub_event.expect("to be defined");
+                                        Ok(Some(sub_event)) => {
                                             match sub_event {
                                                 crate::types::SubEvent::EventAppeared(event) => {
-                                                    offset = crate::types::StreamPosition::Position(event.get_original_event().position);
-                                                    yield Ok(crate::types::SubEvent::EventAppeared(event));
+                                                    let position = event.get_original_event().position;
+
+                                                    if boundary.delivered(position) {
+                                                        offset = crate::types::StreamPosition::Position(position);
+                                                        yield Ok(crate::types::SubEvent::EventAppeared(event));
+                                                    }
+                                                }
+
+                                                crate::types::SubEvent::RawEventAppeared(raw) => {
+                                                    let position = raw.event.get_original_event().position;
+
+                                                    if boundary.delivered(position) {
+                                                        offset = crate::types::StreamPosition::Position(position);
+                                                        yield Ok(crate::types::SubEvent::RawEventAppeared(raw));
+                                                    }
+                                                }
+
+                                                crate::types::SubEvent::Checkpoint(position) => {
+                                                    if boundary.delivered(position) {
+                                                        offset = crate::types::StreamPosition::Position(position);
+                                                    }
+
+                                                    yield Ok(crate::types::SubEvent::Checkpoint(position));
+                                                }
+
+                                                crate::types::SubEvent::CaughtUp => {
+                                                    // No need to track the catch-up phase again when resubscribing.
+                                                    options.notify_caught_up = false;
+                                                    yield Ok(crate::types::SubEvent::CaughtUp);
                                                 }
 
                                                 ignored => yield Ok(ignored),
                                             }
                                         }
+                                        Ok(None) => {
+                                            attempt_count = 1;
+                                            options = options.position(offset);
+
+                                            error!("Subscription ended by the server. Reconnecting");
+                                            yield Ok(crate::types::SubEvent::Resubscribing);
+                                            break;
+                                        }
+                                        Err(crate::Error::Cancelled) => {
+                                            yield Err(crate::Error::Cancelled);
+                                            return;
+                                        }
+                                        Err(crate::Error::SubscriptionDropped(reason)) if !reason.is_transient() => {
+                                            error!("Subscription dropped cause: {}. Giving up", reason);
+
+                                            yield Err(crate::Error::SubscriptionDropped(reason));
+                                            return;
+                                        }
+                                        Err(e) if e.ends_subscription() => {
+                                            error!("Subscription failed: {}. Giving up", e);
+
+                                            yield Err(e);
+                                            return;
+                                        }
                                         Err(e) => {
                                             attempt_count = 1;
                                             options = options.position(offset);
 
                                             error!("Subscription dropped cause: {}. Reconnecting", e);
+                                            yield Ok(crate::types::SubEvent::Resubscribing);
                                             break;
                                         }
                                     }
@@ -288,6 +1665,37 @@ impl Client {
         }
     }
 
+    /// Like [`subscribe_from_end`] but specific to system `$all` stream. The
+    /// position of `options` is ignored.
+    ///
+    /// [`subscribe_from_end`]: #method.subscribe_from_end
+    pub async fn subscribe_to_all_from_end<'a>(
+        &self,
+        options: &SubscribeToAllOptions,
+    ) -> crate::Result<BoxStream<'a, crate::Result<SubEvent>>> {
+        let mut read_options = ReadAllOptions::default()
+            .position(StreamPosition::End)
+            .backwards();
+
+        if let Some(credentials) = options.credentials.clone() {
+            read_options = read_options.authenticated(credentials);
+        }
+
+        let head = commands::read_all(&self.client, &read_options, 1)
+            .await?
+            .try_next()
+            .await?;
+
+        let position = match head {
+            Some(event) => StreamPosition::Position(event.get_original_event().position),
+            None => StreamPosition::Start,
+        };
+
+        debug!("Subscribing to $all from {:?}", position);
+        self.subscribe_to_all(&options.clone().position(position))
+            .await
+    }
+
     /// Creates a persistent subscription group on a stream.
     ///
     /// Persistent subscriptions are special kind of subscription where the
@@ -353,6 +1761,195 @@ impl Client {
         .await
     }
 
+    /// Reads the last checkpoint of a persistent subscription group, to
+    /// create another group starting where this one is. If the group never
+    /// checkpointed, its configured start is returned instead, which requires
+    /// reading the `$persistentSubscriptionConfig` system stream. Both
+    /// streams usually require admin rights. Fails with
+    /// [`crate::Error::ResourceNotFound`] if the group doesn't exist.
+    pub async fn persistent_subscription_checkpoint<StreamName, GroupName>(
+        &self,
+        stream_name: StreamName,
+        group_name: GroupName,
+        options: &ReadStreamOptions,
+    ) -> crate::Result<PersistentSubscriptionCheckpoint>
+    where
+        StreamName: AsRef<str>,
+        GroupName: AsRef<str>,
+    {
+        let stream = stream_name.as_ref();
+        let group = group_name.as_ref();
+        let options = options.clone().position(StreamPosition::End);
+        let checkpoint_stream = format!("$persistentsubscription-{}::{}-checkpoint", stream, group);
+
+        if let ReadResult::Ok(Some(event)) = self
+            .read_stream(checkpoint_stream, &options, Single)
+            .await?
+        {
+            let revision =
+                PersistentSubscriptionCheckpoint::parse(&event.get_original_event().data)
+                    .ok_or_else(|| {
+                        crate::Error::InternalParsingError(format!(
+                            "Invalid checkpoint for persistent subscription {}::{}",
+                            stream, group
+                        ))
+                    })?;
+
+            return Ok(PersistentSubscriptionCheckpoint::Checkpointed(revision));
+        }
+
+        let start_from = self
+            .read_persistent_subscription_config(&options)
+            .await?
+            .and_then(|config| config.start_from(stream, group))
+            .ok_or(crate::Error::ResourceNotFound)?;
+
+        Ok(PersistentSubscriptionCheckpoint::NotCheckpointed { start_from })
+    }
+
+    /// Creates a persistent subscription group if it doesn't exist, or
+    /// updates it if its settings differ from the given ones. The current
+    /// settings are read from the `$persistentSubscriptionConfig` system
+    /// stream, which usually requires admin rights.
+    pub async fn ensure_persistent_subscription<StreamName, GroupName>(
+        &self,
+        stream_name: StreamName,
+        group_name: GroupName,
+        options: &PersistentSubscriptionOptions,
+    ) -> crate::Result<EnsurePersistentSubscriptionResult>
+    where
+        StreamName: AsRef<str>,
+        GroupName: AsRef<str>,
+    {
+        let stream = stream_name.as_ref();
+        let group = group_name.as_ref();
+        let mut read_options = ReadStreamOptions::default().position(StreamPosition::End);
+
+        if let Some(credentials) = options.credentials.clone() {
+            read_options = read_options.authenticated(credentials);
+        }
+
+        let configured = self
+            .read_persistent_subscription_config(&read_options)
+            .await?
+            .and_then(|config| config.has_options(stream, group, options.revision, &options.setts));
+
+        match configured {
+            Some(true) => Ok(EnsurePersistentSubscriptionResult::Unchanged),
+
+            Some(false) => {
+                self.update_persistent_subscription(stream, group, options)
+                    .await?;
+
+                Ok(EnsurePersistentSubscriptionResult::Updated)
+            }
+
+            None => match self
+                .create_persistent_subscription(stream, group, options)
+                .await
+            {
+                Ok(()) => Ok(EnsurePersistentSubscriptionResult::Created),
+
+                // Created concurrently, after the configuration was read.
+                Err(crate::Error::ResourceAlreadyExists) => {
+                    self.update_persistent_subscription(stream, group, options)
+                        .await?;
+
+                    Ok(EnsurePersistentSubscriptionResult::Updated)
+                }
+
+                Err(e) => Err(e),
+            },
+        }
+    }
+
+    /// Returns the configuration of a persistent subscription group as stored
+    /// by the server, `None` if the group doesn't exist. The configuration
+    /// is read from the `$persistentSubscriptionConfig` system stream, which
+    /// usually requires admin rights, at the version `options` points to. See
+    /// [`Client::persistent_subscription_configs`].
+    pub async fn persistent_subscription_config<StreamName, GroupName>(
+        &self,
+        stream_name: StreamName,
+        group_name: GroupName,
+        options: &ReadStreamOptions,
+    ) -> crate::Result<Option<PersistentSubscriptionGroupConfig>>
+    where
+        StreamName: AsRef<str>,
+        GroupName: AsRef<str>,
+    {
+        let group = self
+            .persistent_subscription_configs(options)
+            .await?
+            .into_iter()
+            .find(|config| {
+                config.stream == stream_name.as_ref() && config.group == group_name.as_ref()
+            });
+
+        Ok(group)
+    }
+
+    /// Returns the configuration of every persistent subscription group, as
+    /// stored by the server in the `$persistentSubscriptionConfig` system
+    /// stream. Each event of that stream is a version of the configuration:
+    /// [`StreamPosition::End`] reads the latest one, [`StreamPosition::Start`]
+    /// the first one and a position the one written at that event number,
+    /// for auditing purposes.
+    pub async fn persistent_subscription_configs(
+        &self,
+        options: &ReadStreamOptions,
+    ) -> crate::Result<Vec<PersistentSubscriptionGroupConfig>> {
+        let groups = self
+            .read_persistent_subscription_config(options)
+            .await?
+            .map(|config| config.groups())
+            .unwrap_or_default();
+
+        Ok(groups)
+    }
+
+    /// Applies the configuration of a persistent subscription group, creating
+    /// the group if it doesn't exist or updating it if its settings differ.
+    /// See [`Client::ensure_persistent_subscription`]. The revision and
+    /// settings of `options` are replaced by the ones of `config`.
+    pub async fn apply_persistent_subscription_config(
+        &self,
+        config: &PersistentSubscriptionGroupConfig,
+        options: &PersistentSubscriptionOptions,
+    ) -> crate::Result<EnsurePersistentSubscriptionResult> {
+        let options = options
+            .clone()
+            .revision(config.start_from)
+            .settings(config.settings);
+
+        self.ensure_persistent_subscription(config.stream.as_str(), config.group.as_str(), &options)
+            .await
+    }
+
+    async fn read_persistent_subscription_config(
+        &self,
+        options: &ReadStreamOptions,
+    ) -> crate::Result<Option<PersistentSubscriptionConfig>> {
+        // Bypasses the read cache, the server writes that stream on its own.
+        let result =
+            commands::read_stream(&self.client, options, "$persistentSubscriptionConfig", 1)
+                .await?;
+
+        let event = match result {
+            ReadResult::Ok(mut events) => events.try_next().await?,
+            ReadResult::StreamNotFound(_) => None,
+        };
+
+        event
+            .map(|event| {
+                event
+                    .get_original_event()
+                    .as_json::<PersistentSubscriptionConfig>()
+                    .map_err(|e| crate::Error::InternalParsingError(e.to_string()))
+            })
+            .transpose()
+    }
+
     /// Connects to a persistent subscription group on a stream.
     pub async fn connect_persistent_subscription<StreamName, GroupName>(
         &self,
@@ -372,4 +1969,110 @@ impl Client {
         )
         .await
     }
+
+    /// Connects to a persistent subscription group on a stream, returning
+    /// its events paired with the [`AckHandle`] settling each of them.
+    ///
+    /// ```no_run
+    /// # use eventstore::{Client, NakAction};
+    /// # use futures::TryStreamExt;
+    /// # async fn f(client: Client) -> Result<(), Box<dyn std::error::Error>> {
+    /// let mut events = client
+    ///     .connect_persistent_subscription_stream("orders", "billing", &Default::default())
+    ///     .await?;
+    ///
+    /// while let Some((event, handle)) = events.try_next().await? {
+    ///     if event.get_original_event().event_type == "order-placed" {
+    ///         handle.ack().await?;
+    ///     } else {
+    ///         handle.nack(NakAction::Skip, "Unsupported event").await?;
+    ///     }
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn connect_persistent_subscription_stream<StreamName, GroupName>(
+        &self,
+        stream_name: StreamName,
+        group_name: GroupName,
+        options: &ConnectToPersistentSubscription,
+    ) -> crate::Result<BoxStream<'static, crate::Result<(ResolvedEvent, AckHandle)>>>
+    where
+        StreamName: AsRef<str>,
+        GroupName: AsRef<str>,
+    {
+        let (read, write) = self
+            .connect_persistent_subscription(stream_name, group_name, options)
+            .await?;
+
+        Ok(commands::ack_stream(
+            read,
+            write,
+            options.unsettled_event_policy,
+        ))
+    }
+}
+
+/// Tracks the last position a resubscribing subscription delivered, so events
+/// the server sends again after a resubscription are skipped.
+struct Boundary<A> {
+    last: Option<A>,
+}
+
+impl<A: PartialOrd + Copy> Boundary<A> {
+    fn resuming_from(position: StreamPosition<A>) -> Self {
+        let last = match position {
+            // Subscribing from a position doesn't deliver the event at that
+            // position.
+            StreamPosition::Position(position) => Some(position),
+            StreamPosition::Start | StreamPosition::End => None,
+        };
+
+        Boundary { last }
+    }
+
+    /// Records a position, returning `false` if it isn't past the last one
+    /// delivered.
+    fn delivered(&mut self, position: A) -> bool {
+        if matches!(self.last, Some(last) if position <= last) {
+            return false;
+        }
+
+        self.last = Some(position);
+
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Boundary;
+    use crate::StreamPosition;
+
+    #[test]
+    fn resubscription_boundary_is_deduplicated() {
+        let mut boundary = Boundary::resuming_from(StreamPosition::Start);
+        let mut delivered = Vec::new();
+
+        // The connection fails over after event 3 was delivered, and the new
+        // node replays the events following an older checkpoint.
+        let first_connection = 0..=3u64;
+        let after_failover = 2..=6u64;
+
+        for revision in first_connection.chain(after_failover) {
+            if boundary.delivered(revision) {
+                delivered.push(revision);
+            }
+        }
+
+        assert_eq!(delivered, (0..=6).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn resuming_from_position_excludes_it() {
+        let mut boundary = Boundary::resuming_from(StreamPosition::Position(10u64));
+
+        assert!(!boundary.delivered(10));
+        assert!(boundary.delivered(11));
+    }
 }
@@ -0,0 +1,142 @@
+//! Source of time used by the client timeout and backoff logic.
+//!
+//! Production code runs on [`SystemClock`], which relies on the monotonic
+//! clock. [`TestClock`] only moves forward when told to, allowing tests to
+//! exercise time-dependent behaviors without real sleeps.
+use futures::future::BoxFuture;
+use futures::FutureExt;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::sync::oneshot;
+
+/// Provides the current time and a way to wait for some duration.
+pub trait Clock: Send + Sync + 'static {
+    /// Returns the current instant.
+    fn now(&self) -> Instant;
+
+    /// Completes once the given duration elapsed.
+    fn sleep(&self, duration: Duration) -> BoxFuture<'static, ()>;
+}
+
+/// Clock backed by the monotonic clock of the operating system. That's the
+/// clock used by default.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> Instant {
+        Instant::now()
+    }
+
+    fn sleep(&self, duration: Duration) -> BoxFuture<'static, ()> {
+        tokio::time::sleep(duration).boxed()
+    }
+}
+
+struct TestClockState {
+    now: Instant,
+    sleepers: Vec<(Instant, oneshot::Sender<()>)>,
+}
+
+/// Clock that only moves forward when [`TestClock::advance`] is called.
+/// Pending sleeps complete as soon as the clock reaches their deadline.
+#[derive(Clone)]
+pub struct TestClock {
+    state: Arc<Mutex<TestClockState>>,
+}
+
+impl Default for TestClock {
+    fn default() -> Self {
+        TestClock::new()
+    }
+}
+
+impl TestClock {
+    pub fn new() -> Self {
+        TestClock {
+            state: Arc::new(Mutex::new(TestClockState {
+                now: Instant::now(),
+                sleepers: Vec::new(),
+            })),
+        }
+    }
+
+    /// Moves the clock forward, waking up the sleeps whose deadline is
+    /// reached.
+    pub fn advance(&self, duration: Duration) {
+        let mut state = self.state.lock().unwrap();
+        let now = state.now + duration;
+        let sleepers = std::mem::take(&mut state.sleepers);
+
+        state.now = now;
+
+        for (deadline, waker) in sleepers {
+            if deadline <= now {
+                let _ = waker.send(());
+            } else {
+                state.sleepers.push((deadline, waker));
+            }
+        }
+    }
+
+    /// Number of sleeps waiting for the clock to move forward.
+    pub fn pending_sleeps(&self) -> usize {
+        self.state.lock().unwrap().sleepers.len()
+    }
+}
+
+impl Clock for TestClock {
+    fn now(&self) -> Instant {
+        self.state.lock().unwrap().now
+    }
+
+    fn sleep(&self, duration: Duration) -> BoxFuture<'static, ()> {
+        let mut state = self.state.lock().unwrap();
+        let deadline = state.now + duration;
+
+        if deadline <= state.now {
+            return futures::future::ready(()).boxed();
+        }
+
+        let (waker, sleeping) = oneshot::channel();
+
+        state.sleepers.push((deadline, waker));
+
+        async move {
+            let _ = sleeping.await;
+        }
+        .boxed()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Clock, TestClock};
+    use futures::FutureExt;
+    use std::time::Duration;
+
+    #[test]
+    fn sleep_completes_once_advanced_past_deadline() {
+        let clock = TestClock::new();
+        let start = clock.now();
+        let mut sleep = clock.sleep(Duration::from_secs(10));
+
+        assert!((&mut sleep).now_or_never().is_none());
+
+        clock.advance(Duration::from_secs(5));
+        assert!((&mut sleep).now_or_never().is_none());
+        assert_eq!(clock.pending_sleeps(), 1);
+
+        clock.advance(Duration::from_secs(5));
+        assert!(sleep.now_or_never().is_some());
+        assert_eq!(clock.pending_sleeps(), 0);
+        assert_eq!(clock.now() - start, Duration::from_secs(10));
+    }
+
+    #[test]
+    fn zero_sleep_completes_immediately() {
+        let clock = TestClock::new();
+
+        assert!(clock.sleep(Duration::default()).now_or_never().is_some());
+    }
+}
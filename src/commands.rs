@@ -3,13 +3,19 @@ use futures::{stream, TryStreamExt};
 use futures::{Stream, StreamExt};
 
 use crate::event_store::client::{persistent, shared, streams};
+use crate::metrics::OperationKind;
+use crate::operation;
+use crate::subscriptions::SubscriptionTarget;
 use crate::types::{
-    EventData, ExpectedRevision, PersistentSubscriptionSettings, Position, ReadDirection,
-    RecordedEvent, ResolvedEvent, StreamPosition, SubEvent, WriteResult, WrongExpectedVersion,
+    DecodeFailurePolicy, DeleteResult, EmptyAppendBehavior, EventData, ExpectedRevision,
+    PersistentSubscriptionSettings, Position, RawEvent, ReadAllEvent, ReadDirection, RecordedEvent,
+    ResolvedEvent, StreamPosition, SubEvent, SubscriptionDropReason, TransportInfo, WriteResult,
+    WrongExpectedVersion,
 };
 
 use async_stream::stream;
 use persistent::persistent_subscriptions_client::PersistentSubscriptionsClient;
+use prost::Message;
 use shared::{Empty, StreamIdentifier, Uuid};
 use streams::streams_client::StreamsClient;
 
@@ -22,56 +28,58 @@ use crate::options::subscribe_to_stream::SubscribeToStreamOptions;
 use crate::{
     ConnectToPersistentSubscription, Credentials, CurrentRevision,
     DeletePersistentSubscriptionOptions, DeleteStreamOptions, NakAction, ReadResult,
-    SubscribeToAllOptions, SubscriptionFilter, SystemConsumerStrategy,
+    SubscribeToAllOptions, SubscriptionFilter, SystemConsumerStrategy, UnsettledEventPolicy,
 };
 use futures::stream::BoxStream;
 use tonic::Request;
 
-fn raw_uuid_to_uuid(src: Uuid) -> uuid::Uuid {
+fn decoding_error<S: AsRef<str>>(reason: S) -> crate::Error {
+    crate::Error::InternalParsingError(format!("Unable to decode event: {}", reason.as_ref()))
+}
+
+fn raw_uuid_to_uuid(src: Uuid) -> crate::Result<uuid::Uuid> {
     use byteorder::{BigEndian, ByteOrder};
 
     let value = src
         .value
-        .expect("We expect Uuid value to be defined for now");
+        .ok_or_else(|| decoding_error("Uuid value is not defined"))?;
 
     match value {
         shared::uuid::Value::Structured(s) => {
-            let mut buf = vec![];
+            let mut buf = [0u8; 16];
 
-            BigEndian::write_i64(&mut buf, s.most_significant_bits);
-            BigEndian::write_i64(&mut buf, s.least_significant_bits);
+            BigEndian::write_i64(&mut buf[..8], s.most_significant_bits);
+            BigEndian::write_i64(&mut buf[8..], s.least_significant_bits);
 
-            uuid::Uuid::from_slice(buf.as_slice())
-                .expect("We expect a valid UUID out of byte buffer")
+            uuid::Uuid::from_slice(&buf).map_err(|e| decoding_error(e.to_string()))
         }
 
         shared::uuid::Value::String(s) => s
             .parse()
-            .expect("We expect a valid UUID out of this String"),
+            .map_err(|e: uuid::Error| decoding_error(e.to_string())),
     }
 }
 
-fn raw_persistent_uuid_to_uuid(src: Uuid) -> uuid::Uuid {
+fn raw_persistent_uuid_to_uuid(src: Uuid) -> crate::Result<uuid::Uuid> {
     use byteorder::{BigEndian, ByteOrder};
 
     let value = src
         .value
-        .expect("We expect Uuid value to be defined for now");
+        .ok_or_else(|| decoding_error("Uuid value is not defined"))?;
 
     match value {
         shared::uuid::Value::Structured(s) => {
-            let mut buf = vec![];
+            let mut buf = [0u8; 16];
 
-            BigEndian::write_i64(&mut buf, s.most_significant_bits);
-            BigEndian::write_i64(&mut buf, s.least_significant_bits);
+            BigEndian::write_i64(&mut buf[..8], s.most_significant_bits);
+            BigEndian::write_i64(&mut buf[8..], s.least_significant_bits);
 
-            uuid::Uuid::from_slice(buf.as_slice())
-                .expect("We expect a valid UUID out of byte buffer")
+            uuid::Uuid::from_slice(&buf).map_err(|e| decoding_error(e.to_string()))
         }
 
         shared::uuid::Value::String(s) => s
             .parse()
-            .expect("We expect a valid UUID out of this String"),
+            .map_err(|e: uuid::Error| decoding_error(e.to_string())),
     }
 }
 
@@ -82,7 +90,7 @@ fn convert_event_data(event: EventData) -> streams::AppendReq {
     let id = shared::uuid::Value::String(id.to_string());
     let id = Uuid { value: Some(id) };
     let custom_metadata = event
-        .custom_metadata
+        .encoded_custom_metadata()
         .map_or_else(Vec::new, |b| (&*b).into());
 
     let msg = append_req::ProposedMessage {
@@ -101,11 +109,11 @@ fn convert_event_data(event: EventData) -> streams::AppendReq {
 
 fn convert_proto_recorded_event(
     event: streams::read_resp::read_event::RecordedEvent,
-) -> RecordedEvent {
+) -> crate::Result<RecordedEvent> {
     let id = event
         .id
-        .map(raw_uuid_to_uuid)
-        .expect("Unable to parse Uuid [convert_proto_recorded_event]");
+        .ok_or_else(|| decoding_error("event id is not defined"))
+        .and_then(raw_uuid_to_uuid)?;
 
     let position = Position {
         commit: event.commit_position,
@@ -127,11 +135,12 @@ fn convert_proto_recorded_event(
     let stream_id = String::from_utf8(
         event
             .stream_identifier
-            .expect("stream_identifier is always defined")
+            .ok_or_else(|| decoding_error("stream identifier is not defined"))?
             .stream_name,
     )
-    .expect("It's always UTF-8");
-    RecordedEvent {
+    .map_err(|e| decoding_error(e.to_string()))?;
+
+    Ok(RecordedEvent {
         id,
         stream_id,
         revision: event.stream_revision,
@@ -141,16 +150,16 @@ fn convert_proto_recorded_event(
         metadata: event.metadata,
         custom_metadata: event.custom_metadata.into(),
         data: event.data.into(),
-    }
+    })
 }
 
 fn convert_persistent_proto_recorded_event(
     event: persistent::read_resp::read_event::RecordedEvent,
-) -> RecordedEvent {
+) -> crate::Result<RecordedEvent> {
     let id = event
         .id
-        .map(raw_persistent_uuid_to_uuid)
-        .expect("Unable to parse Uuid [convert_persistent_proto_recorded_event]");
+        .ok_or_else(|| decoding_error("event id is not defined"))
+        .and_then(raw_persistent_uuid_to_uuid)?;
 
     let position = Position {
         commit: event.commit_position,
@@ -172,12 +181,12 @@ fn convert_persistent_proto_recorded_event(
     let stream_id = String::from_utf8(
         event
             .stream_identifier
-            .expect("stream_identifier is always defined")
+            .ok_or_else(|| decoding_error("stream identifier is not defined"))?
             .stream_name,
     )
-    .expect("string is UTF-8 valid");
+    .map_err(|e| decoding_error(e.to_string()))?;
 
-    RecordedEvent {
+    Ok(RecordedEvent {
         id,
         stream_id,
         revision: event.stream_revision,
@@ -187,7 +196,7 @@ fn convert_persistent_proto_recorded_event(
         metadata: event.metadata,
         custom_metadata: event.custom_metadata.into(),
         data: event.data.into(),
-    }
+    })
 }
 
 fn convert_settings_create(
@@ -260,7 +269,7 @@ fn convert_settings_update(
     }
 }
 
-fn convert_proto_read_event(event: streams::read_resp::ReadEvent) -> ResolvedEvent {
+fn convert_proto_read_event(event: streams::read_resp::ReadEvent) -> crate::Result<ResolvedEvent> {
     let commit_position = if let Some(pos_alt) = event.position {
         match pos_alt {
             streams::read_resp::read_event::Position::CommitPosition(pos) => Some(pos),
@@ -270,14 +279,16 @@ fn convert_proto_read_event(event: streams::read_resp::ReadEvent) -> ResolvedEve
         None
     };
 
-    ResolvedEvent {
-        event: event.event.map(convert_proto_recorded_event),
-        link: event.link.map(convert_proto_recorded_event),
+    Ok(ResolvedEvent {
+        event: event.event.map(convert_proto_recorded_event).transpose()?,
+        link: event.link.map(convert_proto_recorded_event).transpose()?,
         commit_position,
-    }
+    })
 }
 
-fn convert_persistent_proto_read_event(event: persistent::read_resp::ReadEvent) -> ResolvedEvent {
+fn convert_persistent_proto_read_event(
+    event: persistent::read_resp::ReadEvent,
+) -> crate::Result<ResolvedEvent> {
     let commit_position = if let Some(pos_alt) = event.position {
         match pos_alt {
             persistent::read_resp::read_event::Position::CommitPosition(pos) => Some(pos),
@@ -287,21 +298,130 @@ fn convert_persistent_proto_read_event(event: persistent::read_resp::ReadEvent)
         None
     };
 
-    ResolvedEvent {
-        event: event.event.map(convert_persistent_proto_recorded_event),
-        link: event.link.map(convert_persistent_proto_recorded_event),
+    Ok(ResolvedEvent {
+        event: event
+            .event
+            .map(convert_persistent_proto_recorded_event)
+            .transpose()?,
+        link: event
+            .link
+            .map(convert_persistent_proto_recorded_event)
+            .transpose()?,
         commit_position,
+    })
+}
+
+/// Tracks when a catch-up subscription reaches the live edge of what it
+/// subscribes to.
+#[derive(Copy, Clone)]
+enum CatchUp<A> {
+    /// The subscription is live as soon as it's confirmed.
+    OnConfirmation,
+    /// The subscription is live once it delivers an event at or past that
+    /// point.
+    Until(A),
+    /// The caught-up notification was emitted or wasn't asked for.
+    Done,
+}
+
+impl<A: Ord + Copy> CatchUp<A> {
+    fn new(start: StreamPosition<A>, edge: Option<A>) -> Self {
+        match (start, edge) {
+            (_, None) | (StreamPosition::End, _) => CatchUp::OnConfirmation,
+            (StreamPosition::Position(start), Some(edge)) if start >= edge => {
+                CatchUp::OnConfirmation
+            }
+            (_, Some(edge)) => CatchUp::Until(edge),
+        }
+    }
+
+    fn on_confirmation(&mut self) -> bool {
+        if let CatchUp::OnConfirmation = self {
+            *self = CatchUp::Done;
+            return true;
+        }
+
+        false
+    }
+
+    fn on_position(&mut self, position: A) -> bool {
+        if let CatchUp::Until(edge) = self {
+            if position >= *edge {
+                *self = CatchUp::Done;
+                return true;
+            }
+        }
+
+        false
     }
 }
 
+/// Returns the revision of the last event of a stream, if any.
+async fn stream_live_edge(
+    connection: &GrpcClient,
+    stream_id: &str,
+    credentials: Option<Credentials>,
+) -> crate::Result<Option<u64>> {
+    let mut options = ReadStreamOptions::default().position(StreamPosition::End);
+
+    if let Some(credentials) = credentials {
+        options = options.authenticated(credentials);
+    }
+
+    match read_stream(connection, &options, stream_id, 1).await? {
+        ReadResult::StreamNotFound(_) => Ok(None),
+        ReadResult::Ok(mut events) => Ok(events
+            .try_next()
+            .await?
+            .map(|event| event.get_original_event().revision)),
+    }
+}
+
+/// Returns the position of the last event of `$all`, if any.
+async fn all_live_edge(
+    connection: &GrpcClient,
+    credentials: Option<Credentials>,
+) -> crate::Result<Option<Position>> {
+    let mut options = ReadAllOptions::default().position(StreamPosition::End);
+
+    if let Some(credentials) = credentials {
+        options = options.authenticated(credentials);
+    }
+
+    let mut events = read_all(connection, &options, 1).await?;
+
+    Ok(events
+        .try_next()
+        .await?
+        .map(|event| event.get_original_event().position))
+}
+
+/// Creates a gRPC request and attaches the given credentials, if any. The
+/// request carries the identifier of the submitted operation it's part of.
+pub(crate) fn new_request<A>(message: A, creds_opt: Option<Credentials>) -> Request<A> {
+    let mut req = Request::new(message);
+
+    configure_auth_req(&mut req, creds_opt);
+
+    if let Some(id) = operation::current() {
+        let header_value = tonic::metadata::MetadataValue::from_str(id.to_string().as_str())
+            .expect("A UUID should be a valid metadata header value");
+
+        req.metadata_mut()
+            .insert(operation::CORRELATION_ID_HEADER, header_value);
+    }
+
+    req
+}
+
 pub(crate) fn configure_auth_req<A>(req: &mut Request<A>, creds_opt: Option<Credentials>) {
     use tonic::metadata::MetadataValue;
 
     if let Some(creds) = creds_opt {
-        let login = String::from_utf8_lossy(&*creds.login).into_owned();
-        let password = String::from_utf8_lossy(&*creds.password).into_owned();
+        let login = String::from_utf8_lossy(&creds.login).into_owned();
+        let password = String::from_utf8_lossy(&creds.password).into_owned();
 
-        let basic_auth_string = base64::encode(&format!("{}:{}", login, password));
+        let basic_auth_string = base64::encode(format!("{}:{}", login, password));
         let basic_auth = format!("Basic {}", basic_auth_string);
         let header_value = MetadataValue::from_str(basic_auth.as_str())
             .expect("Auth header value should be valid metadata header value");
@@ -319,7 +439,7 @@ pub fn filter_into_proto(filter: SubscriptionFilter) -> streams::read_req::optio
     };
 
     let expr = Expression {
-        regex: filter.regex.unwrap_or_else(|| "".to_string()),
+        regex: filter.regex.unwrap_or_default(),
         prefix: filter.prefixes,
     };
 
@@ -347,79 +467,161 @@ where
     S: AsRef<str>,
     Events: Stream<Item = EventData> + Send + Sync + 'static,
 {
-    use streams::append_req::{self, Content};
-    use streams::AppendReq;
-
     let stream = stream.as_ref().to_string();
+    let credentials = options
+        .credentials
+        .clone()
+        .or_else(|| connection.default_credentials());
 
-    connection.execute(move |channel| async move {
-        let stream_identifier = Some(StreamIdentifier {
-            stream_name: stream.into_bytes(),
-        });
-        let header = Content::Options(append_req::Options {
-            stream_identifier,
-            expected_stream_revision: Some(options.version.clone()),
+    // Events are converted upfront so they keep the same ids if the append
+    // has to be sent again.
+    let events: Vec<streams::AppendReq> = events.map(convert_event_data).collect().await;
+
+    if events.is_empty() {
+        reject_empty_append(connection, stream.as_str())?;
+    }
+
+    let bytes = events.iter().map(Message::encoded_len).sum();
+    let events = std::sync::Arc::new(events);
+    let metrics = connection.metrics();
+
+    // The server only guarantees the idempotency of an append, and its ids,
+    // when it expects a revision.
+    let idempotent = !matches!(
+        options.version,
+        streams::append_req::options::ExpectedStreamRevision::Any(_)
+    );
+    let (result, retries) = connection
+        .execute_with_retries(OperationKind::AppendToStream, idempotent, |channel| {
+            metrics.sent(OperationKind::AppendToStream, events.len(), bytes);
+
+            let header = append_header(stream.as_str(), options);
+            let events = events.clone();
+            let events = stream::iter(0..events.len()).map(move |idx| events[idx].clone());
+            let req = new_request(header.chain(events), credentials.clone());
+
+            async move {
+                let mut client = StreamsClient::new(channel.channel);
+                let resp = client.append(req).await?.into_inner();
+
+                Ok(write_result(resp))
+            }
+        })
+        .await?;
+
+    Ok(result.map(|result| WriteResult { retries, ..result }))
+}
+
+/// Sends events to a stream as they are produced. Only one event is held in
+/// memory at a time, on top of what the HTTP/2 transport buffers. Because
+/// the events can't be produced again, the append is never retried.
+pub async fn append_streamed_events<S, Events>(
+    connection: &GrpcClient,
+    stream: S,
+    options: &AppendToStreamOptions,
+    events: Events,
+) -> crate::Result<Result<WriteResult, WrongExpectedVersion>>
+where
+    S: AsRef<str>,
+    Events: Stream<Item = EventData> + Send + Sync + 'static,
+{
+    let credentials = options
+        .credentials
+        .clone()
+        .or_else(|| connection.default_credentials());
+    let mut events = Box::pin(events.peekable());
+
+    if events.as_mut().peek().await.is_none() {
+        reject_empty_append(connection, stream.as_ref())?;
+    }
+
+    let metrics = connection.metrics();
+    let events = events.map(convert_event_data).inspect(move |event| {
+        metrics.sent(OperationKind::AppendToStream, 1, event.encoded_len());
+    });
+    let header = append_header(stream.as_ref(), options);
+    let req = new_request(header.chain(events), credentials);
+
+    connection
+        .execute_once(OperationKind::AppendToStream, |channel| async move {
+            let mut client = StreamsClient::new(channel.channel);
+            let resp = client.append(req).await?.into_inner();
+
+            Ok(write_result(resp))
+        })
+        .await
+}
+
+fn reject_empty_append(connection: &GrpcClient, stream: &str) -> crate::Result<()> {
+    if connection.settings().empty_append == EmptyAppendBehavior::Reject {
+        return Err(crate::Error::NoEventsToWrite {
+            stream: stream.to_string(),
         });
-        let header = AppendReq {
-            content: Some(header),
-        };
-        let header = stream::once(async move { header });
-        let events = events.map(convert_event_data);
-        let payload = header.chain(events);
-        let mut req = Request::new(payload);
+    }
 
-        let credentials = options.credentials.clone().or_else(|| connection.default_credentials());
+    Ok(())
+}
 
-        configure_auth_req(&mut req, credentials);
+fn append_header(
+    stream: &str,
+    options: &AppendToStreamOptions,
+) -> impl Stream<Item = streams::AppendReq> {
+    use streams::append_req::{self, Content};
 
-        let mut client = StreamsClient::new(channel.channel);
-        let resp = client.append(req).await?.into_inner();
+    let stream_identifier = Some(StreamIdentifier {
+        stream_name: stream.to_string().into_bytes(),
+    });
+    let header = Content::Options(append_req::Options {
+        stream_identifier,
+        expected_stream_revision: Some(options.version.clone()),
+    });
+    let header = streams::AppendReq {
+        content: Some(header),
+    };
 
-        match resp.result.unwrap() {
-            streams::append_resp::Result::Success(success) => {
-                let next_expected_version = match success.current_revision_option.unwrap() {
-                    streams::append_resp::success::CurrentRevisionOption::CurrentRevision(rev) => {
-                        rev
-                    }
-                    streams::append_resp::success::CurrentRevisionOption::NoStream(_) => 0,
-                };
+    stream::once(async move { header })
+}
 
-                let position = match success.position_option.unwrap() {
-                    streams::append_resp::success::PositionOption::Position(pos) => Position {
-                        commit: pos.commit_position,
-                        prepare: pos.prepare_position,
-                    },
+fn write_result(resp: streams::AppendResp) -> Result<WriteResult, WrongExpectedVersion> {
+    match resp.result.unwrap() {
+        streams::append_resp::Result::Success(success) => {
+            let next_expected_version = match success.current_revision_option.unwrap() {
+                streams::append_resp::success::CurrentRevisionOption::CurrentRevision(rev) => rev,
+                streams::append_resp::success::CurrentRevisionOption::NoStream(_) => 0,
+            };
 
-                    streams::append_resp::success::PositionOption::NoPosition(_) => {
-                        Position::start()
-                    }
-                };
+            let position = match success.position_option.unwrap() {
+                streams::append_resp::success::PositionOption::Position(pos) => Position {
+                    commit: pos.commit_position,
+                    prepare: pos.prepare_position,
+                },
 
-                let write_result = WriteResult {
-                    next_expected_version,
-                    position,
-                };
+                streams::append_resp::success::PositionOption::NoPosition(_) => Position::start(),
+            };
 
-                Ok(Ok(write_result))
-            }
+            Ok(WriteResult {
+                next_expected_version,
+                position,
+                retries: Default::default(),
+            })
+        }
 
-            streams::append_resp::Result::WrongExpectedVersion(error) => {
-                let current = match error.current_revision_option.unwrap() {
-                    streams::append_resp::wrong_expected_version::CurrentRevisionOption::CurrentRevision(rev) => CurrentRevision::Current(rev),
-                    streams::append_resp::wrong_expected_version::CurrentRevisionOption::CurrentNoStream(_) => CurrentRevision::NoStream,
-                };
+        streams::append_resp::Result::WrongExpectedVersion(error) => {
+            let current = match error.current_revision_option.unwrap() {
+                streams::append_resp::wrong_expected_version::CurrentRevisionOption::CurrentRevision(rev) => CurrentRevision::Current(rev),
+                streams::append_resp::wrong_expected_version::CurrentRevisionOption::CurrentNoStream(_) => CurrentRevision::NoStream,
+            };
 
-                let expected = match error.expected_revision_option.unwrap() {
-                    streams::append_resp::wrong_expected_version::ExpectedRevisionOption::ExpectedRevision(rev) => ExpectedRevision::Exact(rev),
-                    streams::append_resp::wrong_expected_version::ExpectedRevisionOption::ExpectedAny(_) => ExpectedRevision::Any,
-                    streams::append_resp::wrong_expected_version::ExpectedRevisionOption::ExpectedStreamExists(_) => ExpectedRevision::StreamExists,
-                    streams::append_resp::wrong_expected_version::ExpectedRevisionOption::ExpectedNoStream(_) => ExpectedRevision::NoStream,
-                };
+            let expected = match error.expected_revision_option.unwrap() {
+                streams::append_resp::wrong_expected_version::ExpectedRevisionOption::ExpectedRevision(rev) => ExpectedRevision::Exact(rev),
+                streams::append_resp::wrong_expected_version::ExpectedRevisionOption::ExpectedAny(_) => ExpectedRevision::Any,
+                streams::append_resp::wrong_expected_version::ExpectedRevisionOption::ExpectedStreamExists(_) => ExpectedRevision::StreamExists,
+                streams::append_resp::wrong_expected_version::ExpectedRevisionOption::ExpectedNoStream(_) => ExpectedRevision::NoStream,
+            };
 
-                Ok(Err(WrongExpectedVersion { current, expected }))
-            }
+            Err(WrongExpectedVersion { current, expected })
         }
-    }).await
+    }
 }
 
 /// Sends asynchronously the read command to the server.
@@ -474,71 +676,91 @@ pub async fn read_stream<'a, S: AsRef<str>>(
         options: Some(options),
     };
 
-    let mut req = Request::new(req);
-
-    configure_auth_req(&mut req, credentials);
+    let metrics = connection.metrics();
 
     connection
-        .execute(|channel| async {
-            let mut client = StreamsClient::new(channel.channel.clone());
-            let mut stream = client.read(req).await?.into_inner();
-
-            if let Some(resp) = stream.try_next().await? {
-                match resp.content.as_ref().unwrap() {
-                    streams::read_resp::Content::StreamNotFound(params) => {
-                        let stream_name = std::string::String::from_utf8(
-                            params
-                                .stream_identifier
-                                .as_ref()
-                                .unwrap()
-                                .stream_name
-                                .clone(),
-                        )
-                        .expect("Don't worry this string is valid!");
-
-                        return Ok(ReadResult::StreamNotFound(stream_name));
-                    }
+        .execute(OperationKind::ReadStream, |channel| {
+            let req = new_request(req.clone(), credentials.clone());
+
+            let metrics = metrics.clone();
+
+            async move {
+                let mut client = StreamsClient::new(channel.channel.clone());
+                let mut stream = client.read(req).await?.into_inner();
+
+                if let Some(resp) = stream.try_next().await? {
+                    match resp.content.as_ref().unwrap() {
+                        streams::read_resp::Content::StreamNotFound(params) => {
+                            let stream_name = std::string::String::from_utf8(
+                                params
+                                    .stream_identifier
+                                    .as_ref()
+                                    .unwrap()
+                                    .stream_name
+                                    .clone(),
+                            )
+                            .expect("Don't worry this string is valid!");
+
+                            return Ok(ReadResult::StreamNotFound(stream_name));
+                        }
 
-                    _ => {
-                        let stream = stream! {
-                            // We send back to the user the first event we received.
-                            if let streams::read_resp::Content::Event(event) = resp.content.expect("content is defined") {
-                                yield Ok(convert_proto_read_event(event));
-                            }
+                        _ => {
+                            let stream = stream! {
+                                // We send back to the user the first event we received.
+                                if let streams::read_resp::Content::Event(event) = resp.content.expect("content is defined") {
+                                    metrics.received(OperationKind::ReadStream, event.encoded_len());
+                                    let event = convert_proto_read_event(event);
+                                    let failed = event.is_err();
 
-                            loop {
-                                match stream.try_next().await {
-                                    Err(e) => {
-                                        let e = crate::Error::from_grpc(e);
+                                    yield event;
 
-                                        channel.report_error(e.clone()).await;
-                                        yield Err(e);
-                                        break;
+                                    if failed {
+                                        return;
                                     }
+                                }
 
-                                    Ok(resp) => {
-                                        if let Some(resp) = resp {
-                                            if let streams::read_resp::Content::Event(event) = resp.content.expect("content is defined") {
-                                                yield Ok(convert_proto_read_event(event));
-                                            }
+                                loop {
+                                    match channel.next_message(&mut stream).await {
+                                        Err(e) => {
+                                            let e = crate::Error::from_grpc(e);
 
-                                            continue;
+                                            channel.report_error(e.clone()).await;
+                                            yield Err(e);
+                                            break;
                                         }
 
-                                        break;
+                                        Ok(resp) => {
+                                            if let Some(resp) = resp {
+                                                if let streams::read_resp::Content::Event(event) = resp.content.expect("content is defined") {
+                                                    metrics.received(OperationKind::ReadStream, event.encoded_len());
+                                                    let event = convert_proto_read_event(event);
+                                                    let failed = event.is_err();
+
+                                                    yield event;
+
+                                                    if failed {
+                                                        break;
+                                                    }
+                                                }
+
+                                                continue;
+                                            }
+
+                                            break;
+                                        }
                                     }
                                 }
-                            }
-                        };
+                            };
 
-                        let stream: BoxStream<crate::Result<ResolvedEvent>> = Box::pin(stream);
+                            let stream: BoxStream<crate::Result<ResolvedEvent>> = Box::pin(stream);
 
-                        return Ok(ReadResult::Ok(stream));
+                            return Ok(ReadResult::Ok(stream));
+                        }
                     }
                 }
-            }
 
-            Ok(ReadResult::Ok(Box::pin(stream::empty())))
+                Ok(ReadResult::Ok(Box::pin(stream::empty())))
+            }
         })
         .await
 }
@@ -548,6 +770,24 @@ pub async fn read_all<'a>(
     options: &ReadAllOptions,
     count: u64,
 ) -> crate::Result<BoxStream<'a, crate::Result<ResolvedEvent>>> {
+    let stream = read_all_filtered(connection, options, count).await?;
+    let stream = stream.try_filter_map(|item| async move {
+        match item {
+            ReadAllEvent::EventAppeared(event) => Ok(Some(event)),
+            ReadAllEvent::Checkpoint(_) => Ok(None),
+        }
+    });
+
+    Ok(Box::pin(stream))
+}
+
+/// Reads the $all stream, along with the checkpoints the server reports when
+/// the read is filtered with a search window.
+pub async fn read_all_filtered<'a>(
+    connection: &GrpcClient,
+    options: &ReadAllOptions,
+    count: u64,
+) -> crate::Result<BoxStream<'a, crate::Result<ReadAllEvent>>> {
     use streams::read_req::options::all_options::AllOption;
     use streams::read_req::options::{self, AllOptions, StreamOption};
     use streams::read_req::Options;
@@ -583,11 +823,16 @@ pub async fn read_all<'a>(
         .credentials
         .clone()
         .or_else(|| connection.default_credentials());
+    let exclusion = options.exclusion;
+    let filter_option = match options.filter.clone() {
+        Some(filter) => options::FilterOption::Filter(filter_into_proto(filter)),
+        None => options::FilterOption::NoFilter(Empty {}),
+    };
 
     let options = Options {
         stream_option: Some(StreamOption::All(stream_options)),
         resolve_links: options.resolve_link_tos,
-        filter_option: Some(options::FilterOption::NoFilter(Empty {})),
+        filter_option: Some(filter_option),
         count_option: Some(options::CountOption::Count(count)),
         uuid_option: Some(uuid_option),
         read_direction,
@@ -597,44 +842,77 @@ pub async fn read_all<'a>(
         options: Some(options),
     };
 
-    let mut req = Request::new(req);
-
-    configure_auth_req(&mut req, credentials);
+    let metrics = connection.metrics();
 
     connection
-        .execute(|channel| async {
-            let mut client = StreamsClient::new(channel.channel.clone());
-            let mut stream = client.read(req).await?.into_inner();
-
-            let stream = stream! {
-                loop {
-                    match stream.try_next().await {
-                        Err(e) => {
-                            let e = crate::Error::from_grpc(e);
-
-                            channel.report_error(e.clone()).await;
-                            yield Err(e);
-                            break;
-                        }
+        .execute(OperationKind::ReadAll, |channel| {
+            let req = new_request(req.clone(), credentials.clone());
 
-                        Ok(resp) => {
-                            if let Some(resp) = resp {
-                                if let streams::read_resp::Content::Event(event) = resp.content.expect("content is defined") {
-                                    yield Ok(convert_proto_read_event(event));
-                                }
+            let metrics = metrics.clone();
+
+            async move {
+                let mut client = StreamsClient::new(channel.channel.clone());
+                let mut stream = client.read(req).await?.into_inner();
+
+                let stream = stream! {
+                    loop {
+                        match channel.next_message(&mut stream).await {
+                            Err(e) => {
+                                let e = crate::Error::from_grpc(e);
 
-                                continue;
+                                channel.report_error(e.clone()).await;
+                                yield Err(e);
+                                break;
                             }
 
-                            break;
+                            Ok(resp) => {
+                                if let Some(resp) = resp {
+                                    match resp.content.expect("content is defined") {
+                                        streams::read_resp::Content::Event(event) => {
+                                            metrics.received(OperationKind::ReadAll, event.encoded_len());
+                                            let event = convert_proto_read_event(event);
+                                            let failed = event.is_err();
+
+                                            if let Ok(ref event) = event {
+                                                if exclusion.excludes(event) {
+                                                    // Keeps progress tracking moving forward.
+                                                    yield Ok(ReadAllEvent::Checkpoint(event.get_original_event().position));
+                                                    continue;
+                                                }
+                                            }
+
+                                            yield event.map(ReadAllEvent::EventAppeared);
+
+                                            if failed {
+                                                break;
+                                            }
+                                        }
+
+                                        streams::read_resp::Content::Checkpoint(chk) => {
+                                            let position = Position {
+                                                commit: chk.commit_position,
+                                                prepare: chk.prepare_position,
+                                            };
+
+                                            yield Ok(ReadAllEvent::Checkpoint(position));
+                                        }
+
+                                        _ => {}
+                                    }
+
+                                    continue;
+                                }
+
+                                break;
+                            }
                         }
                     }
-                }
-            };
+                };
 
-            let stream: BoxStream<crate::Result<ResolvedEvent>> = Box::pin(stream);
+                let stream: BoxStream<crate::Result<ReadAllEvent>> = Box::pin(stream);
 
-            Ok(stream)
+                Ok(stream)
+            }
         })
         .await
 }
@@ -644,7 +922,7 @@ pub async fn delete_stream<S: AsRef<str>>(
     connection: &GrpcClient,
     stream: S,
     options: &DeleteStreamOptions,
-) -> crate::Result<Option<Position>> {
+) -> crate::Result<Result<DeleteResult, WrongExpectedVersion>> {
     let credentials = options
         .credentials
         .clone()
@@ -671,32 +949,36 @@ pub async fn delete_stream<S: AsRef<str>>(
             expected_stream_revision,
         };
 
-        let mut req = Request::new(streams::TombstoneReq {
+        let req = streams::TombstoneReq {
             options: Some(options),
-        });
-
-        configure_auth_req(&mut req, credentials);
+        };
 
         connection
-            .execute(|channel| async {
-                let mut client = StreamsClient::new(channel.channel);
-                let result = client.tombstone(req).await?.into_inner();
-
-                if let Some(opts) = result.position_option {
-                    match opts {
-                        PositionOption::Position(pos) => {
-                            let pos = Position {
-                                commit: pos.commit_position,
-                                prepare: pos.prepare_position,
+            .execute(OperationKind::TombstoneStream, |channel| {
+                let req = new_request(req.clone(), credentials.clone());
+
+                async move {
+                    let mut client = StreamsClient::new(channel.channel);
+                    let result = match client.tombstone(req).await {
+                        Ok(resp) => resp.into_inner(),
+                        Err(status) => {
+                            return match WrongExpectedVersion::from_status(&status) {
+                                Some(error) => Ok(Err(error)),
+                                None => Err(status),
                             };
-
-                            Ok(Some(pos))
                         }
+                    };
 
-                        PositionOption::NoPosition(_) => Ok(None),
-                    }
-                } else {
-                    Ok(None)
+                    let position = match result.position_option {
+                        Some(PositionOption::Position(pos)) => Some(Position {
+                            commit: pos.commit_position,
+                            prepare: pos.prepare_position,
+                        }),
+
+                        Some(PositionOption::NoPosition(_)) | None => None,
+                    };
+
+                    Ok(Ok(DeleteResult { position }))
                 }
             })
             .await
@@ -721,32 +1003,36 @@ pub async fn delete_stream<S: AsRef<str>>(
             expected_stream_revision,
         };
 
-        let mut req = Request::new(streams::DeleteReq {
+        let req = streams::DeleteReq {
             options: Some(options),
-        });
-
-        configure_auth_req(&mut req, credentials);
+        };
 
         connection
-            .execute(|channel| async {
-                let mut client = StreamsClient::new(channel.channel);
-                let result = client.delete(req).await?.into_inner();
-
-                if let Some(opts) = result.position_option {
-                    match opts {
-                        PositionOption::Position(pos) => {
-                            let pos = Position {
-                                commit: pos.commit_position,
-                                prepare: pos.prepare_position,
+            .execute(OperationKind::DeleteStream, |channel| {
+                let req = new_request(req.clone(), credentials.clone());
+
+                async move {
+                    let mut client = StreamsClient::new(channel.channel);
+                    let result = match client.delete(req).await {
+                        Ok(resp) => resp.into_inner(),
+                        Err(status) => {
+                            return match WrongExpectedVersion::from_status(&status) {
+                                Some(error) => Ok(Err(error)),
+                                None => Err(status),
                             };
-
-                            Ok(Some(pos))
                         }
+                    };
 
-                        PositionOption::NoPosition(_) => Ok(None),
-                    }
-                } else {
-                    Ok(None)
+                    let position = match result.position_option {
+                        Some(PositionOption::Position(pos)) => Some(Position {
+                            commit: pos.commit_position,
+                            prepare: pos.prepare_position,
+                        }),
+
+                        Some(PositionOption::NoPosition(_)) | None => None,
+                    };
+
+                    Ok(Ok(DeleteResult { position }))
                 }
             })
             .await
@@ -764,6 +1050,9 @@ pub async fn subscribe_to_stream<'a, S: AsRef<str>>(
     use streams::read_req::Options;
 
     let read_direction = 0; // <- Going forward.
+    let raw_delivery = options.raw_delivery;
+    let exclusion = options.exclusion;
+    let decode_failure_policy = connection.settings().decode_failure_policy();
 
     let revision = match options.position {
         StreamPosition::Start => RevisionOption::Start(Empty {}),
@@ -788,6 +1077,17 @@ pub async fn subscribe_to_stream<'a, S: AsRef<str>>(
         .clone()
         .or_else(|| connection.default_credentials());
 
+    let notify_caught_up = options.notify_caught_up;
+
+    // The slow consumer detection only starts once the subscription is live.
+    let catch_up = if notify_caught_up || connection.subscriptions().detects_slow_consumers() {
+        let edge = stream_live_edge(connection, stream_id.as_ref(), credentials.clone()).await?;
+
+        CatchUp::new(options.position, edge)
+    } else {
+        CatchUp::Done
+    };
+
     let options = Options {
         stream_option: Some(StreamOption::Stream(stream_options)),
         resolve_links: options.resolve_link_tos,
@@ -801,53 +1101,116 @@ pub async fn subscribe_to_stream<'a, S: AsRef<str>>(
         options: Some(options),
     };
 
-    let mut req = Request::new(req);
+    let metrics = connection.metrics();
 
-    configure_auth_req(&mut req, credentials);
+    let name = stream_id.as_ref().to_string();
 
     connection
-        .execute(|channel| async {
-            let mut client = StreamsClient::new(channel.channel.clone());
-            let mut stream = client.read(req).await?.into_inner();
-
-            let stream = stream! {
-                loop {
-                    match stream.try_next().await {
-                        Err(e) => {
-                            let e = crate::Error::from_grpc(e);
-
-                            channel.report_error(e.clone()).await;
-                            yield Err(e);
-                            break;
-                        }
-
-                        Ok(resp) => {
-                            if let Some(resp) = resp {
-                                match resp.content.expect("content is defined") {
-                                    streams::read_resp::Content::Event(event) => {
-                                        yield Ok(SubEvent::EventAppeared(convert_proto_read_event(event)));
+        .execute(OperationKind::SubscribeToStream, |channel| {
+            let req = new_request(req.clone(), credentials.clone());
+
+            let metrics = metrics.clone();
+
+            async move {
+                let mut client = StreamsClient::new(channel.channel.clone());
+                let mut stream = client.read(req).await?.into_inner();
+                let connection_id = channel.id();
+
+                let stream = stream! {
+                    let mut subscription_id = None;
+                    let mut catch_up = catch_up;
+
+                    loop {
+                        match channel.next_message(&mut stream).await {
+                            Err(status) => {
+                                let e = match SubscriptionDropReason::from_status(&status) {
+                                    Some(reason) => crate::Error::SubscriptionDropped(reason),
+                                    None => {
+                                        let e = crate::Error::from_grpc(status);
+                                        channel.report_error(e.clone()).await;
+                                        e
                                     }
+                                };
 
-                                    streams::read_resp::Content::Confirmation(sub) => {
-                                        yield Ok(SubEvent::Confirmed(sub.subscription_id));
-                                    }
+                                yield Err(e);
+                                break;
+                            }
+
+                            Ok(resp) => {
+                                if let Some(resp) = resp {
+                                    match resp.content.expect("content is defined") {
+                                        streams::read_resp::Content::Event(event) => {
+                                            metrics.received(OperationKind::SubscribeToStream, event.encoded_len());
+                                            let event = match convert_proto_read_event(event) {
+                                                Ok(event) => event,
+                                                Err(e) => {
+                                                    if decode_failure_policy == DecodeFailurePolicy::SkipEvent {
+                                                        warn!("Skipping subscription event: {}", e);
+                                                        continue;
+                                                    }
+
+                                                    yield Err(e);
+                                                    break;
+                                                }
+                                            };
+
+                                            let caught_up = catch_up.on_position(event.get_original_event().revision);
+
+                                            if exclusion.excludes(&event) {
+                                                // Keeps progress tracking moving forward.
+                                                yield Ok(SubEvent::Checkpoint(event.get_original_event().position));
+                                            } else if raw_delivery {
+                                                let transport = TransportInfo {
+                                                    connection_id,
+                                                    subscription_id: subscription_id.clone(),
+                                                };
+
+                                                yield Ok(SubEvent::RawEventAppeared(RawEvent { event, transport }));
+                                            } else {
+                                                yield Ok(SubEvent::EventAppeared(event));
+                                            }
+
+                                            if caught_up {
+                                                yield Ok(SubEvent::CaughtUp);
+                                            }
+                                        }
+
+                                        streams::read_resp::Content::Confirmation(sub) => {
+                                            subscription_id = Some(sub.subscription_id.clone());
+                                            yield Ok(SubEvent::Confirmed(sub.subscription_id));
+
+                                            if catch_up.on_confirmation() {
+                                                yield Ok(SubEvent::CaughtUp);
+                                            }
+                                        }
 
-                                    _ => {}
+                                        _ => {}
+                                    }
+                                    continue;
                                 }
-                                continue;
-                            }
 
-                            break;
+                                break;
+                            }
                         }
                     }
-                }
-            };
+                };
 
-            let stream: BoxStream<crate::Result<SubEvent>> = Box::pin(stream);
+                let stream: BoxStream<crate::Result<SubEvent>> = Box::pin(stream);
 
-            Ok(stream)
+                Ok(stream)
+            }
         })
         .await
+        .map(|stream| {
+            connection
+                .subscriptions()
+                .track(
+                SubscriptionTarget::Stream(name),
+                connection.clock().clone(),
+                notify_caught_up,
+                stream,
+            )
+        })
 }
 
 pub async fn subscribe_to_all<'a>(
@@ -859,6 +1222,9 @@ pub async fn subscribe_to_all<'a>(
     use streams::read_req::Options;
 
     let read_direction = 0; // <- Going forward.
+    let raw_delivery = options.raw_delivery;
+    let exclusion = options.exclusion;
+    let decode_failure_policy = connection.settings().decode_failure_policy();
 
     let revision = match options.position {
         StreamPosition::Start => AllOption::Start(Empty {}),
@@ -887,6 +1253,17 @@ pub async fn subscribe_to_all<'a>(
         .clone()
         .or_else(|| connection.default_credentials());
 
+    let notify_caught_up = options.notify_caught_up;
+
+    // The slow consumer detection only starts once the subscription is live.
+    let catch_up = if notify_caught_up || connection.subscriptions().detects_slow_consumers() {
+        let edge = all_live_edge(connection, credentials.clone()).await?;
+
+        CatchUp::new(options.position, edge)
+    } else {
+        CatchUp::Done
+    };
+
     let options = Options {
         stream_option: Some(StreamOption::All(stream_options)),
         resolve_links: options.resolve_link_tos,
@@ -900,62 +1277,128 @@ pub async fn subscribe_to_all<'a>(
         options: Some(options),
     };
 
-    let mut req = Request::new(req);
-
-    configure_auth_req(&mut req, credentials);
+    let metrics = connection.metrics();
 
     connection
-        .execute(|channel| async {
-            let mut client = StreamsClient::new(channel.channel.clone());
-            let mut stream = client.read(req).await?.into_inner();
-
-            let stream = stream! {
-                loop {
-                    match stream.try_next().await {
-                        Err(e) => {
-                            let e = crate::Error::from_grpc(e);
-
-                            channel.report_error(e.clone()).await;
-                            yield Err(e);
-                            break;
-                        }
-
-                        Ok(resp) => {
-                            if let Some(resp) = resp {
-                                match resp.content.expect("content is defined") {
-                                    streams::read_resp::Content::Event(event) => {
-                                        yield Ok(SubEvent::EventAppeared(convert_proto_read_event(event)));
+        .execute(OperationKind::SubscribeToAll, |channel| {
+            let req = new_request(req.clone(), credentials.clone());
+
+            let metrics = metrics.clone();
+
+            async move {
+                let mut client = StreamsClient::new(channel.channel.clone());
+                let mut stream = client.read(req).await?.into_inner();
+                let connection_id = channel.id();
+
+                let stream = stream! {
+                    let mut subscription_id = None;
+                    let mut catch_up = catch_up;
+
+                    loop {
+                        match channel.next_message(&mut stream).await {
+                            Err(status) => {
+                                let e = match SubscriptionDropReason::from_status(&status) {
+                                    Some(reason) => crate::Error::SubscriptionDropped(reason),
+                                    None => {
+                                        let e = crate::Error::from_grpc(status);
+                                        channel.report_error(e.clone()).await;
+                                        e
                                     }
+                                };
 
-                                    streams::read_resp::Content::Confirmation(sub) => {
-                                        yield Ok(SubEvent::Confirmed(sub.subscription_id));
-                                    }
+                                yield Err(e);
+                                break;
+                            }
 
-                                    streams::read_resp::Content::Checkpoint(chk) => {
-                                        let position = Position {
-                                            commit: chk.commit_position,
-                                            prepare: chk.prepare_position,
-                                        };
+                            Ok(resp) => {
+                                if let Some(resp) = resp {
+                                    match resp.content.expect("content is defined") {
+                                        streams::read_resp::Content::Event(event) => {
+                                            metrics.received(OperationKind::SubscribeToAll, event.encoded_len());
+                                            let event = match convert_proto_read_event(event) {
+                                                Ok(event) => event,
+                                                Err(e) => {
+                                                    if decode_failure_policy == DecodeFailurePolicy::SkipEvent {
+                                                        warn!("Skipping subscription event: {}", e);
+                                                        continue;
+                                                    }
+
+                                                    yield Err(e);
+                                                    break;
+                                                }
+                                            };
+
+                                            let position = event.get_original_event().position;
+                                            let caught_up = catch_up.on_position(position);
+
+                                            if exclusion.excludes(&event) {
+                                                // Keeps progress tracking moving forward.
+                                                yield Ok(SubEvent::Checkpoint(position));
+                                            } else if raw_delivery {
+                                                let transport = TransportInfo {
+                                                    connection_id,
+                                                    subscription_id: subscription_id.clone(),
+                                                };
+
+                                                yield Ok(SubEvent::RawEventAppeared(RawEvent { event, transport }));
+                                            } else {
+                                                yield Ok(SubEvent::EventAppeared(event));
+                                            }
 
-                                        yield Ok(SubEvent::Checkpoint(position));
-                                    }
+                                            if caught_up {
+                                                yield Ok(SubEvent::CaughtUp);
+                                            }
+                                        }
+
+                                        streams::read_resp::Content::Confirmation(sub) => {
+                                            subscription_id = Some(sub.subscription_id.clone());
+                                            yield Ok(SubEvent::Confirmed(sub.subscription_id));
+
+                                            if catch_up.on_confirmation() {
+                                                yield Ok(SubEvent::CaughtUp);
+                                            }
+                                        }
+
+                                        streams::read_resp::Content::Checkpoint(chk) => {
+                                            let position = Position {
+                                                commit: chk.commit_position,
+                                                prepare: chk.prepare_position,
+                                            };
 
-                                    _ => {}
+                                            yield Ok(SubEvent::Checkpoint(position));
+
+                                            if catch_up.on_position(position) {
+                                                yield Ok(SubEvent::CaughtUp);
+                                            }
+                                        }
+
+                                        _ => {}
+                                    }
+                                    continue;
                                 }
-                                continue;
-                            }
 
-                            break;
+                                break;
+                            }
                         }
                     }
-                }
-            };
+                };
 
-            let stream: BoxStream<'a, crate::Result<SubEvent>> = Box::pin(stream);
+                let stream: BoxStream<'a, crate::Result<SubEvent>> = Box::pin(stream);
 
-            Ok(stream)
+                Ok(stream)
+            }
         })
         .await
+        .map(|stream| {
+            connection
+                .subscriptions()
+                .track(
+                SubscriptionTarget::All,
+                connection.clock().clone(),
+                notify_caught_up,
+                stream,
+            )
+        })
 }
 
 pub async fn create_persistent_subscription<S: AsRef<str>>(
@@ -1009,16 +1452,16 @@ pub async fn create_persistent_subscription<S: AsRef<str>>(
         options: Some(options),
     };
 
-    let mut req = Request::new(req);
-
-    configure_auth_req(&mut req, credentials);
-
     connection
-        .execute(|channel| async {
-            let mut client = PersistentSubscriptionsClient::new(channel.channel);
-            client.create(req).await?;
+        .execute(OperationKind::CreatePersistentSubscription, |channel| {
+            let req = new_request(req.clone(), credentials.clone());
+
+            async move {
+                let mut client = PersistentSubscriptionsClient::new(channel.channel);
+                client.create(req).await?;
 
-            Ok(())
+                Ok(())
+            }
         })
         .await
 }
@@ -1074,16 +1517,16 @@ pub async fn update_persistent_subscription<S: AsRef<str>>(
         options: Some(options),
     };
 
-    let mut req = Request::new(req);
-
-    configure_auth_req(&mut req, credentials);
-
     connection
-        .execute(|channel| async {
-            let mut client = PersistentSubscriptionsClient::new(channel.channel);
-            client.update(req).await?;
+        .execute(OperationKind::UpdatePersistentSubscription, |channel| {
+            let req = new_request(req.clone(), credentials.clone());
+
+            async move {
+                let mut client = PersistentSubscriptionsClient::new(channel.channel);
+                client.update(req).await?;
 
-            Ok(())
+                Ok(())
+            }
         })
         .await
 }
@@ -1117,16 +1560,16 @@ pub async fn delete_persistent_subscription<S: AsRef<str>>(
         options: Some(options),
     };
 
-    let mut req = Request::new(req);
-
-    configure_auth_req(&mut req, credentials);
-
     connection
-        .execute(|channel| async {
-            let mut client = PersistentSubscriptionsClient::new(channel.channel);
-            client.delete(req).await?;
+        .execute(OperationKind::DeletePersistentSubscription, |channel| {
+            let req = new_request(req.clone(), credentials.clone());
+
+            async move {
+                let mut client = PersistentSubscriptionsClient::new(channel.channel);
+                client.delete(req).await?;
 
-            Ok(())
+                Ok(())
+            }
         })
         .await
 }
@@ -1146,8 +1589,6 @@ pub async fn connect_persistent_subscription<S: AsRef<str>>(
     use persistent::read_resp;
     use persistent::ReadReq;
 
-    let (mut sender, recv) = mpsc::channel(500);
-
     let uuid_option = UuidOption {
         content: Some(options::uuid_option::Content::String(Empty {})),
     };
@@ -1159,6 +1600,7 @@ pub async fn connect_persistent_subscription<S: AsRef<str>>(
     let stream_option = StreamOption::StreamIdentifier(stream_identifier);
     let stream_option = Some(stream_option);
 
+    let decode_failure_policy = connection.settings().decode_failure_policy();
     let credentials = options
         .credentials
         .clone()
@@ -1175,56 +1617,95 @@ pub async fn connect_persistent_subscription<S: AsRef<str>>(
         content: Some(read_req::Content::Options(options)),
     };
 
-    let mut req = Request::new(recv);
-
-    configure_auth_req(&mut req, credentials);
-
-    let _ = sender.send(read_req).await;
+    let metrics = connection.metrics();
 
     connection
-        .execute(|channel| async {
-            let mut client = PersistentSubscriptionsClient::new(channel.channel.clone());
-            let mut stream = client.read(req).await?.into_inner();
-
-            let stream = stream! {
-                loop {
-                    match stream.try_next().await {
-                        Err(e) => {
-                            let e = crate::Error::from_grpc(e);
-
-                            channel.report_error(e.clone()).await;
-                            yield Err(e);
-                            break;
-                        }
-
-                        Ok(resp) => {
-                            if let Some(resp) = resp {
-                                match resp.content.expect("content is defined") {
-                                    read_resp::Content::Event(event) => {
-                                        yield Ok(SubEvent::EventAppeared(convert_persistent_proto_read_event(event)));
+        .execute(OperationKind::ConnectPersistentSubscription, |channel| {
+            let (mut sender, recv) = mpsc::channel(500);
+            let req = new_request(recv, credentials.clone());
+            let read_req = read_req.clone();
+
+            let metrics = metrics.clone();
+
+            async move {
+                let _ = sender.send(read_req).await;
+                let mut client = PersistentSubscriptionsClient::new(channel.channel.clone());
+                let mut stream = client.read(req).await?.into_inner();
+
+                let stream = stream! {
+                    loop {
+                        match channel.next_message(&mut stream).await {
+                            Err(status) => {
+                                let e = match SubscriptionDropReason::from_status(&status) {
+                                    Some(reason) => crate::Error::SubscriptionDropped(reason),
+                                    None => {
+                                        let e = crate::Error::from_grpc(status);
+                                        channel.report_error(e.clone()).await;
+                                        e
                                     }
+                                };
+
+                                yield Err(e);
+                                break;
+                            }
+
+                            Ok(resp) => {
+                                if let Some(resp) = resp {
+                                    match resp.content.expect("content is defined") {
+                                        read_resp::Content::Event(event) => {
+                                            metrics.received(OperationKind::ConnectPersistentSubscription, event.encoded_len());
+                                            let event = match convert_persistent_proto_read_event(event) {
+                                                Ok(event) => event,
+                                                Err(e) => {
+                                                    if decode_failure_policy == DecodeFailurePolicy::SkipEvent {
+                                                        warn!("Skipping subscription event: {}", e);
+                                                        continue;
+                                                    }
+
+                                                    yield Err(e);
+                                                    break;
+                                                }
+                                            };
+
+                                            yield Ok(SubEvent::EventAppeared(event));
+                                        }
 
-                                    read_resp::Content::SubscriptionConfirmation(sub) => {
-                                        yield Ok(SubEvent::Confirmed(sub.subscription_id));
+                                        read_resp::Content::SubscriptionConfirmation(sub) => {
+                                            yield Ok(SubEvent::Confirmed(sub.subscription_id));
+                                        }
                                     }
+                                    continue;
                                 }
-                                continue;
-                            }
 
-                            break;
+                                break;
+                            }
                         }
                     }
-                }
-            };
+                };
 
-            let read = SubscriptionRead {
-                inner: Box::pin(stream),
-            };
-            let write = SubscriptionWrite { sender };
+                let read = SubscriptionRead {
+                    inner: Box::pin(stream),
+                };
+                let write = SubscriptionWrite { sender };
 
-            Ok((read, write))
+                Ok((read, write))
+            }
         })
         .await
+        .map(|(read, write)| {
+            let target = SubscriptionTarget::Persistent {
+                stream: stream_id.as_ref().to_string(),
+                group: group_name.as_ref().to_string(),
+            };
+            let inner = connection.subscriptions().track_persistent(
+                target,
+                connection.clock().clone(),
+                write.sender.clone(),
+                read.inner,
+            );
+
+            (SubscriptionRead { inner }, write)
+        })
 }
 
 pub struct SubscriptionRead {
@@ -1266,25 +1747,14 @@ impl SubscriptionWrite {
         I: IntoIterator<Item = uuid::Uuid>,
     {
         use futures::sink::SinkExt;
-        use persistent::read_req::{Ack, Content};
-        use persistent::ReadReq;
 
-        let ids = event_ids.into_iter().map(to_proto_uuid).collect();
-        let ack = Ack {
-            id: Vec::new(),
-            ids,
-        };
-
-        let content = Content::Ack(ack);
-        let read_req = ReadReq {
-            content: Some(content),
-        };
-
-        let _ = self.sender.send(read_req).await;
+        let _ = self.sender.send(ack_request(event_ids)).await;
 
         Ok(())
     }
 
+    /// Negatively acknowledges several events in a single request, the
+    /// server applying the given action to each of them.
     pub async fn nack<I>(
         &mut self,
         event_ids: I,
@@ -1292,36 +1762,254 @@ impl SubscriptionWrite {
         reason: String,
     ) -> Result<(), tonic::Status>
     where
-        I: Iterator<Item = uuid::Uuid>,
+        I: IntoIterator<Item = uuid::Uuid>,
     {
         use futures::sink::SinkExt;
-        use persistent::read_req::{Content, Nack};
-        use persistent::ReadReq;
 
-        let ids = event_ids.map(to_proto_uuid).collect();
+        let _ = self
+            .sender
+            .send(nack_request(event_ids, action, reason))
+            .await;
 
-        let action = match action {
-            NakAction::Unknown => 0,
-            NakAction::Park => 1,
-            NakAction::Retry => 2,
-            NakAction::Skip => 3,
-            NakAction::Stop => 4,
-        };
+        Ok(())
+    }
+}
+
+fn ack_request<I>(event_ids: I) -> persistent::ReadReq
+where
+    I: IntoIterator<Item = uuid::Uuid>,
+{
+    use persistent::read_req::{Ack, Content};
+
+    let ids = event_ids.into_iter().map(to_proto_uuid).collect();
+    let ack = Ack {
+        id: Vec::new(),
+        ids,
+    };
+
+    persistent::ReadReq {
+        content: Some(Content::Ack(ack)),
+    }
+}
+
+fn nack_request<I>(event_ids: I, action: NakAction, reason: String) -> persistent::ReadReq
+where
+    I: IntoIterator<Item = uuid::Uuid>,
+{
+    use persistent::read_req::{Content, Nack};
+
+    let ids = event_ids.into_iter().map(to_proto_uuid).collect();
+
+    let action = match action {
+        NakAction::Unknown => 0,
+        NakAction::Park => 1,
+        NakAction::Retry => 2,
+        NakAction::Skip => 3,
+        NakAction::Stop => 4,
+    };
+
+    let nack = Nack {
+        id: Vec::new(),
+        ids,
+        action,
+        reason,
+    };
+
+    persistent::ReadReq {
+        content: Some(Content::Nack(nack)),
+    }
+}
+
+/// Settles a single event delivered by
+/// `Client::connect_persistent_subscription_stream`.
+///
+/// Every event must be acknowledged or negatively acknowledged. Dropping the
+/// handle without doing so applies the [`UnsettledEventPolicy`] the
+/// subscription was connected with.
+pub struct AckHandle {
+    event_id: uuid::Uuid,
+    // `None` once the event is settled.
+    sender: Option<futures::channel::mpsc::Sender<persistent::ReadReq>>,
+    policy: UnsettledEventPolicy,
+}
+
+impl AckHandle {
+    /// Id of the event the handle settles. For a resolved link, that's the
+    /// id of the link.
+    pub fn event_id(&self) -> uuid::Uuid {
+        self.event_id
+    }
+
+    /// Acknowledges the event.
+    pub async fn ack(mut self) -> Result<(), tonic::Status> {
+        use futures::sink::SinkExt;
+
+        if let Some(mut sender) = self.sender.take() {
+            let _ = sender.send(ack_request(vec![self.event_id])).await;
+        }
+
+        Ok(())
+    }
 
-        let nack = Nack {
-            id: Vec::new(),
-            ids,
-            action,
-            reason,
+    /// Negatively acknowledges the event, the server applying the given
+    /// action.
+    pub async fn nack<R: Into<String>>(
+        mut self,
+        action: NakAction,
+        reason: R,
+    ) -> Result<(), tonic::Status> {
+        use futures::sink::SinkExt;
+
+        if let Some(mut sender) = self.sender.take() {
+            let req = nack_request(vec![self.event_id], action, reason.into());
+            let _ = sender.send(req).await;
+        }
+
+        Ok(())
+    }
+}
+
+impl Drop for AckHandle {
+    fn drop(&mut self) {
+        if let Some(sender) = self.sender.as_mut() {
+            if self.policy == UnsettledEventPolicy::NackRetry {
+                let req = nack_request(
+                    vec![self.event_id],
+                    NakAction::Retry,
+                    "Event handle dropped without being settled".to_string(),
+                );
+
+                if sender.try_send(req).is_err() {
+                    warn!(
+                        "Unable to retry unsettled event {}, the server will retry it on timeout",
+                        self.event_id
+                    );
+                }
+            }
+        }
+    }
+}
+
+/// Pairs every event of a persistent subscription with the handle settling
+/// it. Events are only pulled from the server as the stream is consumed,
+/// the server never delivering more unsettled events than the subscription
+/// buffer size.
+pub(crate) fn ack_stream(
+    read: SubscriptionRead,
+    write: SubscriptionWrite,
+    policy: UnsettledEventPolicy,
+) -> BoxStream<'static, crate::Result<(ResolvedEvent, AckHandle)>> {
+    let sender = write.sender;
+
+    Box::pin(read.inner.try_filter_map(move |event| {
+        let item = match event {
+            SubEvent::EventAppeared(event) => {
+                let handle = AckHandle {
+                    event_id: event.get_original_event().id,
+                    sender: Some(sender.clone()),
+                    policy,
+                };
+
+                Some((event, handle))
+            }
+
+            _ => None,
         };
 
-        let content = Content::Nack(nack);
-        let read_req = ReadReq {
-            content: Some(content),
+        futures::future::ready(Ok(item))
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{AckHandle, CatchUp, SubscriptionWrite};
+    use crate::event_store::client::persistent::read_req::Content;
+    use crate::{NakAction, StreamPosition, UnsettledEventPolicy};
+    use futures::channel::mpsc;
+
+    fn handle(
+        policy: UnsettledEventPolicy,
+    ) -> (
+        AckHandle,
+        mpsc::Receiver<crate::event_store::client::persistent::ReadReq>,
+    ) {
+        let (sender, recv) = mpsc::channel(1);
+        let handle = AckHandle {
+            event_id: uuid::Uuid::new_v4(),
+            sender: Some(sender),
+            policy,
         };
 
-        let _ = self.sender.send(read_req).await;
+        (handle, recv)
+    }
 
-        Ok(())
+    #[test]
+    fn dropped_handle_nacks_per_policy() {
+        let (retried, mut recv) = handle(UnsettledEventPolicy::NackRetry);
+        drop(retried);
+
+        match recv.try_recv().ok().and_then(|req| req.content) {
+            Some(Content::Nack(nack)) => assert_eq!(nack.action, 2),
+            other => panic!("Expected a retry nack, got {:?}", other),
+        }
+
+        let (ignored, mut recv) = handle(UnsettledEventPolicy::Ignore);
+        drop(ignored);
+
+        assert!(recv.try_recv().is_err());
+    }
+
+    #[tokio::test]
+    async fn settled_handle_sends_nothing_on_drop() {
+        let (settled, mut recv) = handle(UnsettledEventPolicy::NackRetry);
+
+        settled.ack().await.unwrap();
+
+        assert!(matches!(
+            recv.try_recv().ok().and_then(|req| req.content),
+            Some(Content::Ack(_))
+        ));
+        assert!(recv.try_recv().is_err());
+    }
+
+    #[tokio::test]
+    async fn nack_sends_the_batch_in_one_request() {
+        let (sender, mut recv) = mpsc::channel(1);
+        let mut write = SubscriptionWrite { sender };
+        let ids = vec![uuid::Uuid::new_v4(), uuid::Uuid::new_v4()];
+
+        write
+            .nack(ids, NakAction::Park, "Unsupported".to_string())
+            .await
+            .unwrap();
+
+        match recv.try_recv().ok().and_then(|req| req.content) {
+            Some(Content::Nack(nack)) => assert_eq!((nack.ids.len(), nack.action), (2, 1)),
+            other => panic!("Expected a park nack, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn catch_up_ends_at_live_edge() {
+        let mut catch_up = CatchUp::new(StreamPosition::Start, Some(2u64));
+
+        assert!(!catch_up.on_confirmation());
+        assert!(!catch_up.on_position(1));
+        assert!(catch_up.on_position(2));
+        assert!(!catch_up.on_position(3));
+    }
+
+    #[test]
+    fn catch_up_ends_on_confirmation_when_already_live() {
+        for (start, edge) in [
+            (StreamPosition::Start, None),
+            (StreamPosition::End, Some(2u64)),
+            (StreamPosition::Position(2), Some(2)),
+        ] {
+            let mut catch_up = CatchUp::new(start, edge);
+
+            assert!(catch_up.on_confirmation());
+            assert!(!catch_up.on_confirmation());
+        }
     }
 }
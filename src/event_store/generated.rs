@@ -1,4 +1,5 @@
-#![allow(clippy::large_enum_variant)]
+#![allow(dead_code)]
+#![allow(clippy::large_enum_variant, clippy::enum_variant_names)]
 pub mod client;
 pub mod google_rpc;
 pub mod gossip;
@@ -22,6 +22,13 @@ impl Gossip {
         Gossip { inner }
     }
 
+    /// Sends a gossip request, discarding the response without decoding it.
+    pub async fn ping(&self) -> Result<(), Status> {
+        self.inner.clone().read(Request::new(Empty {})).await?;
+
+        Ok(())
+    }
+
     pub async fn read(&self) -> Result<Vec<MemberInfo>, Status> {
         let wire_members = self
             .inner
@@ -76,7 +83,8 @@ impl Gossip {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
+#[allow(dead_code)]
 pub struct MemberInfo {
     pub instance_id: Uuid,
     pub time_stamp: i64,
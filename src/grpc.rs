@@ -1,25 +1,42 @@
+use crate::clock::Clock;
 use crate::gossip::{Gossip, MemberInfo, VNodeState};
-use crate::types::{Endpoint, GrpcConnectionError};
-use crate::{Credentials, DnsClusterSettings, Either, NodePreference};
+use crate::listener::{OperationListener, OperationOutcome};
+use crate::metrics::{Metrics, OperationKind, OperationStats};
+use crate::operation;
+use crate::retry_policy::{RetryAttempt, RetryDecision, RetryPolicy, SettingsRetryPolicy};
+use crate::subscriptions::SubscriptionRegistry;
+use crate::throttle::{self, Throttle, ThrottleState};
+use crate::time_skew::{self, ServerTimeSkew, SharedTimeSkew};
+use crate::topology::{self as topo, ClusterTopology};
+use crate::types::{Endpoint, GrpcConnectionError, Retries, RetryReason};
+use crate::{
+    Credentials, DecodeFailurePolicy, DnsClusterSettings, Either, EmptyAppendBehavior,
+    NodePreference, NodeRole, NotLeaderBehavior,
+};
 use futures::channel::mpsc::UnboundedSender;
 use futures::channel::oneshot;
 use futures::stream::StreamExt;
 use futures::{Future, SinkExt};
 use nom::branch::alt;
-use nom::bytes::complete::take_while;
+use nom::bytes::complete::{take_while, take_while1};
 use nom::combinator::{all_consuming, complete, opt};
 use nom::error::ErrorKind;
 use nom::lib::std::fmt::Formatter;
 use nom::{bytes::complete::tag, IResult};
 use rand::rngs::SmallRng;
 use rand::seq::SliceRandom;
-use rand::{RngCore, SeedableRng};
+use rand::{Rng, RngCore, SeedableRng};
 use serde::de::Visitor;
 use serde::{Deserialize, Serialize};
 use serde::{Deserializer, Serializer};
 use std::cmp::Ordering;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
 use std::str::FromStr;
-use std::time::Duration;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::watch;
+use tokio::task::JoinHandle;
 use tonic::transport::Channel;
 use tonic::Status;
 use uuid::Uuid;
@@ -38,6 +55,127 @@ impl rustls::ServerCertVerifier for NoVerification {
     }
 }
 
+const PIN_MISMATCH: &str = "Server certificate doesn't match any pinned fingerprint";
+
+/// Accepts a server certificate only if its SHA-256 fingerprint is pinned.
+/// Certificate authorities aren't involved.
+struct PinnedCertificates {
+    fingerprints: Vec<String>,
+}
+
+impl rustls::ServerCertVerifier for PinnedCertificates {
+    fn verify_server_cert(
+        &self,
+        _roots: &rustls::RootCertStore,
+        presented_certs: &[rustls::Certificate],
+        _dns_name: webpki::DNSNameRef,
+        _ocsp_response: &[u8],
+    ) -> Result<rustls::ServerCertVerified, rustls::TLSError> {
+        let leaf = presented_certs
+            .first()
+            .ok_or(rustls::TLSError::NoCertificatesPresented)?;
+        let fingerprint = certificate_fingerprint(&leaf.0);
+
+        if self.fingerprints.contains(&fingerprint) {
+            Ok(rustls::ServerCertVerified::assertion())
+        } else {
+            warn!(
+                "Server certificate fingerprint {} isn't pinned",
+                fingerprint
+            );
+
+            Err(rustls::TLSError::General(PIN_MISMATCH.to_string()))
+        }
+    }
+}
+
+fn certificate_fingerprint(der: &[u8]) -> String {
+    ring::digest::digest(&ring::digest::SHA256, der)
+        .as_ref()
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
+
+/// Normalizes a SHA-256 fingerprint to lowercase hexadecimal digits, with or
+/// without colon separators.
+fn parse_fingerprint(value: &str) -> Option<String> {
+    let fingerprint: String = value
+        .chars()
+        .filter(|c| *c != ':')
+        .map(|c| c.to_ascii_lowercase())
+        .collect();
+
+    if fingerprint.len() == 64 && fingerprint.chars().all(|c| c.is_ascii_hexdigit()) {
+        Some(fingerprint)
+    } else {
+        None
+    }
+}
+
+/// Tells if a channel couldn't be created because the server certificate
+/// isn't pinned.
+fn is_pin_mismatch(err: &tonic::transport::Error) -> bool {
+    let mut source: Option<&(dyn std::error::Error + 'static)> = Some(err);
+
+    while let Some(current) = source {
+        // `std::io::Error` doesn't expose the error it wraps as its source.
+        let wrapped = current
+            .downcast_ref::<std::io::Error>()
+            .and_then(|err| err.get_ref())
+            .map(|err| err as &(dyn std::error::Error + 'static));
+
+        for candidate in std::iter::once(current).chain(wrapped) {
+            if let Some(rustls::TLSError::General(message)) =
+                candidate.downcast_ref::<rustls::TLSError>()
+            {
+                if message == PIN_MISMATCH {
+                    return true;
+                }
+            }
+        }
+
+        source = current.source();
+    }
+
+    false
+}
+
+#[test]
+fn test_pinned_certificate_verification() {
+    use rustls::ServerCertVerifier;
+
+    let certificates = vec![rustls::Certificate(b"not really a certificate".to_vec())];
+    let dns_name = webpki::DNSNameRef::try_from_ascii_str("localhost").unwrap();
+    let roots = rustls::RootCertStore::empty();
+    let pinned = PinnedCertificates {
+        fingerprints: vec![certificate_fingerprint(&certificates[0].0)],
+    };
+    let other = PinnedCertificates {
+        fingerprints: vec!["00".repeat(32)],
+    };
+
+    assert!(pinned
+        .verify_server_cert(&roots, &certificates, dns_name, &[])
+        .is_ok());
+
+    match other.verify_server_cert(&roots, &certificates, dns_name, &[]) {
+        Err(rustls::TLSError::General(message)) => assert_eq!(message, PIN_MISMATCH),
+        result => panic!("Unexpected verification result: {:?}", result.is_ok()),
+    }
+}
+
+#[test]
+fn test_parse_fingerprint() {
+    let hex = "ab".repeat(32);
+    let colons = vec!["AB"; 32].join(":");
+
+    assert_eq!(parse_fingerprint(&hex), Some(hex.clone()));
+    assert_eq!(parse_fingerprint(&colons), Some(hex));
+    assert_eq!(parse_fingerprint("abcd"), None);
+    assert_eq!(parse_fingerprint(&"zz".repeat(32)), None);
+}
+
 #[test]
 fn test_connection_string() {
     #[derive(Debug, Serialize, Deserialize)]
@@ -86,6 +224,527 @@ impl std::fmt::Display for ClientSettingsParseError {
 
 impl std::error::Error for ClientSettingsParseError {}
 
+/// Invalid combination of settings, reported by [`ClientSettingsBuilder::build`].
+#[derive(thiserror::Error, Clone, Debug, PartialEq, Eq)]
+pub enum SettingsError {
+    #[error("At least one host is required")]
+    NoHost,
+    #[error("DNS discovery requires a single domain name, got {0} hosts")]
+    DiscoveryRequiresSingleHost(usize),
+    #[error("Certificate verification requires a host name, got IP address {0:?}")]
+    TlsRequiresHostName(Endpoint),
+    #[error("Keep-alive interval {interval:?} must not exceed keep-alive timeout {timeout:?}")]
+    KeepAliveIntervalExceedsTimeout {
+        interval: Duration,
+        timeout: Duration,
+    },
+    #[error("Maximum discovery attempt count must be greater than zero")]
+    ZeroMaxDiscoverAttempts,
+    #[error("Connection identification {0:?} is not a valid user agent")]
+    InvalidConnectionIdentification(String),
+    #[error("Certificate pinning requires a secure connection with certificate verification")]
+    PinningRequiresCertificateVerification,
+    #[error("Certificate fingerprint {0:?} is not a SHA-256 hexadecimal digest")]
+    InvalidCertificateFingerprint(String),
+    #[error("Certificate pinning can't be combined with a certificate authority file")]
+    PinningExcludesCaFile,
+    #[error("Certificate authority file {path:?} can't be used: {reason}")]
+    UnreadableCaFile { path: PathBuf, reason: String },
+    #[error("Retry backoff multiplier must be greater than zero")]
+    ZeroRetryBackoffMultiplier,
+    #[error("Maximum number of operations in flight must be greater than zero")]
+    ZeroMaxInFlight,
+}
+
+#[test]
+fn test_settings_builder_valid() {
+    let setts = ClientSettings::builder()
+        .host(Endpoint {
+            host: "localhost".to_string(),
+            port: 2113,
+        })
+        .build()
+        .unwrap();
+
+    assert_eq!(setts.hosts().len(), 1);
+    assert!(setts.is_secure_mode_enabled());
+}
+
+#[test]
+fn test_settings_builder_no_host() {
+    assert_eq!(
+        ClientSettings::builder().build(),
+        Err(SettingsError::NoHost)
+    );
+}
+
+#[test]
+fn test_settings_builder_discovery_with_multiple_hosts() {
+    let result = ClientSettings::builder()
+        .dns_discover(true)
+        .host(Endpoint {
+            host: "node1.mydomain".to_string(),
+            port: 2113,
+        })
+        .host(Endpoint {
+            host: "node2.mydomain".to_string(),
+            port: 2113,
+        })
+        .build();
+
+    assert_eq!(result, Err(SettingsError::DiscoveryRequiresSingleHost(2)));
+}
+
+#[test]
+fn test_settings_builder_tls_without_host_name() {
+    let endpoint = Endpoint {
+        host: "127.0.0.1".to_string(),
+        port: 2113,
+    };
+    let result = ClientSettings::builder().host(endpoint.clone()).build();
+
+    assert_eq!(
+        result,
+        Err(SettingsError::TlsRequiresHostName(endpoint.clone()))
+    );
+
+    let result = ClientSettings::builder()
+        .host(endpoint)
+        .tls_pinned_fingerprints(vec!["ab".repeat(32)])
+        .build();
+
+    assert!(result.is_ok());
+
+    let result = ClientSettings::builder()
+        .host(Endpoint {
+            host: "127.0.0.1".to_string(),
+            port: 2113,
+        })
+        .tls_verify_cert(false)
+        .build();
+
+    assert!(result.is_ok());
+}
+
+#[test]
+fn test_settings_builder_keep_alive_interval_exceeds_timeout() {
+    let result = ClientSettings::builder()
+        .host(Endpoint {
+            host: "localhost".to_string(),
+            port: 2113,
+        })
+        .keep_alive_interval(Duration::from_secs(20))
+        .keep_alive_timeout(Duration::from_secs(5))
+        .build();
+
+    assert_eq!(
+        result,
+        Err(SettingsError::KeepAliveIntervalExceedsTimeout {
+            interval: Duration::from_secs(20),
+            timeout: Duration::from_secs(5),
+        })
+    );
+}
+
+#[test]
+fn test_settings_builder_disabled_keep_alive_interval() {
+    let result = ClientSettings::builder()
+        .host(Endpoint {
+            host: "localhost".to_string(),
+            port: 2113,
+        })
+        .keep_alive_interval(Duration::from_millis(u64::MAX))
+        .build();
+
+    assert!(result.is_ok());
+
+    let settings = "esdb://localhost?keepAliveInterval=-1"
+        .parse::<ClientSettings>()
+        .unwrap();
+
+    assert_eq!(settings.validate(), Ok(()));
+}
+
+#[test]
+fn test_settings_builder_zero_retry_backoff_multiplier() {
+    let result = ClientSettings::builder()
+        .host(Endpoint {
+            host: "localhost".to_string(),
+            port: 2113,
+        })
+        .retry_backoff_multiplier(0)
+        .build();
+
+    assert_eq!(result, Err(SettingsError::ZeroRetryBackoffMultiplier));
+}
+
+#[test]
+fn test_settings_builder_zero_max_in_flight() {
+    let result = ClientSettings::builder()
+        .host(Endpoint {
+            host: "localhost".to_string(),
+            port: 2113,
+        })
+        .max_in_flight(0)
+        .build();
+
+    assert_eq!(result, Err(SettingsError::ZeroMaxInFlight));
+}
+
+#[test]
+fn test_settings_builder_zero_max_discover_attempts() {
+    let result = ClientSettings::builder()
+        .host(Endpoint {
+            host: "localhost".to_string(),
+            port: 2113,
+        })
+        .max_discover_attempts(0)
+        .build();
+
+    assert_eq!(result, Err(SettingsError::ZeroMaxDiscoverAttempts));
+}
+
+#[test]
+fn test_settings_builder_pinning_with_ca_file() {
+    let result = ClientSettings::builder()
+        .host(Endpoint {
+            host: "localhost".to_string(),
+            port: 2113,
+        })
+        .tls_pinned_fingerprints(vec!["ab".repeat(32)])
+        .tls_ca_file("tests/fixtures/ca.crt")
+        .build();
+
+    assert_eq!(result, Err(SettingsError::PinningExcludesCaFile));
+}
+
+#[test]
+fn test_settings_builder_unreadable_ca_file() {
+    let builder = || {
+        ClientSettings::builder().host(Endpoint {
+            host: "localhost".to_string(),
+            port: 2113,
+        })
+    };
+
+    let missing = std::env::temp_dir().join(format!("esdb-ca-{}.pem", Uuid::new_v4()));
+    let result = builder().tls_ca_file(missing.as_path()).build();
+
+    assert!(matches!(
+        result,
+        Err(SettingsError::UnreadableCaFile { ref path, .. }) if path == &missing
+    ));
+
+    let empty = std::env::temp_dir().join(format!("esdb-ca-{}.pem", Uuid::new_v4()));
+    std::fs::write(&empty, "not a certificate").unwrap();
+    let result = builder().tls_ca_file(empty.as_path()).build();
+    std::fs::remove_file(&empty).unwrap();
+
+    assert!(matches!(
+        result,
+        Err(SettingsError::UnreadableCaFile { ref reason, .. }) if reason == "no PEM certificate found"
+    ));
+}
+
+#[cfg(unix)]
+#[test]
+fn test_parse_unix_socket() {
+    let setts = "unix:///var/run/esdb.sock?tlsVerifyCert=false"
+        .parse::<ClientSettings>()
+        .unwrap();
+
+    assert_eq!(
+        setts.unix_socket(),
+        Some(std::path::Path::new("/var/run/esdb.sock"))
+    );
+    assert_eq!(setts.hosts().len(), 1);
+    assert!(!setts.is_secure_mode_enabled());
+    assert!(!setts.tls_verify_cert);
+    assert!(setts.validate().is_ok());
+
+    let setts = "unix:///var/run/esdb.sock?tls=true"
+        .parse::<ClientSettings>()
+        .unwrap();
+
+    assert!(setts.is_secure_mode_enabled());
+
+    let built = ClientSettings::builder()
+        .unix_socket("/var/run/esdb.sock")
+        .build()
+        .unwrap();
+
+    assert_eq!(
+        built.unix_socket(),
+        Some(std::path::Path::new("/var/run/esdb.sock"))
+    );
+    assert!(!built.is_secure_mode_enabled());
+}
+
+#[tokio::test]
+async fn test_get_handle_fails_after_max_queue_wait() {
+    use crate::clock::{SystemClock, TestClock};
+
+    let setts = "esdb://localhost:1?tls=false&maxDiscoverAttempts=100&discoveryInterval=60000&maxQueueWait=500"
+        .parse::<ClientSettings>()
+        .unwrap();
+    let clock = TestClock::new();
+    // The reconnection delays stay on the system clock, so only the wait of
+    // the operation moves with the test clock.
+    let client = GrpcClient::create(setts, None, Arc::new(SystemClock))
+        .await
+        .unwrap()
+        .with_clock(Arc::new(clock.clone()));
+    let handle = tokio::spawn(async move { client.get_handle().await.map(|_| ()) });
+
+    while clock.pending_sleeps() == 0 {
+        tokio::task::yield_now().await;
+    }
+
+    clock.advance(Duration::from_millis(500));
+
+    assert!(matches!(
+        handle.await.unwrap(),
+        Err(crate::Error::GrpcConnectionError(
+            GrpcConnectionError::ConnectionUnavailable(_)
+        ))
+    ));
+}
+
+#[tokio::test]
+async fn test_warm_up_fails_after_connect_timeout() {
+    use crate::clock::{SystemClock, TestClock};
+
+    let setts = "esdb://localhost:1?tls=false&maxDiscoverAttempts=100&discoveryInterval=60000&connectTimeout=2000"
+        .parse::<ClientSettings>()
+        .unwrap();
+    let clock = TestClock::new();
+    // The reconnection delays stay on the system clock, so only the wait of
+    // the operation moves with the test clock.
+    let client = GrpcClient::create(setts, None, Arc::new(SystemClock))
+        .await
+        .unwrap()
+        .with_clock(Arc::new(clock.clone()));
+    let handle = tokio::spawn(async move { client.warm_up().await });
+
+    while clock.pending_sleeps() == 0 {
+        tokio::task::yield_now().await;
+    }
+
+    clock.advance(Duration::from_millis(2000));
+
+    assert!(matches!(
+        handle.await.unwrap(),
+        Err(crate::Error::GrpcConnectionError(
+            GrpcConnectionError::ConnectionUnavailable(timeout)
+        )) if timeout == Duration::from_millis(2000)
+    ));
+}
+
+#[tokio::test]
+async fn test_reconnection_waits_on_the_clock() {
+    use crate::clock::TestClock;
+
+    let setts = "esdb://localhost:1?tls=false&maxDiscoverAttempts=1&discoveryInterval=60000"
+        .parse::<ClientSettings>()
+        .unwrap();
+    let clock = TestClock::new();
+    let client = GrpcClient::create(setts, None, Arc::new(clock.clone()))
+        .await
+        .unwrap();
+    let handle = tokio::spawn(async move { client.get_handle().await.map(|_| ()) });
+
+    // The first connection attempt fails right away.
+    while clock.pending_sleeps() == 0 {
+        tokio::task::yield_now().await;
+    }
+
+    clock.advance(Duration::from_secs(120));
+
+    assert!(handle.await.unwrap().is_err());
+}
+
+#[test]
+fn test_leader_flapping_within_window() {
+    let window = Duration::from_secs(10);
+    let mut flapping = LeaderFlapping::new(3, window);
+    let start = Instant::now();
+
+    assert!(!flapping.redirected(start));
+    assert!(!flapping.redirected(start + Duration::from_secs(1)));
+    assert!(flapping.redirected(start + Duration::from_secs(2)));
+
+    // The count starts over once the threshold is reached or the window elapsed.
+    assert!(!flapping.redirected(start + Duration::from_secs(3)));
+    assert!(!flapping.redirected(start + Duration::from_secs(4)));
+    assert!(!flapping.redirected(start + Duration::from_secs(30)));
+
+    let mut disabled = LeaderFlapping::new(0, window);
+
+    for _ in 0..10 {
+        assert!(!disabled.redirected(start));
+    }
+}
+
+#[test]
+fn test_node_role_from_gossip() {
+    let member = |port, state| MemberInfo {
+        instance_id: Uuid::nil(),
+        time_stamp: 0,
+        state,
+        is_alive: true,
+        http_end_point: Endpoint {
+            host: "localhost".to_string(),
+            port,
+        },
+    };
+
+    let members = vec![
+        member(1, VNodeState::Leader),
+        member(2, VNodeState::Follower),
+        member(3, VNodeState::CatchingUp),
+    ];
+
+    let role = |port| {
+        node_role(
+            &members,
+            &Endpoint {
+                host: "localhost".to_string(),
+                port,
+            },
+        )
+    };
+
+    assert_eq!(role(1), Some(NodeRole::Leader));
+    assert_eq!(role(2), Some(NodeRole::Follower));
+    assert_eq!(role(3), None);
+    assert_eq!(role(4), None);
+}
+
+#[test]
+fn test_best_node_avoids_the_failed_one() {
+    let member = |port, state| MemberInfo {
+        instance_id: Uuid::nil(),
+        time_stamp: 0,
+        state,
+        is_alive: true,
+        http_end_point: Endpoint {
+            host: "localhost".to_string(),
+            port,
+        },
+    };
+    let endpoint = |port| Endpoint {
+        host: "localhost".to_string(),
+        port,
+    };
+    let mut rng = SmallRng::seed_from_u64(0);
+    let members = vec![
+        member(1, VNodeState::Leader),
+        member(2, VNodeState::Follower),
+    ];
+
+    assert_eq!(
+        determine_best_node_excluding(&mut rng, NodePreference::Leader, &members, None),
+        Some(endpoint(1))
+    );
+    assert_eq!(
+        determine_best_node_excluding(
+            &mut rng,
+            NodePreference::Leader,
+            &members,
+            Some(&endpoint(1))
+        ),
+        Some(endpoint(2))
+    );
+    assert_eq!(
+        determine_best_node_excluding(
+            &mut rng,
+            NodePreference::Leader,
+            &members[..1],
+            Some(&endpoint(1))
+        ),
+        Some(endpoint(1))
+    );
+}
+
+#[test]
+fn test_settings_user_agent() {
+    let setts = ClientSettings::builder()
+        .host(Endpoint {
+            host: "localhost".to_string(),
+            port: 2113,
+        })
+        .connection_name("billing-worker")
+        .connection_property("version", "1.2.0")
+        .connection_property("env", "prod")
+        .build()
+        .unwrap();
+
+    assert_eq!(
+        setts.user_agent(),
+        format!(
+            "eventstore-rust/{} (billing-worker; env=prod; version=1.2.0)",
+            env!("CARGO_PKG_VERSION")
+        )
+    );
+
+    let result = ClientSettings::builder()
+        .host(Endpoint {
+            host: "localhost".to_string(),
+            port: 2113,
+        })
+        .connection_name("bad\nname")
+        .build();
+
+    assert!(matches!(
+        result,
+        Err(SettingsError::InvalidConnectionIdentification(_))
+    ));
+}
+
+#[test]
+fn test_jittered_delay_is_bounded() {
+    let base = Duration::from_millis(100);
+    let setts = ClientSettings::default();
+
+    assert_eq!(setts.jittered(base), base);
+
+    let setts = ClientSettings {
+        retry_jitter: Duration::from_millis(50),
+        ..setts
+    };
+
+    for _ in 0..100 {
+        let delay = setts.jittered(base);
+
+        assert!(delay >= base && delay <= base + setts.retry_jitter);
+    }
+}
+
+#[test]
+fn test_not_leader_backoff_grows_up_to_the_max() {
+    let setts = ClientSettings::default();
+
+    assert_eq!(setts.not_leader_backoff(1), setts.not_leader_retry_delay);
+    assert_eq!(setts.not_leader_backoff(5), setts.not_leader_retry_delay);
+
+    let setts = ClientSettings {
+        not_leader_retry_delay: Duration::from_millis(100),
+        retry_backoff_multiplier: 2,
+        retry_backoff_max: Duration::from_millis(500),
+        ..setts
+    };
+
+    let delays: Vec<_> = (1..=5)
+        .map(|retry| setts.not_leader_backoff(retry).as_millis())
+        .collect();
+
+    assert_eq!(delays, vec![100, 200, 400, 500, 500]);
+    assert_eq!(
+        setts.not_leader_backoff(usize::MAX),
+        Duration::from_millis(500)
+    );
+}
+
 struct DurationVisitor;
 
 impl<'de> Visitor<'de> for DurationVisitor {
@@ -121,6 +780,16 @@ where
     deserializer.deserialize_any(DurationVisitor)
 }
 
+/// Endpoint standing for the Unix domain socket. It's only used to fill the
+/// HTTP/2 authority of requests, the socket being reached through its path.
+#[cfg(unix)]
+fn unix_socket_endpoint() -> Endpoint {
+    Endpoint {
+        host: "localhost".to_string(),
+        port: 2113,
+    }
+}
+
 fn default_max_discover_attempts() -> usize {
     ClientSettings::default().max_discover_attempts
 }
@@ -157,6 +826,102 @@ fn default_keep_alive_timeout() -> Duration {
     ClientSettings::default().keep_alive_timeout
 }
 
+fn default_not_leader_behavior() -> NotLeaderBehavior {
+    ClientSettings::default().not_leader_behavior
+}
+
+fn default_not_leader_retry_limit() -> usize {
+    ClientSettings::default().not_leader_retry_limit
+}
+
+fn default_not_leader_retry_delay() -> Duration {
+    ClientSettings::default().not_leader_retry_delay
+}
+
+fn default_stale_leader_threshold() -> usize {
+    ClientSettings::default().stale_leader_threshold
+}
+
+fn default_stale_leader_window() -> Duration {
+    ClientSettings::default().stale_leader_window
+}
+
+fn default_retry_jitter() -> Duration {
+    ClientSettings::default().retry_jitter
+}
+
+fn default_read_cache_size() -> usize {
+    ClientSettings::default().read_cache_size
+}
+
+fn default_read_cache_ttl() -> Duration {
+    ClientSettings::default().read_cache_ttl
+}
+
+fn default_allow_system_stream_writes() -> bool {
+    ClientSettings::default().allow_system_stream_writes
+}
+
+fn default_auto_enable_system_projections() -> bool {
+    ClientSettings::default().auto_enable_system_projections
+}
+
+fn default_serialize_stream_writes() -> bool {
+    ClientSettings::default().serialize_stream_writes
+}
+
+fn default_decode_failure_policy() -> DecodeFailurePolicy {
+    ClientSettings::default().decode_failure_policy
+}
+
+fn default_empty_append() -> EmptyAppendBehavior {
+    ClientSettings::default().empty_append
+}
+
+fn default_max_queue_wait() -> Duration {
+    ClientSettings::default().max_queue_wait
+}
+
+fn default_connect_timeout() -> Duration {
+    ClientSettings::default().connect_timeout
+}
+
+fn default_server_time_skew_threshold() -> Duration {
+    ClientSettings::default().server_time_skew_threshold
+}
+
+fn default_write_timeout_retry_limit() -> usize {
+    ClientSettings::default().write_timeout_retry_limit
+}
+
+fn default_slow_consumer_threshold() -> Duration {
+    ClientSettings::default().slow_consumer_threshold
+}
+
+fn default_slow_consumer_drop_after() -> Duration {
+    ClientSettings::default().slow_consumer_drop_after
+}
+
+fn default_retry_backoff_multiplier() -> u32 {
+    ClientSettings::default().retry_backoff_multiplier
+}
+
+fn default_retry_backoff_max() -> Duration {
+    ClientSettings::default().retry_backoff_max
+}
+
+fn default_deadline() -> Duration {
+    ClientSettings::default().default_deadline
+}
+
+fn default_reconnect_retry_limit() -> usize {
+    ClientSettings::default().reconnect_retry_limit
+}
+
+fn default_max_in_flight() -> usize {
+    ClientSettings::default().max_in_flight
+}
+
 /// Gathers all the settings related to a gRPC client with an EventStoreDB database.
 /// `ClientSettings` can only be created when parsing a connection string.
 ///
@@ -189,57 +954,205 @@ fn default_keep_alive_timeout() -> Duration {
 /// # }
 /// ```
 ///
+/// On Unix platforms, the client can also connect to a node through a Unix domain socket. Such
+/// connections don't use TLS by default:
+/// ```
+/// # use eventstore::ClientSettings;
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// # #[cfg(unix)]
+/// let setts = "unix:///var/run/eventstore/esdb.sock".parse::<ClientSettings>()?;
+/// # Ok(())
+/// # }
+/// ```
+///
 /// `ClientSettings` supports a wide range of settings. If a setting is not mentioned in the
 /// connection string, that setting default value is used.
 ///
 /// * `maxDiscoverAttempts`: default `3`. Maximum number of DNS discovery attempts before the
-///    connection gives up.
+///   connection gives up.
 ///
 /// * `discoveryInterval`: default `500ms`. Waiting period between discovery attempts.
 ///
 /// * `gossipTimeout`: default `3s`: Waiting period before a gossip request timeout.
-///    __*TODO - Current behavior doesn't timeout at all.*__
+///   __*TODO - Current behavior doesn't timeout at all.*__
 ///
 /// * `tls`: default `true`. Use a secure connection.
 ///
 /// * `tlsVerifyCert`: default `true`. When using a secure connection, perform a certification
-///    verification.
+///   verification.
+///
+/// * `tlsPinnedFingerprints`: default empty. Comma-separated SHA-256 fingerprints of the server
+///   certificates, as hexadecimal digits with or without colons. When set, a server certificate is
+///   only trusted if its fingerprint is pinned, instead of being validated against the certificate
+///   authorities. A connection to a node presenting another certificate fails with
+///   `GrpcConnectionError::CertificatePinMismatch`. Requires `tls` and `tlsVerifyCert`, and can't be
+///   combined with `tlsCaFile`.
+///
+/// * `tlsCaFile`: default none. Path of a PEM file holding certificate authorities trusted on top of
+///   the system ones, for nodes whose certificates are signed by a private authority. Parsing fails
+///   if the file can't be read, and the file is read again each time the client connects to a node.
 ///
 /// * `nodePreference`: default `random`. When in a cluster connection, indicates what type of node
-///    a connection should pick. Keep in mind that's best effort. Supported values are:
+///   a connection should pick. Keep in mind that's best effort. Supported values are:
 ///    * `leader`
 ///    * `random`
 ///    * `follower`
 ///    * `readOnlyReplica`
 ///
-/// * `keepAliveInterval`: default `10s`
-/// * `keepAliveTimeout`: default `10s`
-#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
-pub struct ClientSettings {
-    #[serde(default)]
-    pub(crate) dns_discover: bool,
-    #[serde(default)]
-    pub(crate) hosts: Vec<Endpoint>,
-    #[serde(default = "default_max_discover_attempts")]
-    pub(crate) max_discover_attempts: usize,
-    #[serde(
-        default = "default_discovery_interval",
-        serialize_with = "serialize_duration",
-        deserialize_with = "deserialize_duration"
-    )]
-    pub(crate) discovery_interval: Duration,
-    #[serde(
-        default = "default_gossip_timeout",
-        serialize_with = "serialize_duration",
-        deserialize_with = "deserialize_duration"
-    )]
-    pub(crate) gossip_timeout: Duration,
-    #[serde(default = "default_preference")]
-    pub(crate) preference: NodePreference,
+/// * `keepAliveInterval`: default `10s`. Period after which the client pings the node over its
+///   HTTP/2 connection, so a dead link is noticed even without traffic. Values below the default
+///   are ignored with a warning, `-1` disables the pings.
+/// * `keepAliveTimeout`: default `10s`. Longest period the client waits for the node to answer a
+///   ping. Past it, the connection is closed, the operations in flight fail and the next one
+///   reconnects. Relax it on slow links to avoid spurious disconnections. Values below the
+///   default are ignored with a warning, `-1` waits forever.
+///
+/// * `notLeaderBehavior`: default `reconnect`. Indicates what to do when an operation that
+///   requires a leader node is sent to a node that is not the leader. Supported values are:
+///   * `reconnect`: fails the operation and reconnects to the advertised leader.
+///   * `retry`: reconnects to the advertised leader and retries the operation.
+///   * `fail`: fails the operation without reconnecting, exposing the advertised leader.
+///
+/// * `notLeaderRetryLimit`: default `3`. When `notLeaderBehavior` is `retry`, maximum number of
+///   times an operation is retried.
+///
+/// * `notLeaderRetryDelay`: default `200ms`. When `notLeaderBehavior` is `retry`, waiting period
+///   before the first retry, see `retryBackoffMultiplier`.
+///
+/// * `staleLeaderThreshold`: default `3`. In cluster mode, number of `NotLeader` responses received
+///   within `staleLeaderWindow` after which the advertised leader is no longer trusted and a fresh
+///   gossip query is forced. `0` disables the detection.
+///
+/// * `staleLeaderWindow`: default `10s`. See `staleLeaderThreshold`.
+///
+/// * `connectionName`: default none. Name identifying the client in the `user-agent` header sent
+///   to the server, so the server's connection list and logs are self-describing.
+///
+/// * `connectionProperty`: `key:value` pair added to the `user-agent` header, for instance a
+///   service name, version, instance id or environment. Can be repeated.
+///
+/// * `retryJitter`: default `0ms`. Upper bound of a random delay added to every reconnection,
+///   discovery and retry waiting period, so a fleet of clients doesn't hit a recovering node all
+///   at once.
+///
+/// * `readCacheSize`: default `0`. Number of `Client::read_stream` results with a finite count
+///   the client keeps in memory, evicting the least recently used ones. `0` disables the cache.
+///   Cached results are dropped when the client writes to, deletes or sees a new event of their
+///   stream through a subscription.
+///
+/// * `readCacheTtl`: default `1000ms`. How long a cached read result is served. Writes issued by
+///   other clients go unnoticed during that period.
+///
+/// * `allowSystemStreamWrites`: default `false`. Allows appending to `$`-prefixed system streams.
+///   Stream metadata can still be written through `Client::set_stream_metadata` when disabled.
+///
+/// * `autoEnableSystemProjections`: default `false`. When `Client::read_category`,
+///   `Client::read_event_type` or `Client::list_streams` finds no stream, enables the
+///   `$by_category`, `$by_event_type` or `$streams` system projection if it isn't running, then
+///   reads again. It requires operation or default
+///   credentials allowed to manage projections.
+///
+/// * `serializeStreamWrites`: default `false`. Makes the appends a client, and its clones, issues
+///   to the same stream wait for each other, which avoids the tasks of a process conflicting on
+///   the expected revision of a stream. Appends to different streams still run concurrently. It
+///   doesn't coordinate with other processes writing to the same streams.
+///
+/// * `decodeFailurePolicy`: default `failOperation`. Indicates what a subscription does when it
+///   receives an event it can't decode. Supported values are:
+///   * `failOperation`: ends the subscription with an error.
+///   * `skipEvent`: logs the failure and skips the event.
+///
+/// * `emptyAppend`: default `assertRevision`. Indicates what an append without any event does.
+///   Supported values are:
+///   * `assertRevision`: sends the append, the server writing nothing but checking the expected
+///     revision. The result holds the current revision of the stream.
+///   * `reject`: fails the append without contacting the server.
+///
+/// * `maxQueueWait`: default `0ms`. Longest period an operation waits for a connection to a node
+///   before failing with `GrpcConnectionError::ConnectionUnavailable`, giving callers an upper
+///   bound during outages. `0` waits until the discovery attempts are exhausted.
+///
+/// * `connectTimeout`: default `10000ms`. Longest period `Client::connect` waits for the node
+///   discovery and a first round-trip to the selected node. `0` waits until the discovery
+///   attempts are exhausted.
+///
+/// * `serverTimeSkewThreshold`: default `0ms`. On every connection to a node, the client compares
+///   the node clock, as reported by its gossip, to the local one and logs a warning when they
+///   differ by more than this threshold. The node refreshes its gossip timestamp every gossip
+///   interval, so the threshold should be set well above that interval. `0` disables the check.
+///
+/// * `writeTimeoutRetryLimit`: default `3`. Times an idempotent append is sent again after the
+///   server timed out writing it, whatever the phase it timed out in. Appends expecting
+///   `ExpectedRevision::Any` and streamed appends fail with `Error::WriteTimeout` right away.
+///   `0` disables the retries.
+///
+/// * `slowConsumerThreshold`: default `0ms`. Once a subscription is live, flags it as a slow
+///   consumer when the events it delivers reach the consumer later than this. An event is late
+///   by the time it took to be delivered after it was written, beyond the time the least delayed
+///   event of the subscription took. The flag shows in `Client::subscriptions`, the
+///   `slow_consumers` operation counter and a warning logged with the `eventstore::slow_consumer`
+///   target. `0` disables the detection. While it's enabled, catch-up subscriptions read the live
+///   edge when subscribing, whether or not they asked for `SubEvent::CaughtUp`.
+///
+/// * `slowConsumerDropAfter`: default `0ms`. Drops a live subscription with
+///   `SubscriptionDropReason::SlowConsumer` when an event would reach the consumer later than
+///   this. `0` never drops a subscription.
+///
+/// * `retryBackoffMultiplier`: default `1`. When `notLeaderBehavior` is `retry`, factor applied to
+///   the waiting period after each retry on a new leader, starting from `notLeaderRetryDelay`.
+///   `2` doubles the waiting period every retry, `1` keeps it constant.
+///
+/// * `retryBackoffMax`: default `0ms`. Upper bound of the waiting period between retries on a new
+///   leader, before `retryJitter` is added. `0` doesn't bound it.
+///
+/// * `defaultDeadline`: default `0ms`. Longest period an operation waits for the server to
+///   respond, retries and connection waits included, before failing with
+///   `Error::DeadlineExceeded`. The request is cancelled. Reads and subscriptions only wait for
+///   their first response, not for the stream to end. `0` waits forever.
+///
+/// * `reconnectRetryLimit`: default `0`. Times an operation is sent again after the node it was
+///   sent to went unavailable, once the client reconnected to a node. Only reads, subscriptions
+///   and idempotent appends are sent again, as the server might have handled the other operations
+///   before going away. Reconnecting follows `maxDiscoverAttempts` and `discoveryInterval`. `0`
+///   fails the operations right away.
+///
+/// * `maxInFlight`: default `1024`. Maximum number of operations a client, and its clones, has
+///   in flight at once. Further operations wait for one of them to complete, which keeps a
+///   runaway producer from piling up requests. The adaptive throttling lowers that limit while the
+///   server is too busy, see `Client::throttle_state`, which also reports the operations in
+///   flight.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ClientSettings {
+    #[serde(default)]
+    pub(crate) dns_discover: bool,
+    #[serde(default)]
+    pub(crate) hosts: Vec<Endpoint>,
+    #[serde(default)]
+    pub(crate) unix_socket: Option<PathBuf>,
+    #[serde(default = "default_max_discover_attempts")]
+    pub(crate) max_discover_attempts: usize,
+    #[serde(
+        default = "default_discovery_interval",
+        serialize_with = "serialize_duration",
+        deserialize_with = "deserialize_duration"
+    )]
+    pub(crate) discovery_interval: Duration,
+    #[serde(
+        default = "default_gossip_timeout",
+        serialize_with = "serialize_duration",
+        deserialize_with = "deserialize_duration"
+    )]
+    pub(crate) gossip_timeout: Duration,
+    #[serde(default = "default_preference")]
+    pub(crate) preference: NodePreference,
     #[serde(default = "default_secure")]
     pub(crate) secure: bool,
     #[serde(default = "default_tls_verify_cert")]
     pub(crate) tls_verify_cert: bool,
+    #[serde(default)]
+    pub(crate) tls_pinned_fingerprints: Vec<String>,
+    #[serde(default)]
+    pub(crate) tls_ca_file: Option<PathBuf>,
     #[serde(default = "default_throw_on_append_failure")]
     pub(crate) throw_on_append_failure: bool,
     #[serde(default)]
@@ -256,6 +1169,102 @@ pub struct ClientSettings {
         deserialize_with = "deserialize_duration"
     )]
     pub(crate) keep_alive_timeout: Duration,
+    #[serde(default = "default_not_leader_behavior")]
+    pub(crate) not_leader_behavior: NotLeaderBehavior,
+    #[serde(default = "default_not_leader_retry_limit")]
+    pub(crate) not_leader_retry_limit: usize,
+    #[serde(
+        default = "default_not_leader_retry_delay",
+        serialize_with = "serialize_duration",
+        deserialize_with = "deserialize_duration"
+    )]
+    pub(crate) not_leader_retry_delay: Duration,
+    #[serde(default = "default_stale_leader_threshold")]
+    pub(crate) stale_leader_threshold: usize,
+    #[serde(
+        default = "default_stale_leader_window",
+        serialize_with = "serialize_duration",
+        deserialize_with = "deserialize_duration"
+    )]
+    pub(crate) stale_leader_window: Duration,
+    #[serde(
+        default = "default_retry_jitter",
+        serialize_with = "serialize_duration",
+        deserialize_with = "deserialize_duration"
+    )]
+    pub(crate) retry_jitter: Duration,
+    #[serde(default)]
+    pub(crate) connection_name: Option<String>,
+    #[serde(default)]
+    pub(crate) connection_properties: BTreeMap<String, String>,
+    #[serde(default = "default_read_cache_size")]
+    pub(crate) read_cache_size: usize,
+    #[serde(
+        default = "default_read_cache_ttl",
+        serialize_with = "serialize_duration",
+        deserialize_with = "deserialize_duration"
+    )]
+    pub(crate) read_cache_ttl: Duration,
+    #[serde(default = "default_allow_system_stream_writes")]
+    pub(crate) allow_system_stream_writes: bool,
+    #[serde(default = "default_auto_enable_system_projections")]
+    pub(crate) auto_enable_system_projections: bool,
+    #[serde(default = "default_serialize_stream_writes")]
+    pub(crate) serialize_stream_writes: bool,
+    #[serde(default = "default_decode_failure_policy")]
+    pub(crate) decode_failure_policy: DecodeFailurePolicy,
+    #[serde(default = "default_empty_append")]
+    pub(crate) empty_append: EmptyAppendBehavior,
+    #[serde(
+        default = "default_max_queue_wait",
+        serialize_with = "serialize_duration",
+        deserialize_with = "deserialize_duration"
+    )]
+    pub(crate) max_queue_wait: Duration,
+    #[serde(
+        default = "default_connect_timeout",
+        serialize_with = "serialize_duration",
+        deserialize_with = "deserialize_duration"
+    )]
+    pub(crate) connect_timeout: Duration,
+    #[serde(
+        default = "default_server_time_skew_threshold",
+        serialize_with = "serialize_duration",
+        deserialize_with = "deserialize_duration"
+    )]
+    pub(crate) server_time_skew_threshold: Duration,
+    #[serde(default = "default_write_timeout_retry_limit")]
+    pub(crate) write_timeout_retry_limit: usize,
+    #[serde(
+        default = "default_slow_consumer_threshold",
+        serialize_with = "serialize_duration",
+        deserialize_with = "deserialize_duration"
+    )]
+    pub(crate) slow_consumer_threshold: Duration,
+    #[serde(
+        default = "default_slow_consumer_drop_after",
+        serialize_with = "serialize_duration",
+        deserialize_with = "deserialize_duration"
+    )]
+    pub(crate) slow_consumer_drop_after: Duration,
+    #[serde(default = "default_retry_backoff_multiplier")]
+    pub(crate) retry_backoff_multiplier: u32,
+    #[serde(
+        default = "default_retry_backoff_max",
+        serialize_with = "serialize_duration",
+        deserialize_with = "deserialize_duration"
+    )]
+    pub(crate) retry_backoff_max: Duration,
+    #[serde(
+        default = "default_deadline",
+        serialize_with = "serialize_duration",
+        deserialize_with = "deserialize_duration"
+    )]
+    pub(crate) default_deadline: Duration,
+    #[serde(default = "default_reconnect_retry_limit")]
+    pub(crate) reconnect_retry_limit: usize,
+    #[serde(default = "default_max_in_flight")]
+    pub(crate) max_in_flight: usize,
 }
 
 impl ClientSettings {
@@ -267,6 +1276,11 @@ impl ClientSettings {
         &self.hosts
     }
 
+    /// Path of the Unix domain socket the client connects through, if any.
+    pub fn unix_socket(&self) -> Option<&Path> {
+        self.unix_socket.as_deref()
+    }
+
     pub fn max_discover_attempts(&self) -> usize {
         self.max_discover_attempts
     }
@@ -291,15 +1305,272 @@ impl ClientSettings {
         self.tls_verify_cert
     }
 
+    /// SHA-256 fingerprints of the pinned server certificates, as lowercase
+    /// hexadecimal digits.
+    pub fn tls_pinned_fingerprints(&self) -> &[String] {
+        &self.tls_pinned_fingerprints
+    }
+
+    pub fn tls_ca_file(&self) -> Option<&Path> {
+        self.tls_ca_file.as_deref()
+    }
+
     pub fn default_authenticated_user(&self) -> &Option<Credentials> {
         &self.default_user_name
     }
 
+    pub fn not_leader_behavior(&self) -> NotLeaderBehavior {
+        self.not_leader_behavior
+    }
+
+    pub fn not_leader_retry_limit(&self) -> usize {
+        self.not_leader_retry_limit
+    }
+
+    pub fn not_leader_retry_delay(&self) -> Duration {
+        self.not_leader_retry_delay
+    }
+
+    pub fn stale_leader_threshold(&self) -> usize {
+        self.stale_leader_threshold
+    }
+
+    pub fn stale_leader_window(&self) -> Duration {
+        self.stale_leader_window
+    }
+
+    pub fn connection_name(&self) -> Option<&str> {
+        self.connection_name.as_deref()
+    }
+
+    pub fn connection_properties(&self) -> &BTreeMap<String, String> {
+        &self.connection_properties
+    }
+
+    /// `user-agent` header sent to the server, identifying the client library
+    /// along with the connection name and properties.
+    pub fn user_agent(&self) -> String {
+        let mut details: Vec<String> = self.connection_name.iter().cloned().collect();
+
+        details.extend(
+            self.connection_properties
+                .iter()
+                .map(|(key, value)| format!("{}={}", key, value)),
+        );
+
+        let library = concat!("eventstore-rust/", env!("CARGO_PKG_VERSION"));
+
+        if details.is_empty() {
+            library.to_string()
+        } else {
+            format!("{} ({})", library, details.join("; "))
+        }
+    }
+
+    pub fn retry_jitter(&self) -> Duration {
+        self.retry_jitter
+    }
+
+    /// Adds a random delay, bounded by the `retryJitter` setting, to a waiting
+    /// period.
+    pub(crate) fn jittered(&self, delay: Duration) -> Duration {
+        let bound = self.retry_jitter.as_millis() as u64;
+
+        if bound == 0 {
+            return delay;
+        }
+
+        delay + Duration::from_millis(rand::thread_rng().gen_range(0..=bound))
+    }
+
+    pub fn read_cache_size(&self) -> usize {
+        self.read_cache_size
+    }
+
+    pub fn read_cache_ttl(&self) -> Duration {
+        self.read_cache_ttl
+    }
+
+    pub fn are_system_stream_writes_allowed(&self) -> bool {
+        self.allow_system_stream_writes
+    }
+
+    pub fn auto_enable_system_projections(&self) -> bool {
+        self.auto_enable_system_projections
+    }
+
+    pub fn are_stream_writes_serialized(&self) -> bool {
+        self.serialize_stream_writes
+    }
+
+    pub fn decode_failure_policy(&self) -> DecodeFailurePolicy {
+        self.decode_failure_policy
+    }
+
+    pub fn empty_append(&self) -> EmptyAppendBehavior {
+        self.empty_append
+    }
+
+    pub fn max_queue_wait(&self) -> Duration {
+        self.max_queue_wait
+    }
+
+    pub fn connect_timeout(&self) -> Duration {
+        self.connect_timeout
+    }
+
+    pub fn server_time_skew_threshold(&self) -> Duration {
+        self.server_time_skew_threshold
+    }
+
+    pub fn write_timeout_retry_limit(&self) -> usize {
+        self.write_timeout_retry_limit
+    }
+
+    pub fn slow_consumer_threshold(&self) -> Duration {
+        self.slow_consumer_threshold
+    }
+
+    pub fn slow_consumer_drop_after(&self) -> Duration {
+        self.slow_consumer_drop_after
+    }
+
+    pub fn retry_backoff_multiplier(&self) -> u32 {
+        self.retry_backoff_multiplier
+    }
+
+    pub fn retry_backoff_max(&self) -> Duration {
+        self.retry_backoff_max
+    }
+
+    pub fn default_deadline(&self) -> Duration {
+        self.default_deadline
+    }
+
+    pub fn reconnect_retry_limit(&self) -> usize {
+        self.reconnect_retry_limit
+    }
+
+    pub fn max_in_flight(&self) -> usize {
+        self.max_in_flight
+    }
+
+    /// Waiting period before the given retry on a new leader, `1` being the
+    /// first one, without the jitter.
+    pub(crate) fn not_leader_backoff(&self, retry: usize) -> Duration {
+        let exponent = retry.saturating_sub(1).min(u32::MAX as usize) as u32;
+        let delay = self
+            .not_leader_retry_delay
+            .saturating_mul(self.retry_backoff_multiplier.saturating_pow(exponent));
+
+        if self.retry_backoff_max == Duration::default() {
+            delay
+        } else {
+            delay.min(self.retry_backoff_max)
+        }
+    }
+
+    /// Returns a builder to create validated settings programmatically.
+    pub fn builder() -> ClientSettingsBuilder {
+        ClientSettingsBuilder::default()
+    }
+
+    /// Checks the settings don't hold a combination that would only fail
+    /// once the client is running.
+    pub fn validate(&self) -> Result<(), SettingsError> {
+        if self.hosts.is_empty() {
+            return Err(SettingsError::NoHost);
+        }
+
+        if self.dns_discover && self.hosts.len() > 1 {
+            return Err(SettingsError::DiscoveryRequiresSingleHost(self.hosts.len()));
+        }
+
+        if !self.tls_pinned_fingerprints.is_empty() {
+            if !self.secure || !self.tls_verify_cert {
+                return Err(SettingsError::PinningRequiresCertificateVerification);
+            }
+
+            if self.tls_ca_file.is_some() {
+                return Err(SettingsError::PinningExcludesCaFile);
+            }
+
+            if let Some(invalid) = self
+                .tls_pinned_fingerprints
+                .iter()
+                .find(|f| parse_fingerprint(f).as_deref() != Some(f.as_str()))
+            {
+                return Err(SettingsError::InvalidCertificateFingerprint(
+                    invalid.clone(),
+                ));
+            }
+        }
+
+        // Pinned certificates are trusted regardless of the host name.
+        if self.secure && self.tls_verify_cert && self.tls_pinned_fingerprints.is_empty() {
+            if let Some(endpoint) = self
+                .hosts
+                .iter()
+                .find(|e| e.host.parse::<std::net::IpAddr>().is_ok())
+            {
+                return Err(SettingsError::TlsRequiresHostName(endpoint.clone()));
+            }
+        }
+
+        if let Some(path) = self.tls_ca_file.as_ref() {
+            if let Err(e) = read_ca_file(path) {
+                return Err(SettingsError::UnreadableCaFile {
+                    path: path.clone(),
+                    reason: e.to_string(),
+                });
+            }
+        }
+
+        // Keep-alive settings set to -1 are disabled, there is nothing to
+        // compare.
+        let disabled = Duration::from_millis(u64::MAX);
+
+        if self.keep_alive_interval != disabled
+            && self.keep_alive_timeout != disabled
+            && self.keep_alive_interval > self.keep_alive_timeout
+        {
+            return Err(SettingsError::KeepAliveIntervalExceedsTimeout {
+                interval: self.keep_alive_interval,
+                timeout: self.keep_alive_timeout,
+            });
+        }
+
+        if self.max_discover_attempts == 0 {
+            return Err(SettingsError::ZeroMaxDiscoverAttempts);
+        }
+
+        if self.retry_backoff_multiplier == 0 {
+            return Err(SettingsError::ZeroRetryBackoffMultiplier);
+        }
+
+        if self.max_in_flight == 0 {
+            return Err(SettingsError::ZeroMaxInFlight);
+        }
+
+        let user_agent = self.user_agent();
+
+        if http::HeaderValue::from_str(user_agent.as_str()).is_err() {
+            return Err(SettingsError::InvalidConnectionIdentification(user_agent));
+        }
+
+        Ok(())
+    }
+
     pub fn parse(input: &str) -> IResult<&str, Self> {
         let mut result: ClientSettings = Default::default();
         let mut parsed_authority = false;
 
-        let (initial_input, scheme) = alt((tag("esdb://"), tag("esdb+discover://")))(input)?;
+        let (initial_input, scheme) =
+            alt((tag("esdb://"), tag("esdb+discover://"), tag("unix://")))(input)?;
+
+        if scheme == "unix://" {
+            return ClientSettings::parse_unix_socket(result, initial_input);
+        }
 
         result.dns_discover = scheme == "esdb+discover://";
         let authority_valid_char = |c: char| c.is_ascii() && c != '@';
@@ -384,6 +1655,32 @@ impl ClientSettings {
         }
 
         let (input, _) = opt(tag("/"))(input)?;
+
+        ClientSettings::parse_params(result, input)
+    }
+
+    #[cfg(unix)]
+    fn parse_unix_socket(mut result: ClientSettings, input: &str) -> IResult<&str, Self> {
+        let (input, path) = take_while1(|c: char| c != '?')(input)?;
+
+        result.unix_socket = Some(path.into());
+        result.hosts.push(unix_socket_endpoint());
+        // A socket file is only reachable from the local host, the connection is
+        // plain text unless `tls=true` is set explicitly.
+        result.secure = false;
+
+        ClientSettings::parse_params(result, input)
+    }
+
+    #[cfg(not(unix))]
+    fn parse_unix_socket(_: ClientSettings, input: &str) -> IResult<&str, Self> {
+        Err(nom::Err::Failure(nom::error::Error::new(
+            input,
+            ErrorKind::ParseTo,
+        )))
+    }
+
+    fn parse_params(mut result: ClientSettings, input: &str) -> IResult<&str, Self> {
         let (mut input, has_params) = opt(tag("?"))(input)?;
 
         if has_params.is_some() {
@@ -454,6 +1751,50 @@ impl ClientSettings {
                             }
                         }
 
+                        "tlscafile" => {
+                            let value = values.as_slice()[1];
+                            if value.is_empty() {
+                                return Err(nom::Err::Failure(nom::error::Error::new(
+                                    value,
+                                    ErrorKind::ParseTo,
+                                )));
+                            }
+
+                            // A typo in the path would otherwise only show once
+                            // connecting, or not at all if the system authorities
+                            // happen to trust the node.
+                            if let Err(e) = read_ca_file(Path::new(value)) {
+                                error!(
+                                    "Unable to read certificate authority file {:?}: {}",
+                                    value, e
+                                );
+
+                                return Err(nom::Err::Failure(nom::error::Error::new(
+                                    value,
+                                    ErrorKind::ParseTo,
+                                )));
+                            }
+
+                            result.tls_ca_file = Some(PathBuf::from(value));
+                        }
+
+                        "tlspinnedfingerprints" => {
+                            for value in values.as_slice()[1].split(',') {
+                                match parse_fingerprint(value) {
+                                    Some(fingerprint) => {
+                                        result.tls_pinned_fingerprints.push(fingerprint)
+                                    }
+
+                                    None => {
+                                        return Err(nom::Err::Failure(nom::error::Error::new(
+                                            value,
+                                            ErrorKind::ParseTo,
+                                        )));
+                                    }
+                                }
+                            }
+                        }
+
                         "nodepreference" => {
                             let value = values.as_slice()[1].to_lowercase();
                             match value.as_str() {
@@ -482,32 +1823,373 @@ impl ClientSettings {
                             }
                         }
 
-                        "keepaliveinterval" => {
+                        "keepaliveinterval" => {
+                            let value = values.as_slice()[1];
+
+                            if let Ok(int) = value.parse::<i64>() {
+                                if int >= 0
+                                    && int < self::defaults::KEEP_ALIVE_INTERVAL_IN_MS as i64
+                                {
+                                    warn!("Specified keepAliveInterval of {} is less than recommended {}", int, self::defaults::KEEP_ALIVE_INTERVAL_IN_MS);
+                                    continue;
+                                }
+
+                                if int == -1 {
+                                    result.keep_alive_interval = Duration::from_millis(u64::MAX);
+                                    continue;
+                                }
+
+                                if int < -1 {
+                                    error!("Invalid keepAliveInterval of {}. Please provide a positive integer, or -1 to disable", int);
+
+                                    return Err(nom::Err::Failure(nom::error::Error::new(
+                                        value,
+                                        ErrorKind::ParseTo,
+                                    )));
+                                }
+
+                                result.keep_alive_interval = Duration::from_millis(int as u64);
+                            } else {
+                                return Err(nom::Err::Failure(nom::error::Error::new(
+                                    value,
+                                    ErrorKind::ParseTo,
+                                )));
+                            }
+                        }
+
+                        "keepalivetimeout" => {
+                            let value = values.as_slice()[1];
+
+                            if let Ok(int) = value.parse::<i64>() {
+                                if int >= 0 && int < self::defaults::KEEP_ALIVE_TIMEOUT_IN_MS as i64
+                                {
+                                    warn!("Specified keepAliveTimeout of {} is less than recommended {}", int, self::defaults::KEEP_ALIVE_TIMEOUT_IN_MS);
+                                    continue;
+                                }
+
+                                if int == -1 {
+                                    result.keep_alive_timeout = Duration::from_millis(u64::MAX);
+                                    continue;
+                                }
+
+                                if int < -1 {
+                                    error!("Invalid keepAliveTimeout of {}. Please provide a positive integer, or -1 to disable", int);
+
+                                    return Err(nom::Err::Failure(nom::error::Error::new(
+                                        value,
+                                        ErrorKind::ParseTo,
+                                    )));
+                                }
+
+                                result.keep_alive_timeout = Duration::from_millis(int as u64);
+                            } else {
+                                return Err(nom::Err::Failure(nom::error::Error::new(
+                                    value,
+                                    ErrorKind::ParseTo,
+                                )));
+                            }
+                        }
+
+                        "notleaderbehavior" => {
+                            let value = values.as_slice()[1].to_lowercase();
+                            match value.as_str() {
+                                "reconnect" => {
+                                    result.not_leader_behavior = NotLeaderBehavior::Reconnect;
+                                }
+
+                                "retry" => {
+                                    result.not_leader_behavior = NotLeaderBehavior::Retry;
+                                }
+
+                                "fail" => {
+                                    result.not_leader_behavior = NotLeaderBehavior::Fail;
+                                }
+
+                                _ => {
+                                    return Err(nom::Err::Failure(nom::error::Error::new(
+                                        values.as_slice()[1],
+                                        ErrorKind::ParseTo,
+                                    )));
+                                }
+                            }
+                        }
+
+                        "notleaderretrylimit" => {
+                            let value = values.as_slice()[1];
+                            if let Ok(limit) = value.parse() {
+                                result.not_leader_retry_limit = limit;
+                            } else {
+                                return Err(nom::Err::Failure(nom::error::Error::new(
+                                    value,
+                                    ErrorKind::ParseTo,
+                                )));
+                            }
+                        }
+
+                        "notleaderretrydelay" => {
+                            let value = values.as_slice()[1];
+                            if let Ok(millis) = value.parse() {
+                                result.not_leader_retry_delay = Duration::from_millis(millis);
+                            } else {
+                                return Err(nom::Err::Failure(nom::error::Error::new(
+                                    value,
+                                    ErrorKind::ParseTo,
+                                )));
+                            }
+                        }
+
+                        "staleleaderthreshold" => {
+                            let value = values.as_slice()[1];
+                            if let Ok(int) = value.parse() {
+                                result.stale_leader_threshold = int;
+                            } else {
+                                return Err(nom::Err::Failure(nom::error::Error::new(
+                                    value,
+                                    ErrorKind::ParseTo,
+                                )));
+                            }
+                        }
+
+                        "staleleaderwindow" => {
+                            let value = values.as_slice()[1];
+                            if let Ok(millis) = value.parse() {
+                                result.stale_leader_window = Duration::from_millis(millis);
+                            } else {
+                                return Err(nom::Err::Failure(nom::error::Error::new(
+                                    value,
+                                    ErrorKind::ParseTo,
+                                )));
+                            }
+                        }
+
+                        "retryjitter" => {
+                            let value = values.as_slice()[1];
+                            if let Ok(millis) = value.parse() {
+                                result.retry_jitter = Duration::from_millis(millis);
+                            } else {
+                                return Err(nom::Err::Failure(nom::error::Error::new(
+                                    value,
+                                    ErrorKind::ParseTo,
+                                )));
+                            }
+                        }
+
+                        "readcachesize" => {
+                            let value = values.as_slice()[1];
+                            if let Ok(size) = value.parse() {
+                                result.read_cache_size = size;
+                            } else {
+                                return Err(nom::Err::Failure(nom::error::Error::new(
+                                    value,
+                                    ErrorKind::ParseTo,
+                                )));
+                            }
+                        }
+
+                        "readcachettl" => {
+                            let value = values.as_slice()[1];
+                            if let Ok(millis) = value.parse() {
+                                result.read_cache_ttl = Duration::from_millis(millis);
+                            } else {
+                                return Err(nom::Err::Failure(nom::error::Error::new(
+                                    value,
+                                    ErrorKind::ParseTo,
+                                )));
+                            }
+                        }
+
+                        "allowsystemstreamwrites" => {
+                            let value = values.as_slice()[1];
+                            if let Ok(bool) = value.parse() {
+                                result.allow_system_stream_writes = bool;
+                            } else {
+                                return Err(nom::Err::Failure(nom::error::Error::new(
+                                    value,
+                                    ErrorKind::ParseTo,
+                                )));
+                            }
+                        }
+
+                        "autoenablesystemprojections" => {
+                            let value = values.as_slice()[1];
+                            if let Ok(bool) = value.parse() {
+                                result.auto_enable_system_projections = bool;
+                            } else {
+                                return Err(nom::Err::Failure(nom::error::Error::new(
+                                    value,
+                                    ErrorKind::ParseTo,
+                                )));
+                            }
+                        }
+
+                        "serializestreamwrites" => {
+                            let value = values.as_slice()[1];
+                            if let Ok(bool) = value.parse() {
+                                result.serialize_stream_writes = bool;
+                            } else {
+                                return Err(nom::Err::Failure(nom::error::Error::new(
+                                    value,
+                                    ErrorKind::ParseTo,
+                                )));
+                            }
+                        }
+
+                        "connectionname" => {
+                            result.connection_name = Some(values.as_slice()[1].to_string());
+                        }
+
+                        "connectionproperty" => {
+                            let value = values.as_slice()[1];
+
+                            match value.split_once(':') {
+                                Some((key, prop)) if !key.is_empty() => {
+                                    result
+                                        .connection_properties
+                                        .insert(key.to_string(), prop.to_string());
+                                }
+
+                                _ => {
+                                    return Err(nom::Err::Failure(nom::error::Error::new(
+                                        value,
+                                        ErrorKind::ParseTo,
+                                    )));
+                                }
+                            }
+                        }
+
+                        "decodefailurepolicy" => {
+                            let value = values.as_slice()[1].to_lowercase();
+                            match value.as_str() {
+                                "failoperation" => {
+                                    result.decode_failure_policy =
+                                        DecodeFailurePolicy::FailOperation;
+                                }
+
+                                "skipevent" => {
+                                    result.decode_failure_policy = DecodeFailurePolicy::SkipEvent;
+                                }
+
+                                _ => {
+                                    return Err(nom::Err::Failure(nom::error::Error::new(
+                                        values.as_slice()[1],
+                                        ErrorKind::ParseTo,
+                                    )));
+                                }
+                            }
+                        }
+
+                        "emptyappend" => {
+                            let value = values.as_slice()[1].to_lowercase();
+                            match value.as_str() {
+                                "assertrevision" => {
+                                    result.empty_append = EmptyAppendBehavior::AssertRevision;
+                                }
+
+                                "reject" => {
+                                    result.empty_append = EmptyAppendBehavior::Reject;
+                                }
+
+                                _ => {
+                                    return Err(nom::Err::Failure(nom::error::Error::new(
+                                        values.as_slice()[1],
+                                        ErrorKind::ParseTo,
+                                    )));
+                                }
+                            }
+                        }
+
+                        "maxqueuewait" => {
+                            let value = values.as_slice()[1];
+                            if let Ok(millis) = value.parse() {
+                                result.max_queue_wait = Duration::from_millis(millis);
+                            } else {
+                                return Err(nom::Err::Failure(nom::error::Error::new(
+                                    value,
+                                    ErrorKind::ParseTo,
+                                )));
+                            }
+                        }
+
+                        "connecttimeout" => {
+                            let value = values.as_slice()[1];
+                            if let Ok(millis) = value.parse() {
+                                result.connect_timeout = Duration::from_millis(millis);
+                            } else {
+                                return Err(nom::Err::Failure(nom::error::Error::new(
+                                    value,
+                                    ErrorKind::ParseTo,
+                                )));
+                            }
+                        }
+
+                        "servertimeskewthreshold" => {
+                            let value = values.as_slice()[1];
+                            if let Ok(millis) = value.parse() {
+                                result.server_time_skew_threshold = Duration::from_millis(millis);
+                            } else {
+                                return Err(nom::Err::Failure(nom::error::Error::new(
+                                    value,
+                                    ErrorKind::ParseTo,
+                                )));
+                            }
+                        }
+
+                        "writetimeoutretrylimit" => {
+                            let value = values.as_slice()[1];
+                            if let Ok(limit) = value.parse() {
+                                result.write_timeout_retry_limit = limit;
+                            } else {
+                                return Err(nom::Err::Failure(nom::error::Error::new(
+                                    value,
+                                    ErrorKind::ParseTo,
+                                )));
+                            }
+                        }
+
+                        "slowconsumerthreshold" => {
                             let value = values.as_slice()[1];
+                            if let Ok(millis) = value.parse() {
+                                result.slow_consumer_threshold = Duration::from_millis(millis);
+                            } else {
+                                return Err(nom::Err::Failure(nom::error::Error::new(
+                                    value,
+                                    ErrorKind::ParseTo,
+                                )));
+                            }
+                        }
 
-                            if let Ok(int) = value.parse::<i64>() {
-                                if int >= 0
-                                    && int < self::defaults::KEEP_ALIVE_INTERVAL_IN_MS as i64
-                                {
-                                    warn!("Specified keepAliveInterval of {} is less than recommended {}", int, self::defaults::KEEP_ALIVE_INTERVAL_IN_MS);
-                                    continue;
-                                }
+                        "slowconsumerdropafter" => {
+                            let value = values.as_slice()[1];
+                            if let Ok(millis) = value.parse() {
+                                result.slow_consumer_drop_after = Duration::from_millis(millis);
+                            } else {
+                                return Err(nom::Err::Failure(nom::error::Error::new(
+                                    value,
+                                    ErrorKind::ParseTo,
+                                )));
+                            }
+                        }
 
-                                if int == -1 {
-                                    result.keep_alive_interval = Duration::from_millis(u64::MAX);
-                                    continue;
+                        "retrybackoffmultiplier" => {
+                            let value = values.as_slice()[1];
+                            match value.parse() {
+                                Ok(multiplier) if multiplier > 0 => {
+                                    result.retry_backoff_multiplier = multiplier;
                                 }
 
-                                if int < -1 {
-                                    error!("Invalid keepAliveInterval of {}. Please provide a positive integer, or -1 to disable", int);
-
+                                _ => {
                                     return Err(nom::Err::Failure(nom::error::Error::new(
                                         value,
                                         ErrorKind::ParseTo,
                                     )));
                                 }
+                            }
+                        }
 
-                                result.keep_alive_interval = Duration::from_millis(int as u64);
+                        "retrybackoffmax" => {
+                            let value = values.as_slice()[1];
+                            if let Ok(millis) = value.parse() {
+                                result.retry_backoff_max = Duration::from_millis(millis);
                             } else {
                                 return Err(nom::Err::Failure(nom::error::Error::new(
                                     value,
@@ -516,31 +2198,38 @@ impl ClientSettings {
                             }
                         }
 
-                        "keepalivetimeout" => {
+                        "defaultdeadline" => {
                             let value = values.as_slice()[1];
+                            if let Ok(millis) = value.parse() {
+                                result.default_deadline = Duration::from_millis(millis);
+                            } else {
+                                return Err(nom::Err::Failure(nom::error::Error::new(
+                                    value,
+                                    ErrorKind::ParseTo,
+                                )));
+                            }
+                        }
 
-                            if let Ok(int) = value.parse::<i64>() {
-                                if int >= 0 && int < self::defaults::KEEP_ALIVE_TIMEOUT_IN_MS as i64
-                                {
-                                    warn!("Specified keepAliveTimeout of {} is less than recommended {}", int, self::defaults::KEEP_ALIVE_TIMEOUT_IN_MS);
-                                    continue;
-                                }
-
-                                if int == -1 {
-                                    result.keep_alive_timeout = Duration::from_millis(u64::MAX);
-                                    continue;
+                        "maxinflight" => {
+                            let value = values.as_slice()[1];
+                            match value.parse() {
+                                Ok(max) if max > 0 => {
+                                    result.max_in_flight = max;
                                 }
 
-                                if int < -1 {
-                                    error!("Invalid keepAliveTimeout of {}. Please provide a positive integer, or -1 to disable", int);
-
+                                _ => {
                                     return Err(nom::Err::Failure(nom::error::Error::new(
                                         value,
                                         ErrorKind::ParseTo,
                                     )));
                                 }
+                            }
+                        }
 
-                                result.keep_alive_timeout = Duration::from_millis(int as u64);
+                        "reconnectretrylimit" => {
+                            let value = values.as_slice()[1];
+                            if let Ok(limit) = value.parse() {
+                                result.reconnect_retry_limit = limit;
                             } else {
                                 return Err(nom::Err::Failure(nom::error::Error::new(
                                     value,
@@ -562,6 +2251,15 @@ impl ClientSettings {
                 }
             }
 
+            if !result.tls_pinned_fingerprints.is_empty() && result.tls_ca_file.is_some() {
+                error!("{}", SettingsError::PinningExcludesCaFile);
+
+                return Err(nom::Err::Failure(nom::error::Error::new(
+                    params,
+                    ErrorKind::ParseTo,
+                )));
+            }
+
             input = new_input;
         }
 
@@ -611,20 +2309,299 @@ impl Default for ClientSettings {
         ClientSettings {
             dns_discover: false,
             hosts: Vec::new(),
+            unix_socket: None,
             max_discover_attempts: 3,
             discovery_interval: Duration::from_millis(500),
             gossip_timeout: Duration::from_secs(3),
             preference: Default::default(),
             secure: true,
             tls_verify_cert: true,
+            tls_pinned_fingerprints: Vec::new(),
+            tls_ca_file: None,
             throw_on_append_failure: true,
             default_user_name: None,
             keep_alive_interval: Duration::from_millis(self::defaults::KEEP_ALIVE_INTERVAL_IN_MS),
             keep_alive_timeout: Duration::from_millis(self::defaults::KEEP_ALIVE_TIMEOUT_IN_MS),
+            not_leader_behavior: Default::default(),
+            not_leader_retry_limit: 3,
+            not_leader_retry_delay: Duration::from_millis(200),
+            stale_leader_threshold: 3,
+            stale_leader_window: Duration::from_secs(10),
+            retry_jitter: Duration::default(),
+            connection_name: None,
+            connection_properties: BTreeMap::new(),
+            read_cache_size: 0,
+            read_cache_ttl: Duration::from_secs(1),
+            allow_system_stream_writes: false,
+            auto_enable_system_projections: false,
+            serialize_stream_writes: false,
+            decode_failure_policy: Default::default(),
+            empty_append: Default::default(),
+            max_queue_wait: Duration::default(),
+            connect_timeout: Duration::from_secs(10),
+            server_time_skew_threshold: Duration::default(),
+            write_timeout_retry_limit: 3,
+            slow_consumer_threshold: Duration::default(),
+            slow_consumer_drop_after: Duration::default(),
+            retry_backoff_multiplier: 1,
+            retry_backoff_max: Duration::default(),
+            default_deadline: Duration::default(),
+            reconnect_retry_limit: 0,
+            max_in_flight: throttle::DEFAULT_MAX_WINDOW,
         }
     }
 }
 
+/// Builds [`ClientSettings`] programmatically. Unlike connection string
+/// parsing, [`build`] rejects invalid combinations of settings.
+///
+/// [`build`]: ClientSettingsBuilder::build
+///
+/// ```
+/// # use eventstore::{ClientSettings, Endpoint};
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let setts = ClientSettings::builder()
+///     .host(Endpoint { host: "localhost".to_string(), port: 2113 })
+///     .tls_verify_cert(false)
+///     .build()?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct ClientSettingsBuilder {
+    inner: ClientSettings,
+}
+
+impl ClientSettingsBuilder {
+    /// Uses DNS discovery, the single host being the domain to query.
+    pub fn dns_discover(mut self, value: bool) -> Self {
+        self.inner.dns_discover = value;
+        self
+    }
+
+    /// Adds a host to connect to.
+    pub fn host(mut self, endpoint: Endpoint) -> Self {
+        self.inner.hosts.push(endpoint);
+        self
+    }
+
+    /// Connects through the Unix domain socket at the given path instead of
+    /// the hosts. The connection is plain text unless TLS is enabled
+    /// explicitly afterwards.
+    #[cfg(unix)]
+    pub fn unix_socket(mut self, path: impl Into<PathBuf>) -> Self {
+        self.inner.unix_socket = Some(path.into());
+        self.inner.hosts = vec![unix_socket_endpoint()];
+        self.inner.secure = false;
+        self
+    }
+
+    pub fn max_discover_attempts(mut self, value: usize) -> Self {
+        self.inner.max_discover_attempts = value;
+        self
+    }
+
+    pub fn discovery_interval(mut self, value: Duration) -> Self {
+        self.inner.discovery_interval = value;
+        self
+    }
+
+    pub fn gossip_timeout(mut self, value: Duration) -> Self {
+        self.inner.gossip_timeout = value;
+        self
+    }
+
+    pub fn node_preference(mut self, value: NodePreference) -> Self {
+        self.inner.preference = value;
+        self
+    }
+
+    pub fn secure(mut self, value: bool) -> Self {
+        self.inner.secure = value;
+        self
+    }
+
+    pub fn tls_verify_cert(mut self, value: bool) -> Self {
+        self.inner.tls_verify_cert = value;
+        self
+    }
+
+    /// Pins the SHA-256 fingerprints of the server certificates, replacing
+    /// the validation against the certificate authorities. Fingerprints are
+    /// hexadecimal digits, with or without colons. Pinning can't be combined
+    /// with [`ClientSettingsBuilder::tls_ca_file`].
+    pub fn tls_pinned_fingerprints<I, S>(mut self, fingerprints: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        self.inner.tls_pinned_fingerprints = fingerprints
+            .into_iter()
+            .map(|f| parse_fingerprint(f.as_ref()).unwrap_or_else(|| f.as_ref().to_string()))
+            .collect();
+        self
+    }
+
+    pub fn tls_ca_file(mut self, path: impl Into<PathBuf>) -> Self {
+        self.inner.tls_ca_file = Some(path.into());
+        self
+    }
+
+    pub fn default_authenticated_user(mut self, value: Credentials) -> Self {
+        self.inner.default_user_name = Some(value);
+        self
+    }
+
+    pub fn keep_alive_interval(mut self, value: Duration) -> Self {
+        self.inner.keep_alive_interval = value;
+        self
+    }
+
+    pub fn keep_alive_timeout(mut self, value: Duration) -> Self {
+        self.inner.keep_alive_timeout = value;
+        self
+    }
+
+    pub fn not_leader_behavior(mut self, value: NotLeaderBehavior) -> Self {
+        self.inner.not_leader_behavior = value;
+        self
+    }
+
+    pub fn not_leader_retry_limit(mut self, value: usize) -> Self {
+        self.inner.not_leader_retry_limit = value;
+        self
+    }
+
+    pub fn not_leader_retry_delay(mut self, value: Duration) -> Self {
+        self.inner.not_leader_retry_delay = value;
+        self
+    }
+
+    pub fn stale_leader_threshold(mut self, value: usize) -> Self {
+        self.inner.stale_leader_threshold = value;
+        self
+    }
+
+    pub fn stale_leader_window(mut self, value: Duration) -> Self {
+        self.inner.stale_leader_window = value;
+        self
+    }
+
+    pub fn connection_name(mut self, value: impl Into<String>) -> Self {
+        self.inner.connection_name = Some(value.into());
+        self
+    }
+
+    pub fn connection_property(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.inner
+            .connection_properties
+            .insert(key.into(), value.into());
+        self
+    }
+
+    pub fn retry_jitter(mut self, value: Duration) -> Self {
+        self.inner.retry_jitter = value;
+        self
+    }
+
+    pub fn read_cache_size(mut self, value: usize) -> Self {
+        self.inner.read_cache_size = value;
+        self
+    }
+
+    pub fn read_cache_ttl(mut self, value: Duration) -> Self {
+        self.inner.read_cache_ttl = value;
+        self
+    }
+
+    pub fn allow_system_stream_writes(mut self, value: bool) -> Self {
+        self.inner.allow_system_stream_writes = value;
+        self
+    }
+
+    pub fn auto_enable_system_projections(mut self, value: bool) -> Self {
+        self.inner.auto_enable_system_projections = value;
+        self
+    }
+
+    pub fn serialize_stream_writes(mut self, value: bool) -> Self {
+        self.inner.serialize_stream_writes = value;
+        self
+    }
+
+    pub fn decode_failure_policy(mut self, value: DecodeFailurePolicy) -> Self {
+        self.inner.decode_failure_policy = value;
+        self
+    }
+
+    pub fn empty_append(mut self, value: EmptyAppendBehavior) -> Self {
+        self.inner.empty_append = value;
+        self
+    }
+
+    pub fn max_queue_wait(mut self, value: Duration) -> Self {
+        self.inner.max_queue_wait = value;
+        self
+    }
+
+    pub fn connect_timeout(mut self, value: Duration) -> Self {
+        self.inner.connect_timeout = value;
+        self
+    }
+
+    pub fn server_time_skew_threshold(mut self, value: Duration) -> Self {
+        self.inner.server_time_skew_threshold = value;
+        self
+    }
+
+    pub fn write_timeout_retry_limit(mut self, value: usize) -> Self {
+        self.inner.write_timeout_retry_limit = value;
+        self
+    }
+
+    pub fn slow_consumer_threshold(mut self, value: Duration) -> Self {
+        self.inner.slow_consumer_threshold = value;
+        self
+    }
+
+    pub fn slow_consumer_drop_after(mut self, value: Duration) -> Self {
+        self.inner.slow_consumer_drop_after = value;
+        self
+    }
+
+    pub fn retry_backoff_multiplier(mut self, value: u32) -> Self {
+        self.inner.retry_backoff_multiplier = value;
+        self
+    }
+
+    pub fn retry_backoff_max(mut self, value: Duration) -> Self {
+        self.inner.retry_backoff_max = value;
+        self
+    }
+
+    pub fn default_deadline(mut self, value: Duration) -> Self {
+        self.inner.default_deadline = value;
+        self
+    }
+
+    pub fn reconnect_retry_limit(mut self, value: usize) -> Self {
+        self.inner.reconnect_retry_limit = value;
+        self
+    }
+
+    pub fn max_in_flight(mut self, value: usize) -> Self {
+        self.inner.max_in_flight = value;
+        self
+    }
+
+    /// Validates and returns the settings.
+    pub fn build(self) -> Result<ClientSettings, SettingsError> {
+        self.inner.validate()?;
+
+        Ok(self.inner)
+    }
+}
+
 pub(crate) mod defaults {
     pub const KEEP_ALIVE_INTERVAL_IN_MS: u64 = 10_000;
     pub const KEEP_ALIVE_TIMEOUT_IN_MS: u64 = 10_000;
@@ -632,7 +2609,11 @@ pub(crate) mod defaults {
 
 async fn cluster_mode(
     conn_setts: ClientSettings,
-) -> Result<UnboundedSender<Msg>, Box<dyn std::error::Error>> {
+    topology: Option<ClusterTopology>,
+    clock: Arc<dyn Clock>,
+    aborted: watch::Receiver<()>,
+    current: watch::Sender<Option<CurrentNode>>,
+) -> Result<(UnboundedSender<Msg>, JoinHandle<()>), Box<dyn std::error::Error>> {
     let (sender, mut consumer) = futures::channel::mpsc::unbounded::<Msg>();
     let kind = if conn_setts.dns_discover {
         let endpoint = conn_setts.hosts.as_slice()[0].clone();
@@ -645,14 +2626,36 @@ async fn cluster_mode(
 
     let dup_sender = sender.clone();
 
-    tokio::spawn(async move {
+    if let Some(topology) = topology.as_ref() {
+        let mut changes = topology.subscribe();
+        let mut sender = sender.clone();
+
+        tokio::spawn(async move {
+            while changes.changed().await.is_ok() {
+                let members = changes.borrow().clone();
+
+                if sender.send(Msg::TopologyChanged(members)).await.is_err() {
+                    break;
+                }
+            }
+        });
+    }
+
+    let task = tokio::spawn(async move {
         let mut channel: Option<Channel> = None;
         let mut channel_id = Uuid::new_v4();
         let mut failed_endpoint: Option<Endpoint> = None;
         let mut previous_candidates: Option<Vec<Member>> = None;
+        let mut members: Vec<MemberInfo> = Vec::new();
+        let mut advertised_leader: Option<Endpoint> = None;
         let mut work_queue = Vec::new();
         let mut rng = SmallRng::from_entropy();
         let mut discovery_att_count = 0usize;
+        let mut pin_mismatch: Option<Endpoint> = None;
+        let mut flapping = LeaderFlapping::new(
+            conn_setts.stale_leader_threshold,
+            conn_setts.stale_leader_window,
+        );
 
         while let Some(item) = consumer.next().await {
             work_queue.push(item);
@@ -666,14 +2669,21 @@ async fn cluster_mode(
                                 id: channel_id,
                                 channel: channel.clone(),
                                 sender: dup_sender.clone(),
+                                aborted: aborted.clone(),
                             };
 
                             let _ = resp.send(Ok(handle));
                         } else if discovery_att_count >= conn_setts.max_discover_attempts() {
-                            let _ =
-                                resp.send(Err(GrpcConnectionError::MaxDiscoveryAttemptReached(
+                            let err = match pin_mismatch.take() {
+                                Some(endpoint) => {
+                                    GrpcConnectionError::CertificatePinMismatch(endpoint)
+                                }
+                                None => GrpcConnectionError::MaxDiscoveryAttemptReached(
                                     conn_setts.max_discover_attempts(),
-                                )));
+                                ),
+                            };
+
+                            let _ = resp.send(Err(err));
 
                             discovery_att_count = 0;
                         } else {
@@ -688,6 +2698,8 @@ async fn cluster_mode(
                             continue;
                         }
 
+                        let _ = current.send(None);
+
                         let node = if let Some(ref seed) = seed_opt {
                             Some(seed.clone())
                         } else {
@@ -697,6 +2709,8 @@ async fn cluster_mode(
                                 &failed_endpoint,
                                 &mut rng,
                                 &mut previous_candidates,
+                                &mut members,
+                                topology.as_ref(),
                             )
                             .await
                         };
@@ -704,10 +2718,21 @@ async fn cluster_mode(
                         if let Some(node) = node {
                             match create_channel(&conn_setts, &node).await {
                                 Ok(new_channel) => {
+                                    let role = if advertised_leader.take().as_ref() == Some(&node) {
+                                        Some(NodeRole::Leader)
+                                    } else {
+                                        node_role(&members, &node)
+                                    };
+
+                                    let _ = current.send(Some(CurrentNode {
+                                        endpoint: node.clone(),
+                                        role,
+                                    }));
                                     failed_endpoint = Some(node);
                                     channel_id = Uuid::new_v4();
                                     channel = Some(new_channel);
                                     discovery_att_count = 0;
+                                    pin_mismatch = None;
 
                                     continue;
                                 }
@@ -717,6 +2742,10 @@ async fn cluster_mode(
                                         "Error when creating a gRPC channel for selected node {:?}: {}",
                                         node, err
                                     );
+
+                                    if is_pin_mismatch(&err) {
+                                        pin_mismatch = Some(node);
+                                    }
                                 }
                             }
                         } else {
@@ -728,7 +2757,9 @@ async fn cluster_mode(
                         }
 
                         if discovery_att_count < conn_setts.max_discover_attempts() {
-                            tokio::time::sleep(conn_setts.discovery_interval).await;
+                            clock
+                                .sleep(conn_setts.jittered(conn_setts.discovery_interval))
+                                .await;
                             discovery_att_count += 1;
                             work_queue.push(Msg::CreateChannel(id, seed_opt));
                             continue;
@@ -739,23 +2770,138 @@ async fn cluster_mode(
                             conn_setts.max_discover_attempts()
                         );
                     }
+
+                    Msg::NotLeader(id, leader) => {
+                        if channel_id != id {
+                            continue;
+                        }
+
+                        if !flapping.redirected(clock.now()) {
+                            advertised_leader = Some(leader.clone());
+                            work_queue.push(Msg::CreateChannel(id, Some(leader)));
+                            continue;
+                        }
+
+                        warn!(
+                            "Received {} NotLeader responses within {:?}, the advertised leader {:?} looks stale. Forcing a fresh gossip query",
+                            conn_setts.stale_leader_threshold, conn_setts.stale_leader_window, leader
+                        );
+
+                        previous_candidates = None;
+
+                        if let Some(topology) = topology.as_ref() {
+                            topology.invalidate().await;
+                        }
+
+                        work_queue.push(Msg::CreateChannel(id, None));
+                    }
+
+                    Msg::TopologyChanged(latest) => {
+                        members = latest;
+
+                        let connected = match failed_endpoint.as_ref() {
+                            Some(connected) if channel.is_some() => connected,
+                            _ => continue,
+                        };
+
+                        if topo::node_suits_preference(conn_setts.preference, connected, &members) {
+                            let role = node_role(&members, connected);
+
+                            current.send_if_modified(|node| match node {
+                                Some(node) if node.role != role => {
+                                    node.role = role;
+                                    true
+                                }
+                                _ => false,
+                            });
+
+                            continue;
+                        }
+
+                        if let Some(node) =
+                            determine_best_node(&mut rng, conn_setts.preference, &members)
+                        {
+                            if node != *connected {
+                                info!(
+                                    "Cluster topology changed, switching from {:?} to {:?}",
+                                    connected, node
+                                );
+
+                                work_queue.push(Msg::CreateChannel(channel_id, Some(node)));
+                            }
+                        }
+                    }
                 }
             }
         }
     });
 
-    Ok(sender)
+    Ok((sender, task))
+}
+
+/// Counts the `NotLeader` redirections a cluster connection receives within a
+/// time window, so a leader advertisement that keeps pointing at stale nodes
+/// during a long election is eventually ignored.
+struct LeaderFlapping {
+    threshold: usize,
+    window: Duration,
+    since: Option<Instant>,
+    count: usize,
+}
+
+impl LeaderFlapping {
+    fn new(threshold: usize, window: Duration) -> Self {
+        LeaderFlapping {
+            threshold,
+            window,
+            since: None,
+            count: 0,
+        }
+    }
+
+    /// Records a redirection and returns `true` if the threshold is reached,
+    /// in which case the count starts over.
+    fn redirected(&mut self, now: Instant) -> bool {
+        if self.threshold == 0 {
+            return false;
+        }
+
+        match self.since {
+            Some(since) if now.saturating_duration_since(since) <= self.window => self.count += 1,
+
+            _ => {
+                self.since = Some(now);
+                self.count = 1;
+            }
+        }
+
+        if self.count < self.threshold {
+            return false;
+        }
+
+        self.since = None;
+        self.count = 0;
+
+        true
+    }
 }
 
-fn single_node_mode(conn_setts: ClientSettings, endpoint: Endpoint) -> UnboundedSender<Msg> {
+fn single_node_mode(
+    conn_setts: ClientSettings,
+    endpoint: Endpoint,
+    clock: Arc<dyn Clock>,
+    aborted: watch::Receiver<()>,
+    current: watch::Sender<Option<CurrentNode>>,
+) -> (UnboundedSender<Msg>, JoinHandle<()>) {
     let (sender, mut consumer) = futures::channel::mpsc::unbounded::<Msg>();
     let dup_sender = sender.clone();
 
-    tokio::spawn(async move {
+    let task = tokio::spawn(async move {
         let mut channel: Option<Channel> = None;
         let mut channel_id = Uuid::new_v4();
         let mut work_queue = Vec::new();
         let mut discovery_att_count = 0usize;
+        let mut pin_mismatch: Option<Endpoint> = None;
 
         while let Some(item) = consumer.next().await {
             work_queue.push(item);
@@ -770,14 +2916,21 @@ fn single_node_mode(conn_setts: ClientSettings, endpoint: Endpoint) -> Unbounded
                                 id: channel_id,
                                 channel: channel.clone(),
                                 sender: dup_sender.clone(),
+                                aborted: aborted.clone(),
                             };
 
                             let _ = resp.send(Ok(handle));
                         } else if discovery_att_count >= conn_setts.max_discover_attempts() {
-                            let _ =
-                                resp.send(Err(GrpcConnectionError::MaxDiscoveryAttemptReached(
+                            let err = match pin_mismatch.take() {
+                                Some(endpoint) => {
+                                    GrpcConnectionError::CertificatePinMismatch(endpoint)
+                                }
+                                None => GrpcConnectionError::MaxDiscoveryAttemptReached(
                                     conn_setts.max_discover_attempts(),
-                                )));
+                                ),
+                            };
+
+                            let _ = resp.send(Err(err));
 
                             discovery_att_count = 0;
                         } else {
@@ -792,6 +2945,8 @@ fn single_node_mode(conn_setts: ClientSettings, endpoint: Endpoint) -> Unbounded
                             continue;
                         }
 
+                        let _ = current.send(None);
+
                         let node = if let Some(ref seed) = seed_opt {
                             seed.clone()
                         } else {
@@ -800,10 +2955,24 @@ fn single_node_mode(conn_setts: ClientSettings, endpoint: Endpoint) -> Unbounded
 
                         match create_channel(&conn_setts, &node).await {
                             Ok(new_channel) => {
+                                let _ = current.send(Some(CurrentNode {
+                                    endpoint: node,
+                                    role: None,
+                                }));
                                 channel_id = Uuid::new_v4();
                                 channel = Some(new_channel);
                             }
 
+                            Err(err) if is_pin_mismatch(&err) => {
+                                error!(
+                                    "Certificate of {} isn't pinned, giving up",
+                                    conn_setts.to_uri(&node)
+                                );
+
+                                discovery_att_count = conn_setts.max_discover_attempts();
+                                pin_mismatch = Some(node);
+                            }
+
                             Err(err) => {
                                 error!(
                                     "Error when connecting to {}: {}. Retrying...({}/{})",
@@ -814,7 +2983,9 @@ fn single_node_mode(conn_setts: ClientSettings, endpoint: Endpoint) -> Unbounded
                                 );
 
                                 if discovery_att_count < conn_setts.max_discover_attempts() {
-                                    tokio::time::sleep(conn_setts.discovery_interval).await;
+                                    clock
+                                        .sleep(conn_setts.jittered(conn_setts.discovery_interval))
+                                        .await;
                                     work_queue.push(Msg::CreateChannel(id, seed_opt));
                                     discovery_att_count += 1;
                                     continue;
@@ -827,25 +2998,62 @@ fn single_node_mode(conn_setts: ClientSettings, endpoint: Endpoint) -> Unbounded
                             }
                         }
                     }
+
+                    Msg::NotLeader(id, leader) => {
+                        work_queue.push(Msg::CreateChannel(id, Some(leader)));
+                    }
+
+                    Msg::TopologyChanged(_) => {}
                 }
             }
         }
     });
 
-    sender
+    (sender, task)
+}
+
+/// Reads a PEM file, making sure it holds at least a certificate.
+fn read_ca_file(path: &Path) -> std::io::Result<Vec<u8>> {
+    let pem = std::fs::read(path)?;
+    let certs = rustls::internal::pemfile::certs(&mut pem.as_slice()).unwrap_or_default();
+
+    if certs.is_empty() {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidData,
+            "no PEM certificate found",
+        ));
+    }
+
+    Ok(pem)
 }
 
-async fn create_channel(
+pub(crate) async fn create_channel(
     setts: &ClientSettings,
     endpoint: &Endpoint,
 ) -> Result<Channel, tonic::transport::Error> {
     let uri = setts.to_uri(endpoint);
 
-    debug!("Create gRPC channel for: {}", uri);
+    debug!("Create gRPC channel for: {}", uri);
+
+    let mut channel = Channel::builder(uri.clone());
+
+    if !setts.tls_pinned_fingerprints.is_empty() && setts.secure {
+        let mut rustls_config = rustls::ClientConfig::new();
+        let protocols = vec![(b"h2".to_vec())];
+
+        rustls_config.set_protocols(protocols.as_slice());
 
-    let mut channel = Channel::builder(uri.clone());
+        rustls_config
+            .dangerous()
+            .set_certificate_verifier(std::sync::Arc::new(PinnedCertificates {
+                fingerprints: setts.tls_pinned_fingerprints.clone(),
+            }));
+
+        let client_config =
+            tonic::transport::ClientTlsConfig::new().rustls_client_config(rustls_config);
 
-    if !setts.tls_verify_cert && setts.secure {
+        channel = channel.tls_config(client_config)?;
+    } else if !setts.tls_verify_cert && setts.secure {
         let mut rustls_config = rustls::ClientConfig::new();
         let protocols = vec![(b"h2".to_vec())];
 
@@ -860,14 +3068,44 @@ async fn create_channel(
 
         channel = channel.tls_config(client_config)?;
     } else if setts.secure {
-        channel = channel.tls_config(tonic::transport::ClientTlsConfig::new())?;
+        let mut client_config = tonic::transport::ClientTlsConfig::new();
+
+        if let Some(path) = setts.tls_ca_file.as_ref() {
+            match read_ca_file(path) {
+                Ok(pem) => {
+                    client_config =
+                        client_config.ca_certificate(tonic::transport::Certificate::from_pem(pem));
+                }
+
+                // Only the system authorities are trusted then, so the nodes
+                // signed by the missing one are rejected.
+                Err(e) => error!(
+                    "Unable to read certificate authority file {:?}: {}",
+                    path, e
+                ),
+            }
+        }
+
+        channel = channel.tls_config(client_config)?;
     }
 
     let channel = channel
+        .user_agent(setts.user_agent())?
         .http2_keep_alive_interval(setts.keep_alive_interval)
-        .keep_alive_timeout(setts.keep_alive_timeout)
-        .connect()
-        .await?;
+        .keep_alive_timeout(setts.keep_alive_timeout);
+
+    let channel = match setts.unix_socket.clone() {
+        #[cfg(unix)]
+        Some(path) => {
+            channel
+                .connect_with_connector(tower::service_fn(move |_: http::Uri| {
+                    tokio::net::UnixStream::connect(path.clone())
+                }))
+                .await?
+        }
+
+        _ => channel.connect().await?,
+    };
 
     debug!("Connected to Node: {}", uri);
 
@@ -879,18 +3117,86 @@ pub(crate) struct Handle {
     id: Uuid,
     pub(crate) channel: Channel,
     sender: futures::channel::mpsc::UnboundedSender<Msg>,
+    aborted: watch::Receiver<()>,
 }
 
 impl Handle {
+    pub(crate) fn id(&self) -> Uuid {
+        self.id
+    }
+
+    /// Waits for the next message of a server stream. If every client
+    /// sharing this connection is dropped in the meantime, fails with an
+    /// aborted status, which maps to [`crate::Error::Aborted`].
+    pub(crate) async fn next_message<M>(
+        &self,
+        stream: &mut tonic::Streaming<M>,
+    ) -> Result<Option<M>, Status> {
+        let mut aborted = self.aborted.clone();
+        let next = stream.message();
+        let aborted = aborted.changed();
+
+        futures::pin_mut!(next, aborted);
+
+        match futures::future::select(next, aborted).await {
+            futures::future::Either::Left((message, _)) => message,
+            futures::future::Either::Right(_) => Err(Status::aborted(ABORTED_MESSAGE)),
+        }
+    }
+
     pub(crate) async fn report_error(mut self, e: crate::Error) {
         error!("Error occurred during operation execution: {:?}", e);
         let _ = self.sender.send(Msg::CreateChannel(self.id, None)).await;
     }
 }
 
-enum Msg {
+#[cfg(test)]
+impl Handle {
+    pub(crate) fn new(
+        channel: Channel,
+        sender: futures::channel::mpsc::UnboundedSender<Msg>,
+        aborted: watch::Receiver<()>,
+    ) -> Self {
+        Handle {
+            id: Uuid::new_v4(),
+            channel,
+            sender,
+            aborted,
+        }
+    }
+}
+
+/// Failures after which an operation is sent again.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum Resend {
+    /// The operation can only be sent once.
+    Never,
+    /// The server declined to handle the operation.
+    NotHandled,
+    /// The operation is an idempotent write, also sent again when the server
+    /// timed out writing it.
+    Idempotent,
+}
+
+/// Whether an operation can be sent again after its node went away, which
+/// the server might have handled before.
+fn replayable(kind: OperationKind, resend: Resend) -> bool {
+    resend == Resend::Idempotent
+        || matches!(
+            kind,
+            OperationKind::ReadStream
+                | OperationKind::ReadAll
+                | OperationKind::SubscribeToStream
+                | OperationKind::SubscribeToAll
+                | OperationKind::ConnectPersistentSubscription
+        )
+}
+
+pub(crate) enum Msg {
     GetChannel(oneshot::Sender<Result<Handle, GrpcConnectionError>>),
     CreateChannel(Uuid, Option<Endpoint>),
+    NotLeader(Uuid, Endpoint),
+    TopologyChanged(Vec<MemberInfo>),
 }
 
 impl std::fmt::Debug for Msg {
@@ -900,21 +3206,60 @@ impl std::fmt::Debug for Msg {
             Msg::CreateChannel(id, seed_opt) => {
                 write!(f, "Msg::CreateChannel({:?}, {:?})", id, seed_opt)
             }
+            Msg::NotLeader(id, leader) => write!(f, "Msg::NotLeader({:?}, {:?})", id, leader),
+            Msg::TopologyChanged(_) => write!(f, "Msg::TopologyChanged"),
         }
     }
 }
 
+/// Status message used when an operation is aborted because the client was
+/// dropped.
+pub(crate) const ABORTED_MESSAGE: &str = "The client was dropped";
+
+/// Stops the connection task once the last clone of a client is dropped.
+struct ConnectionGuard {
+    task: JoinHandle<()>,
+    // Dropping the sender notifies every handle the connection is aborted.
+    _aborted: watch::Sender<()>,
+}
+
+impl Drop for ConnectionGuard {
+    fn drop(&mut self) {
+        debug!("Last client handle dropped, stopping the connection");
+        self.task.abort();
+    }
+}
+
 #[derive(Clone)]
 pub struct GrpcClient {
     sender: futures::channel::mpsc::UnboundedSender<Msg>,
     default_credentials: Option<Credentials>,
+    settings: Arc<ClientSettings>,
+    throttle: Arc<Throttle>,
+    metrics: Arc<Metrics>,
+    clock: Arc<dyn Clock>,
+    retry_policy: Arc<dyn RetryPolicy>,
+    listener: Option<Arc<dyn OperationListener>>,
+    current: watch::Receiver<Option<CurrentNode>>,
+    time_skew: SharedTimeSkew,
+    subscriptions: Arc<SubscriptionRegistry>,
+    _guard: Arc<ConnectionGuard>,
 }
 
 impl GrpcClient {
-    pub async fn create(conn_setts: ClientSettings) -> Result<Self, Box<dyn std::error::Error>> {
+    /// Relies on `clock` for the timeout and backoff logic, the reconnection
+    /// delays included.
+    pub async fn create(
+        conn_setts: ClientSettings,
+        topology: Option<ClusterTopology>,
+        clock: Arc<dyn Clock>,
+    ) -> Result<Self, Box<dyn std::error::Error>> {
         let default_credentials = conn_setts.default_user_name.clone();
-        let sender = if conn_setts.dns_discover || conn_setts.hosts.len() > 1 {
-            cluster_mode(conn_setts).await?
+        let settings = Arc::new(conn_setts.clone());
+        let (aborted_sender, aborted) = watch::channel(());
+        let (current_sender, current) = watch::channel(None);
+        let (sender, task) = if conn_setts.dns_discover || conn_setts.hosts.len() > 1 {
+            cluster_mode(conn_setts, topology, clock.clone(), aborted, current_sender).await?
         } else {
             let endpoint = conn_setts
                 .hosts
@@ -922,66 +3267,593 @@ impl GrpcClient {
                 .expect("Impossible: hosts can't be empty")
                 .clone();
 
-            single_node_mode(conn_setts, endpoint)
+            single_node_mode(conn_setts, endpoint, clock.clone(), aborted, current_sender)
         };
 
+        let time_skew = SharedTimeSkew::default();
+        let metrics = Arc::new(Metrics::default());
+        let subscriptions = Arc::new(SubscriptionRegistry::new(&settings, metrics.clone()));
+
+        if settings.server_time_skew_threshold != Duration::default() {
+            time_skew::spawn_monitor(settings.clone(), current.clone(), time_skew.clone());
+        }
+
         Ok(GrpcClient {
             sender,
             default_credentials,
+            throttle: Arc::new(Throttle::new(settings.max_in_flight)),
+            settings,
+            metrics,
+            clock,
+            retry_policy: Arc::new(SettingsRetryPolicy),
+            listener: None,
+            current,
+            time_skew,
+            subscriptions,
+            _guard: Arc::new(ConnectionGuard {
+                task,
+                _aborted: aborted_sender,
+            }),
         })
     }
 
-    pub(crate) async fn execute<F, Fut, A>(&self, action: F) -> crate::Result<A>
-    where
-        F: FnOnce(Handle) -> Fut + Send,
-        Fut: Future<Output = Result<A, Status>> + Send,
-        A: Send,
-    {
+    /// Client whose connection task is provided by the caller, see
+    /// [`crate::test_transport`].
+    #[cfg(test)]
+    pub(crate) fn from_connection(
+        settings: ClientSettings,
+        sender: futures::channel::mpsc::UnboundedSender<Msg>,
+        task: JoinHandle<()>,
+        aborted: watch::Sender<()>,
+    ) -> Self {
+        let (_, current) = watch::channel(None);
+        let metrics = Arc::new(Metrics::default());
+        let subscriptions = Arc::new(SubscriptionRegistry::new(&settings, metrics.clone()));
+
+        GrpcClient {
+            sender,
+            default_credentials: settings.default_user_name.clone(),
+            throttle: Arc::new(Throttle::new(settings.max_in_flight)),
+            settings: Arc::new(settings),
+            metrics,
+            clock: Arc::new(crate::clock::SystemClock),
+            retry_policy: Arc::new(SettingsRetryPolicy),
+            listener: None,
+            current,
+            time_skew: SharedTimeSkew::default(),
+            subscriptions,
+            _guard: Arc::new(ConnectionGuard {
+                task,
+                _aborted: aborted,
+            }),
+        }
+    }
+
+    /// Uses the given clock for the operations, the connection keeping the
+    /// clock it was created with.
+    #[cfg(test)]
+    pub(crate) fn with_clock(self, clock: Arc<dyn Clock>) -> Self {
+        GrpcClient { clock, ..self }
+    }
+
+    /// Decides whether the operations the server didn't handle are sent
+    /// again, instead of following the settings.
+    pub(crate) fn with_retry_policy(self, retry_policy: Arc<dyn RetryPolicy>) -> Self {
+        GrpcClient {
+            retry_policy,
+            ..self
+        }
+    }
+
+    /// Notifies the given listener of the lifecycle of every operation.
+    pub(crate) fn with_listener(self, listener: Arc<dyn OperationListener>) -> Self {
+        GrpcClient {
+            listener: Some(listener),
+            ..self
+        }
+    }
+
+    pub(crate) fn clock(&self) -> &Arc<dyn Clock> {
+        &self.clock
+    }
+
+    /// Node the connection has a channel to, `None` while disconnected.
+    pub(crate) fn current_node(&self) -> Option<CurrentNode> {
+        self.current.borrow().clone()
+    }
+
+    /// Registry of the subscriptions open through this connection.
+    pub(crate) fn subscriptions(&self) -> &Arc<SubscriptionRegistry> {
+        &self.subscriptions
+    }
+
+    /// Measures the round-trip time of a gossip request to the current node.
+    /// It's not recorded in the operation counters.
+    pub(crate) async fn ping(&self) -> crate::Result<Duration> {
+        let handle = self.get_handle().await?;
+        let gossip = Gossip::create(handle.channel.clone());
+        let start = self.clock.now();
+
+        gossip.ping().await.map_err(crate::Error::from_grpc)?;
+
+        Ok(self.clock.now() - start)
+    }
+
+    /// Waits for a channel to a node and a first round-trip to it, failing
+    /// with `GrpcConnectionError::ConnectionUnavailable` if it takes longer
+    /// than the `connectTimeout` setting.
+    pub(crate) async fn warm_up(&self) -> crate::Result<()> {
+        let timeout = self.settings.connect_timeout;
+        let ping = self.ping();
+
+        if timeout == Duration::default() {
+            return ping.await.map(|_| ());
+        }
+
+        futures::pin_mut!(ping);
+
+        match futures::future::select(ping, self.clock.sleep(timeout)).await {
+            futures::future::Either::Left((result, _)) => result.map(|_| ()),
+            futures::future::Either::Right(_) => {
+                warn!("Couldn't connect within {:?}", timeout);
+
+                Err(crate::Error::GrpcConnectionError(
+                    GrpcConnectionError::ConnectionUnavailable(timeout),
+                ))
+            }
+        }
+    }
+
+    /// Last skew measured between the node clock and the local one.
+    pub(crate) fn server_time_skew(&self) -> Option<ServerTimeSkew> {
+        *self.time_skew.lock().unwrap()
+    }
+
+    async fn get_handle(&self) -> crate::Result<Handle> {
         let (sender, consumer) = futures::channel::oneshot::channel();
 
         debug!("Sending channel handle request...");
         let _ = self.sender.clone().send(Msg::GetChannel(sender)).await;
 
-        let handle = match consumer.await {
+        let max_wait = self.settings.max_queue_wait;
+        let handle = if max_wait == Duration::default() {
+            consumer.await
+        } else {
+            let timeout = self.clock.sleep(max_wait);
+
+            match futures::future::select(consumer, timeout).await {
+                futures::future::Either::Left((handle, _)) => handle,
+                futures::future::Either::Right(_) => {
+                    warn!("No connection available after waiting {:?}", max_wait);
+
+                    Ok(Err(GrpcConnectionError::ConnectionUnavailable(max_wait)))
+                }
+            }
+        };
+
+        let handle = match handle {
             Ok(handle) => handle.map_err(crate::Error::GrpcConnectionError),
             Err(_) => Err(crate::Error::ConnectionClosed),
         }?;
 
         debug!("Handle received!");
 
-        let id = handle.id;
-        match action(handle).await {
-            Err(status) => {
-                let err = crate::Error::from_grpc(status);
+        Ok(handle)
+    }
+
+    /// Runs an operation against the currently selected node. Because the
+    /// operation might be retried, depending on the [`NotLeaderBehavior`]
+    /// setting, when the server is too busy or as the retry policy decides,
+    /// `action` must be able to rebuild its request on each call.
+    pub(crate) async fn execute<F, Fut, A>(
+        &self,
+        kind: OperationKind,
+        action: F,
+    ) -> crate::Result<A>
+    where
+        F: Fn(Handle) -> Fut + Send,
+        Fut: Future<Output = Result<A, Status>> + Send,
+        A: Send,
+    {
+        let (a, _) = self.execute_with(kind, Resend::NotHandled, action).await?;
+
+        Ok(a)
+    }
+
+    /// Like [`GrpcClient::execute`], also returning the retries the
+    /// operation went through. An `idempotent` write is also sent again when
+    /// the server timed out writing it, up to the `writeTimeoutRetryLimit`
+    /// setting.
+    pub(crate) async fn execute_with_retries<F, Fut, A>(
+        &self,
+        kind: OperationKind,
+        idempotent: bool,
+        action: F,
+    ) -> crate::Result<(A, Retries)>
+    where
+        F: Fn(Handle) -> Fut + Send,
+        Fut: Future<Output = Result<A, Status>> + Send,
+        A: Send,
+    {
+        let resend = if idempotent {
+            Resend::Idempotent
+        } else {
+            Resend::NotHandled
+        };
+
+        self.execute_with(kind, resend, action).await
+    }
+
+    /// Runs an operation that can only be sent once, for instance because
+    /// its request streams data that can't be produced again. The operation
+    /// is never retried.
+    pub(crate) async fn execute_once<F, Fut, A>(
+        &self,
+        kind: OperationKind,
+        action: F,
+    ) -> crate::Result<A>
+    where
+        F: FnOnce(Handle) -> Fut + Send,
+        Fut: Future<Output = Result<A, Status>> + Send,
+        A: Send,
+    {
+        let action = std::sync::Mutex::new(Some(action));
+
+        let (a, _) = self
+            .execute_with(kind, Resend::Never, |handle| {
+                let action = action
+                    .lock()
+                    .unwrap()
+                    .take()
+                    .expect("Impossible: the operation was already sent");
+
+                action(handle)
+            })
+            .await?;
+
+        Ok(a)
+    }
+
+    async fn execute_with<F, Fut, A>(
+        &self,
+        kind: OperationKind,
+        resend: Resend,
+        action: F,
+    ) -> crate::Result<(A, Retries)>
+    where
+        F: Fn(Handle) -> Fut + Send,
+        Fut: Future<Output = Result<A, Status>> + Send,
+        A: Send,
+    {
+        self.metrics.issued(kind);
+
+        let id = operation::current();
+
+        if let Some(id) = id {
+            debug!("Running {:?} operation {}", kind, id);
+        }
+
+        let started = self.listener.as_ref().map(|listener| {
+            listener.on_issued(kind, id);
+
+            self.clock.now()
+        });
+
+        let deadline = self.settings.default_deadline;
+        let result = if deadline == Duration::default() {
+            self.execute_attempts(kind, resend, action).await
+        } else {
+            let attempts = self.execute_attempts(kind, resend, action);
+
+            futures::pin_mut!(attempts);
+
+            // Dropping the attempts cancels the request in flight.
+            match futures::future::select(attempts, self.clock.sleep(deadline)).await {
+                futures::future::Either::Left((result, _)) => result,
+                futures::future::Either::Right(_) => {
+                    warn!("{:?} operation didn't complete within {:?}", kind, deadline);
+
+                    Err(crate::Error::DeadlineExceeded)
+                }
+            }
+        };
+
+        let outcome = match result {
+            Ok(_) => OperationOutcome::Succeeded,
+            Err(ref e) => OperationOutcome::Failed(e),
+        };
+
+        match outcome {
+            OperationOutcome::Succeeded => self.metrics.succeeded(kind),
+            OperationOutcome::Failed(_) => self.metrics.failed(kind, outcome.timed_out()),
+        }
+
+        if let (Some(listener), Some(started)) = (self.listener.as_ref(), started) {
+            listener.on_completed(kind, id, outcome, self.clock.now() - started);
+        }
+
+        result
+    }
+
+    async fn execute_attempts<F, Fut, A>(
+        &self,
+        kind: OperationKind,
+        resend: Resend,
+        action: F,
+    ) -> crate::Result<(A, Retries)>
+    where
+        F: Fn(Handle) -> Fut + Send,
+        Fut: Future<Output = Result<A, Status>> + Send,
+        A: Send,
+    {
+        let mut attempts = 1usize;
+        let mut busy_attempts = 0usize;
+        let mut timeout_attempts = 0usize;
+        let mut reconnect_attempts = 0usize;
+        let mut retries = Retries::default();
+        let retry = resend != Resend::Never;
+
+        loop {
+            let permit = self.throttle.acquire().await;
+            let handle = self.get_handle().await?;
+            let id = handle.id;
+            let result = action(handle).await;
+
+            drop(permit);
+
+            match result {
+                Err(status) => {
+                    let err = crate::Error::from_grpc(status);
+
+                    if let crate::Error::ServerBusy = err {
+                        let delay = self.throttle.on_busy();
+                        let suggested = if busy_attempts < throttle::MAX_BUSY_RETRIES {
+                            RetryDecision::Retry(self.settings.jittered(delay))
+                        } else {
+                            RetryDecision::GiveUp
+                        };
+                        let decision = self.retry_decision(
+                            retry,
+                            kind,
+                            RetryReason::ServerBusy,
+                            &retries,
+                            suggested,
+                        );
 
-                if let crate::Error::ServerError(ref status) = err {
-                    error!(
+                        self.metrics
+                            .not_handled(kind, RetryReason::ServerBusy, decision.is_some());
+
+                        if let Some(delay) = decision {
+                            busy_attempts += 1;
+                            self.retry_after(kind, RetryReason::ServerBusy, &mut retries, delay)
+                                .await;
+                            continue;
+                        }
+                    } else if let crate::Error::ServerError(ref status) = err {
+                        error!(
                             "Current selected EventStoreDB node gone unavailable. Starting node selection process: {}", status
                         );
 
-                    let _ = self.sender.clone().send(Msg::CreateChannel(id, None)).await;
-                } else if let crate::Error::NotLeaderException(ref leader) = err {
-                    let _ = self
-                        .sender
-                        .clone()
-                        .send(Msg::CreateChannel(id, Some(leader.clone())))
-                        .await;
-
-                    warn!(
-                        "NotLeaderException found. Start reconnection process on: {:?}",
-                        leader
-                    );
-                } else if let crate::Error::Grpc(ref status) = err {
-                    debug!("Map: {:?}", status.metadata());
+                        let _ = self.sender.clone().send(Msg::CreateChannel(id, None)).await;
+
+                        let limit = self.settings.reconnect_retry_limit;
+                        // Getting the next handle waits for the reconnection.
+                        let suggested = if reconnect_attempts < limit {
+                            RetryDecision::Retry(self.settings.jittered(Duration::default()))
+                        } else {
+                            RetryDecision::GiveUp
+                        };
+                        let decision = self.retry_decision(
+                            retry && replayable(kind, resend),
+                            kind,
+                            RetryReason::NodeUnavailable,
+                            &retries,
+                            suggested,
+                        );
+
+                        self.metrics.not_handled(
+                            kind,
+                            RetryReason::NodeUnavailable,
+                            decision.is_some(),
+                        );
+
+                        if let Some(delay) = decision {
+                            reconnect_attempts += 1;
+
+                            warn!(
+                                "Sending {:?} operation again once reconnected ({}/{})",
+                                kind, reconnect_attempts, limit
+                            );
+
+                            self.retry_after(
+                                kind,
+                                RetryReason::NodeUnavailable,
+                                &mut retries,
+                                delay,
+                            )
+                            .await;
+                            continue;
+                        }
+                    } else if let crate::Error::NotLeaderException(ref leader) = err {
+                        let behavior = self.settings.not_leader_behavior;
+
+                        if behavior == NotLeaderBehavior::Fail {
+                            warn!("NotLeaderException found. Advertised leader: {:?}", leader);
+                            self.metrics
+                                .not_handled(kind, RetryReason::NotLeader, false);
+
+                            return Err(err);
+                        }
+
+                        let _ = self
+                            .sender
+                            .clone()
+                            .send(Msg::NotLeader(id, leader.clone()))
+                            .await;
+
+                        warn!(
+                            "NotLeaderException found. Start reconnection process on: {:?}",
+                            leader
+                        );
+
+                        let suggested = if behavior == NotLeaderBehavior::Retry
+                            && attempts <= self.settings.not_leader_retry_limit
+                        {
+                            RetryDecision::Retry(
+                                self.settings
+                                    .jittered(self.settings.not_leader_backoff(attempts)),
+                            )
+                        } else {
+                            RetryDecision::GiveUp
+                        };
+                        let decision = self.retry_decision(
+                            retry,
+                            kind,
+                            RetryReason::NotLeader,
+                            &retries,
+                            suggested,
+                        );
+
+                        self.metrics
+                            .not_handled(kind, RetryReason::NotLeader, decision.is_some());
+
+                        if let Some(delay) = decision {
+                            warn!(
+                                "Retrying operation on new leader ({}/{})",
+                                attempts, self.settings.not_leader_retry_limit
+                            );
+
+                            attempts += 1;
+                            self.retry_after(kind, RetryReason::NotLeader, &mut retries, delay)
+                                .await;
+                            continue;
+                        }
+                    } else if let crate::Error::WriteTimeout { phase } = err {
+                        let limit = self.settings.write_timeout_retry_limit;
+                        // The server already waited for its own timeout to
+                        // elapse, there's no need to back off.
+                        let suggested = if timeout_attempts < limit {
+                            RetryDecision::Retry(Duration::default())
+                        } else {
+                            RetryDecision::GiveUp
+                        };
+                        let decision = self.retry_decision(
+                            resend == Resend::Idempotent,
+                            kind,
+                            RetryReason::WriteTimeout,
+                            &retries,
+                            suggested,
+                        );
+
+                        self.metrics.not_handled(
+                            kind,
+                            RetryReason::WriteTimeout,
+                            decision.is_some(),
+                        );
+
+                        if let Some(delay) = decision {
+                            timeout_attempts += 1;
+
+                            warn!(
+                                "{:?} operation timed out in the {} phase, sending it again ({}/{})",
+                                kind, phase, timeout_attempts, limit
+                            );
+
+                            self.retry_after(kind, RetryReason::WriteTimeout, &mut retries, delay)
+                                .await;
+                            continue;
+                        }
+                    } else if let crate::Error::Grpc(ref status) = err {
+                        debug!("Map: {:?}", status.metadata());
+                    }
+
+                    if let Some(reason) = retries.last_reason {
+                        warn!(
+                            "{:?} operation failed after {} retries, the last one because of {:?}",
+                            kind, retries.count, reason
+                        );
+                    }
+
+                    if let Some(id) = operation::current() {
+                        debug!("{:?} operation {} failed: {}", kind, id, err);
+                    }
+
+                    return Err(err);
                 }
 
-                Err(err)
+                Ok(a) => {
+                    self.throttle.on_success();
+
+                    return Ok((a, retries));
+                }
             }
+        }
+    }
+
+    /// Delay after which the operation is sent again, `None` if it fails.
+    /// An operation that can't be resent always fails, the retry policy is
+    /// only consulted otherwise.
+    fn retry_decision(
+        &self,
+        resendable: bool,
+        kind: OperationKind,
+        reason: RetryReason,
+        retries: &Retries,
+        suggested: RetryDecision,
+    ) -> Option<Duration> {
+        if !resendable {
+            return None;
+        }
+
+        let attempt = RetryAttempt {
+            attempt: retries.count + 1,
+            kind,
+            reason,
+            suggested,
+        };
+
+        match self.retry_policy.should_retry(&attempt) {
+            RetryDecision::Retry(delay) => Some(delay),
+            RetryDecision::GiveUp => None,
+        }
+    }
+
+    async fn retry_after(
+        &self,
+        kind: OperationKind,
+        reason: RetryReason,
+        retries: &mut Retries,
+        delay: Duration,
+    ) {
+        retries.count += 1;
+        retries.last_reason = Some(reason);
+        self.metrics.retried(kind, reason);
+
+        if let Some(listener) = self.listener.as_ref() {
+            listener.on_retried(kind, operation::current(), reason);
+        }
 
-            Ok(a) => Ok(a),
+        if delay != Duration::default() {
+            self.clock.sleep(delay).await;
         }
     }
 
+    pub(crate) fn settings(&self) -> &ClientSettings {
+        &self.settings
+    }
+
+    pub(crate) fn throttle_state(&self) -> ThrottleState {
+        self.throttle.state()
+    }
+
+    pub(crate) fn operation_stats(&self) -> OperationStats {
+        self.metrics.snapshot()
+    }
+
+    pub(crate) fn metrics(&self) -> Arc<Metrics> {
+        self.metrics.clone()
+    }
+
     pub fn default_credentials(&self) -> Option<Credentials> {
         self.default_credentials.clone()
     }
@@ -998,10 +3870,23 @@ async fn node_selection(
     failed_endpoint: &Option<Endpoint>,
     rng: &mut SmallRng,
     previous_candidates: &mut Option<Vec<Member>>,
+    members: &mut Vec<MemberInfo>,
+    topology: Option<&ClusterTopology>,
 ) -> Option<Endpoint> {
+    if let Some(topology) = topology {
+        *members = topology.members().await?;
+
+        return determine_best_node_excluding(
+            rng,
+            conn_setts.preference,
+            members.as_slice(),
+            failed_endpoint.as_ref(),
+        );
+    }
+
     let candidates = match previous_candidates.take() {
         Some(old_candidates) => {
-            let mut new_candidates = candidates_from_old_gossip(&failed_endpoint, old_candidates);
+            let mut new_candidates = candidates_from_old_gossip(failed_endpoint, old_candidates);
 
             // Use case: when the cluster is only comprised of a single node and that node
             // previously failed. This can only happen if the user used a fixed set of seeds.
@@ -1044,6 +3929,7 @@ async fn node_selection(
                             );
 
                             if let Some(selected_node) = selected_node {
+                                *members = members_info;
                                 return Some(selected_node);
                             }
                         }
@@ -1069,6 +3955,26 @@ async fn node_selection(
     None
 }
 
+/// Node a connection currently has a channel to.
+#[derive(Clone, Debug)]
+pub(crate) struct CurrentNode {
+    pub(crate) endpoint: Endpoint,
+    pub(crate) role: Option<NodeRole>,
+}
+
+fn node_role(members: &[MemberInfo], endpoint: &Endpoint) -> Option<NodeRole> {
+    let member = members
+        .iter()
+        .find(|member| member.http_end_point == *endpoint)?;
+
+    match member.state {
+        VNodeState::Leader => Some(NodeRole::Leader),
+        VNodeState::Follower => Some(NodeRole::Follower),
+        VNodeState::ReadOnlyReplica => Some(NodeRole::ReadOnlyReplica),
+        _ => None,
+    }
+}
+
 struct Candidates {
     nodes: Vec<Member>,
     managers: Vec<Member>,
@@ -1131,6 +4037,32 @@ fn arrange_gossip_candidates(candidates: Vec<Member>) -> Vec<Endpoint> {
     arranged_candidates.endpoints()
 }
 
+/// Same as [`determine_best_node`], avoiding the node that just failed. The
+/// shared topology might predate the failure and still list that node as
+/// alive. It's only picked again if no other node fits.
+fn determine_best_node_excluding(
+    rng: &mut SmallRng,
+    preference: NodePreference,
+    members: &[MemberInfo],
+    failed_endpoint: Option<&Endpoint>,
+) -> Option<Endpoint> {
+    if let Some(failed) = failed_endpoint {
+        let others: Vec<MemberInfo> = members
+            .iter()
+            .filter(|member| member.http_end_point != *failed)
+            .cloned()
+            .collect();
+
+        if let Some(node) = determine_best_node(rng, preference, &others) {
+            return Some(node);
+        }
+    }
+
+    determine_best_node(rng, preference, members)
+}
+
+// `is_multiple_of` would raise the minimum supported Rust version.
+#[allow(clippy::manual_is_multiple_of)]
 fn determine_best_node(
     rng: &mut SmallRng,
     preference: NodePreference,
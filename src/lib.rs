@@ -58,18 +58,41 @@
 //! ```
 //! [EventStoreDB]: https://eventstore.com/
 //! [eventstoredb docs]: https://developers.eventstore.com/server/20.6/server/installation/
+// `Error` carries `tonic::Status` by value in public variants, which makes
+// every `Result` of the crate large. Boxing it would break callers matching on
+// those variants.
+#![allow(clippy::result_large_err)]
 #[macro_use]
 extern crate log;
 
+mod catch_up;
+mod checkpoint_flush;
 mod client;
+mod clock;
 mod commands;
 mod event_store;
 mod gossip;
 mod grpc;
+mod listener;
+mod metrics;
+mod multi_stream;
+mod operation;
 mod options;
 mod private;
 mod projection_client;
+mod read_cache;
+mod repository;
+mod retry_policy;
+mod stream_locks;
+mod subscriptions;
+#[cfg(test)]
+mod test_transport;
+mod throttle;
+mod time_skew;
+mod topology;
 mod types;
+#[cfg(feature = "unstable")]
+pub mod unstable;
 
 pub(crate) mod google {
     pub mod rpc {
@@ -77,10 +100,20 @@ pub(crate) mod google {
     }
 }
 
-pub use client::Client;
-pub use commands::{SubscriptionRead, SubscriptionWrite};
-pub use grpc::{ClientSettings, ClientSettingsParseError};
+pub use catch_up::CatchUpSubscription;
+pub use client::{Client, ClientBuilder};
+pub use clock::{Clock, SystemClock, TestClock};
+pub use commands::{AckHandle, SubscriptionRead, SubscriptionWrite};
+pub use grpc::{ClientSettings, ClientSettingsBuilder, ClientSettingsParseError, SettingsError};
+pub use listener::{OperationListener, OperationOutcome};
+pub use metrics::{NotHandledCounters, OperationCounters, OperationKind, OperationStats};
+pub use multi_stream::{
+    MultiStreamWrite, PartialWriteError, StreamWriteFailure, StreamWriteResult,
+};
+pub use operation::{OperationId, SubmittedOperation};
 pub use options::append_to_stream::*;
+pub use options::catch_up::*;
+pub use options::checkpoint_flush::*;
 pub use options::delete_stream::*;
 pub use options::persistent_subscription::*;
 pub use options::read_all::*;
@@ -89,13 +122,33 @@ pub use options::retry::*;
 pub use options::subscribe_to_all::*;
 pub use options::subscribe_to_stream::*;
 pub use projection_client::*;
+pub use repository::{Aggregate, Loaded, Repository, RepositoryError};
+pub use retry_policy::{RetryAttempt, RetryDecision, RetryPolicy, SettingsRetryPolicy};
+pub use subscriptions::{SubscriptionId, SubscriptionInfo, SubscriptionTarget};
+pub use throttle::ThrottleState;
+pub use time_skew::ServerTimeSkew;
+pub use topology::ClusterTopology;
 pub use types::*;
 
 pub mod prelude {
-    pub use crate::client::Client;
-    pub use crate::commands::{SubscriptionRead, SubscriptionWrite};
-    pub use crate::grpc::{ClientSettings, ClientSettingsParseError};
+    pub use crate::catch_up::CatchUpSubscription;
+    pub use crate::client::{Client, ClientBuilder};
+    pub use crate::clock::{Clock, SystemClock, TestClock};
+    pub use crate::commands::{AckHandle, SubscriptionRead, SubscriptionWrite};
+    pub use crate::grpc::{
+        ClientSettings, ClientSettingsBuilder, ClientSettingsParseError, SettingsError,
+    };
+    pub use crate::listener::{OperationListener, OperationOutcome};
+    pub use crate::metrics::{
+        NotHandledCounters, OperationCounters, OperationKind, OperationStats,
+    };
+    pub use crate::multi_stream::{
+        MultiStreamWrite, PartialWriteError, StreamWriteFailure, StreamWriteResult,
+    };
+    pub use crate::operation::{OperationId, SubmittedOperation};
     pub use crate::options::append_to_stream::*;
+    pub use crate::options::catch_up::*;
+    pub use crate::options::checkpoint_flush::*;
     pub use crate::options::delete_stream::*;
     pub use crate::options::persistent_subscription::*;
     pub use crate::options::read_all::*;
@@ -104,5 +157,11 @@ pub mod prelude {
     pub use crate::options::subscribe_to_all::*;
     pub use crate::options::subscribe_to_stream::*;
     pub use crate::projection_client::*;
+    pub use crate::repository::{Aggregate, Loaded, Repository, RepositoryError};
+    pub use crate::retry_policy::{RetryAttempt, RetryDecision, RetryPolicy, SettingsRetryPolicy};
+    pub use crate::subscriptions::{SubscriptionId, SubscriptionInfo, SubscriptionTarget};
+    pub use crate::throttle::ThrottleState;
+    pub use crate::time_skew::ServerTimeSkew;
+    pub use crate::topology::ClusterTopology;
     pub use crate::types::*;
 }
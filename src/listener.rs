@@ -0,0 +1,63 @@
+//! Callbacks on the lifecycle of operations, see [`OperationListener`].
+//!
+//! A listener sees every operation the client sends, when it's issued, each
+//! time it's sent again and when it completes, along with the identifier of
+//! the operations submitted with [`crate::Client::submit`]. It runs on the
+//! task issuing the operation, so it should return quickly, handing slow
+//! work such as network calls to another task. Clients without a listener
+//! skip the callbacks entirely.
+use crate::metrics::OperationKind;
+use crate::operation::OperationId;
+use crate::types::RetryReason;
+use std::time::Duration;
+
+/// How an operation completed.
+#[derive(Debug, Clone, Copy)]
+pub enum OperationOutcome<'a> {
+    Succeeded,
+    /// The operation failed with the given error.
+    Failed(&'a crate::Error),
+}
+
+impl OperationOutcome<'_> {
+    /// The operation failed because its deadline, or the deadline of the
+    /// server writing its events, was exceeded.
+    pub fn timed_out(&self) -> bool {
+        match self {
+            OperationOutcome::Succeeded => false,
+            OperationOutcome::Failed(crate::Error::Grpc(status)) => {
+                status.code() == tonic::Code::DeadlineExceeded
+            }
+            OperationOutcome::Failed(error) => matches!(
+                error,
+                crate::Error::DeadlineExceeded | crate::Error::WriteTimeout { .. }
+            ),
+        }
+    }
+}
+
+/// Gets notified of the lifecycle of the operations of a client. Every
+/// callback does nothing by default.
+pub trait OperationListener: Send + Sync + 'static {
+    /// The operation is about to be sent for the first time.
+    fn on_issued(&self, kind: OperationKind, id: Option<OperationId>) {
+        let _ = (kind, id);
+    }
+
+    /// The operation is about to be sent again.
+    fn on_retried(&self, kind: OperationKind, id: Option<OperationId>, reason: RetryReason) {
+        let _ = (kind, id, reason);
+    }
+
+    /// The operation completed, `elapsed` after being issued, retries
+    /// included.
+    fn on_completed(
+        &self,
+        kind: OperationKind,
+        id: Option<OperationId>,
+        outcome: OperationOutcome<'_>,
+        elapsed: Duration,
+    ) {
+        let _ = (kind, id, outcome, elapsed);
+    }
+}
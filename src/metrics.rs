@@ -0,0 +1,285 @@
+//! Per-operation counters collected by the client, meant to give a breakdown
+//! of the load by operation type.
+use crate::types::RetryReason;
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+/// Kind of operation sent to the server.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum OperationKind {
+    AppendToStream,
+    ReadStream,
+    ReadAll,
+    DeleteStream,
+    TombstoneStream,
+    SubscribeToStream,
+    SubscribeToAll,
+    CreatePersistentSubscription,
+    UpdatePersistentSubscription,
+    DeletePersistentSubscription,
+    ConnectPersistentSubscription,
+    /// Any projection management operation.
+    Projection,
+    /// Any request sent through the `unstable` module.
+    #[cfg(feature = "unstable")]
+    Raw,
+}
+
+/// Counters of a given kind of operation.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub struct OperationCounters {
+    /// Number of operations issued by the user.
+    pub issued: u64,
+
+    /// Number of operations that succeeded.
+    pub succeeded: u64,
+
+    /// Number of operations that failed, including the ones that timed out.
+    pub failed: u64,
+
+    /// Number of times an operation was re-sent to the server.
+    pub retried: u64,
+
+    /// Number of operations that failed because their deadline was exceeded.
+    pub timed_out: u64,
+
+    /// Reason of the last time an operation was re-sent to the server.
+    pub last_retry_reason: Option<RetryReason>,
+
+    /// Number of events sent to the server, counting an event again each
+    /// time its operation is retried.
+    pub events_sent: u64,
+
+    /// Encoded size in bytes of the events sent to the server.
+    pub bytes_sent: u64,
+
+    /// Number of events received from the server.
+    pub events_received: u64,
+
+    /// Encoded size in bytes of the events received from the server.
+    pub bytes_received: u64,
+
+    /// Times the server declined to handle an operation, by reason.
+    pub not_handled: NotHandledCounters,
+
+    /// Times a subscription was flagged as a slow consumer, see the
+    /// `slowConsumerThreshold` setting.
+    pub slow_consumers: u64,
+}
+
+/// Times the server declined to handle an operation, whether or not it was
+/// sent again.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub struct NotHandledCounters {
+    /// The node wasn't the leader.
+    pub not_leader: u64,
+
+    /// The server was too busy.
+    pub server_busy: u64,
+
+    /// The server timed out writing the events.
+    pub write_timeout: u64,
+
+    /// The node went unavailable.
+    pub node_unavailable: u64,
+
+    /// Number of those times the operation was sent again.
+    pub retried: u64,
+}
+
+impl NotHandledCounters {
+    /// Returns the counter of the given reason.
+    pub fn get(&self, reason: RetryReason) -> u64 {
+        match reason {
+            RetryReason::NotLeader => self.not_leader,
+            RetryReason::ServerBusy => self.server_busy,
+            RetryReason::WriteTimeout => self.write_timeout,
+            RetryReason::NodeUnavailable => self.node_unavailable,
+        }
+    }
+}
+
+/// Snapshot of the operation counters of a client.
+#[derive(Debug, Clone, Default)]
+pub struct OperationStats {
+    counters: HashMap<OperationKind, OperationCounters>,
+}
+
+impl OperationStats {
+    /// Returns the counters of the given kind of operation.
+    pub fn get(&self, kind: OperationKind) -> OperationCounters {
+        self.counters.get(&kind).copied().unwrap_or_default()
+    }
+
+    /// Iterates over the counters of every kind of operation issued so far.
+    pub fn iter(&self) -> impl Iterator<Item = (OperationKind, OperationCounters)> + '_ {
+        self.counters
+            .iter()
+            .map(|(kind, counters)| (*kind, *counters))
+    }
+
+    /// Times the server declined to handle an operation for the given
+    /// reason, across every kind of operation.
+    pub fn not_handled(&self, reason: RetryReason) -> u64 {
+        self.counters
+            .values()
+            .map(|counters| counters.not_handled.get(reason))
+            .sum()
+    }
+}
+
+#[derive(Default)]
+pub(crate) struct Metrics {
+    operations: Mutex<HashMap<OperationKind, OperationCounters>>,
+}
+
+impl Metrics {
+    fn record<F>(&self, kind: OperationKind, update: F)
+    where
+        F: FnOnce(&mut OperationCounters),
+    {
+        update(self.operations.lock().unwrap().entry(kind).or_default());
+    }
+
+    pub(crate) fn issued(&self, kind: OperationKind) {
+        self.record(kind, |c| c.issued += 1);
+    }
+
+    pub(crate) fn succeeded(&self, kind: OperationKind) {
+        self.record(kind, |c| c.succeeded += 1);
+    }
+
+    pub(crate) fn failed(&self, kind: OperationKind, timed_out: bool) {
+        self.record(kind, |c| {
+            c.failed += 1;
+
+            if timed_out {
+                c.timed_out += 1;
+            }
+        });
+    }
+
+    pub(crate) fn slow_consumer(&self, kind: OperationKind) {
+        self.record(kind, |c| c.slow_consumers += 1);
+    }
+
+    pub(crate) fn retried(&self, kind: OperationKind, reason: RetryReason) {
+        self.record(kind, |c| {
+            c.retried += 1;
+            c.last_retry_reason = Some(reason);
+        });
+    }
+
+    /// Records the server declined to handle an operation. Each occurrence
+    /// is also logged with the `eventstore::not_handled` target.
+    pub(crate) fn not_handled(&self, kind: OperationKind, reason: RetryReason, retried: bool) {
+        debug!(
+            target: "eventstore::not_handled",
+            "kind={:?} reason={:?} retried={}",
+            kind,
+            reason,
+            retried
+        );
+
+        self.record(kind, |c| {
+            match reason {
+                RetryReason::NotLeader => c.not_handled.not_leader += 1,
+                RetryReason::ServerBusy => c.not_handled.server_busy += 1,
+                RetryReason::WriteTimeout => c.not_handled.write_timeout += 1,
+                RetryReason::NodeUnavailable => c.not_handled.node_unavailable += 1,
+            }
+
+            if retried {
+                c.not_handled.retried += 1;
+            }
+        });
+    }
+
+    pub(crate) fn sent(&self, kind: OperationKind, events: usize, bytes: usize) {
+        self.record(kind, |c| {
+            c.events_sent += events as u64;
+            c.bytes_sent += bytes as u64;
+        });
+    }
+
+    pub(crate) fn received(&self, kind: OperationKind, bytes: usize) {
+        self.record(kind, |c| {
+            c.events_received += 1;
+            c.bytes_received += bytes as u64;
+        });
+    }
+
+    pub(crate) fn snapshot(&self) -> OperationStats {
+        OperationStats {
+            counters: self.operations.lock().unwrap().clone(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Metrics, OperationKind};
+    use crate::types::RetryReason;
+
+    #[test]
+    fn counters_are_keyed_by_operation_kind() {
+        let metrics = Metrics::default();
+
+        metrics.issued(OperationKind::AppendToStream);
+        metrics.retried(OperationKind::AppendToStream, RetryReason::ServerBusy);
+        metrics.succeeded(OperationKind::AppendToStream);
+        metrics.issued(OperationKind::ReadStream);
+        metrics.failed(OperationKind::ReadStream, true);
+
+        let stats = metrics.snapshot();
+        let append = stats.get(OperationKind::AppendToStream);
+        let read = stats.get(OperationKind::ReadStream);
+
+        assert_eq!((append.issued, append.retried, append.succeeded), (1, 1, 1));
+        assert_eq!((read.issued, read.failed, read.timed_out), (1, 1, 1));
+        assert_eq!(append.last_retry_reason, Some(RetryReason::ServerBusy));
+        assert_eq!(read.last_retry_reason, None);
+        assert_eq!(stats.get(OperationKind::ReadAll), Default::default());
+    }
+
+    #[test]
+    fn not_handled_reasons_are_counted() {
+        let metrics = Metrics::default();
+
+        metrics.not_handled(OperationKind::AppendToStream, RetryReason::NotLeader, true);
+        metrics.not_handled(
+            OperationKind::AppendToStream,
+            RetryReason::ServerBusy,
+            false,
+        );
+        metrics.not_handled(OperationKind::ReadStream, RetryReason::NotLeader, false);
+
+        let stats = metrics.snapshot();
+        let append = stats.get(OperationKind::AppendToStream).not_handled;
+
+        assert_eq!(
+            (append.not_leader, append.server_busy, append.retried),
+            (1, 1, 1)
+        );
+        assert_eq!(stats.not_handled(RetryReason::NotLeader), 2);
+        assert_eq!(stats.not_handled(RetryReason::ServerBusy), 1);
+    }
+
+    #[test]
+    fn wire_sizes_are_accumulated() {
+        let metrics = Metrics::default();
+
+        metrics.sent(OperationKind::AppendToStream, 3, 120);
+        metrics.sent(OperationKind::AppendToStream, 1, 40);
+        metrics.received(OperationKind::ReadStream, 70);
+        metrics.received(OperationKind::ReadStream, 30);
+
+        let stats = metrics.snapshot();
+        let append = stats.get(OperationKind::AppendToStream);
+        let read = stats.get(OperationKind::ReadStream);
+
+        assert_eq!((append.events_sent, append.bytes_sent), (4, 160));
+        assert_eq!((read.events_received, read.bytes_received), (2, 100));
+        assert_eq!((append.events_received, read.events_sent), (0, 0));
+    }
+}
@@ -0,0 +1,130 @@
+//! Appends to several streams issued as one unit, see [`MultiStreamWrite`].
+//!
+//! This is NOT a distributed transaction. The server only guarantees an
+//! append to be atomic within a single stream: the appends are sent one
+//! after the other, and the ones that succeeded stay committed when a later
+//! append fails. On a partial failure, [`PartialWriteError`] tells exactly
+//! which streams were written, so the caller can compensate, with a saga
+//! marker or compensating events for instance, or roll forward by retrying
+//! the remaining appends.
+use crate::options::append_to_stream::AppendToStreamOptions;
+use crate::types::{EventData, WriteResult, WrongExpectedVersion};
+use thiserror::Error;
+
+/// Appends to several streams, issued in the order they were added by
+/// [`crate::Client::append_to_streams`].
+#[derive(Clone, Default)]
+pub struct MultiStreamWrite {
+    pub(crate) writes: Vec<StreamWrite>,
+}
+
+#[derive(Clone)]
+pub(crate) struct StreamWrite {
+    pub(crate) stream: String,
+    pub(crate) options: AppendToStreamOptions,
+    pub(crate) events: Vec<EventData>,
+}
+
+impl MultiStreamWrite {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds an append of the given events to a stream.
+    pub fn append<S, Events>(
+        mut self,
+        stream: S,
+        options: &AppendToStreamOptions,
+        events: Events,
+    ) -> Self
+    where
+        S: AsRef<str>,
+        Events: IntoIterator<Item = EventData>,
+    {
+        self.writes.push(StreamWrite {
+            stream: stream.as_ref().to_string(),
+            options: options.clone(),
+            events: events.into_iter().collect(),
+        });
+
+        self
+    }
+
+    /// Streams to append to, in the order the appends are issued.
+    pub fn streams(&self) -> impl Iterator<Item = &str> + '_ {
+        self.writes.iter().map(|write| write.stream.as_str())
+    }
+}
+
+/// Result of an append to one of the streams of a [`MultiStreamWrite`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StreamWriteResult {
+    pub stream: String,
+    pub result: WriteResult,
+}
+
+/// Why the append to a stream of a [`MultiStreamWrite`] failed.
+#[derive(Error, Debug, Clone)]
+pub enum StreamWriteFailure {
+    #[error("Wrong expected version: {0}")]
+    WrongExpectedVersion(WrongExpectedVersion),
+    #[error(transparent)]
+    Error(#[from] crate::Error),
+}
+
+/// Returned when an append of a [`MultiStreamWrite`] failed. The appends
+/// that succeeded before aren't rolled back.
+#[derive(Error, Debug, Clone)]
+#[error(
+    "Append to stream {failed} failed after {} stream(s) were written: {reason}",
+    succeeded.len()
+)]
+pub struct PartialWriteError {
+    /// Appends that were committed, in the order they were issued.
+    pub succeeded: Vec<StreamWriteResult>,
+
+    /// Stream whose append failed.
+    pub failed: String,
+
+    /// Why the append failed.
+    #[source]
+    pub reason: StreamWriteFailure,
+
+    /// Streams whose append wasn't issued.
+    pub not_attempted: Vec<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{MultiStreamWrite, PartialWriteError, StreamWriteFailure};
+    use crate::types::{CurrentRevision, ExpectedRevision, WrongExpectedVersion};
+
+    #[test]
+    fn appends_keep_their_order() {
+        let write = MultiStreamWrite::new()
+            .append("orders", &Default::default(), Vec::new())
+            .append("invoices", &Default::default(), Vec::new());
+
+        assert_eq!(
+            write.streams().collect::<Vec<_>>(),
+            vec!["orders", "invoices"]
+        );
+    }
+
+    #[test]
+    fn partial_failure_names_the_failed_stream() {
+        let error = PartialWriteError {
+            succeeded: Vec::new(),
+            failed: "invoices".to_string(),
+            reason: StreamWriteFailure::WrongExpectedVersion(WrongExpectedVersion {
+                current: CurrentRevision::NoStream,
+                expected: ExpectedRevision::StreamExists,
+            }),
+            not_attempted: vec!["shipments".to_string()],
+        };
+
+        assert!(error
+            .to_string()
+            .starts_with("Append to stream invoices failed after 0 stream(s) were written"));
+    }
+}
@@ -0,0 +1,123 @@
+//! Identification of the operations submitted with [`crate::Client::submit`].
+//!
+//! The identifier of an operation is known before it's sent, so it can be
+//! logged upfront. It's generated unless the caller provides its own, see
+//! [`crate::Client::submit_with_id`]. The client mentions it in its own log lines and sends it
+//! along every request of the operation, in the `correlation-id` header.
+use futures::future::BoxFuture;
+use std::fmt;
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use uuid::Uuid;
+
+/// Header the operation identifier is sent in.
+pub(crate) const CORRELATION_ID_HEADER: &str = "correlation-id";
+
+tokio::task_local! {
+    static CURRENT_OPERATION: OperationId;
+}
+
+/// Identifies an operation submitted with [`crate::Client::submit`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct OperationId(Uuid);
+
+impl OperationId {
+    pub(crate) fn new() -> Self {
+        OperationId(Uuid::new_v4())
+    }
+
+    pub fn as_uuid(&self) -> Uuid {
+        self.0
+    }
+}
+
+impl From<Uuid> for OperationId {
+    fn from(id: Uuid) -> Self {
+        OperationId(id)
+    }
+}
+
+impl fmt::Display for OperationId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+/// Identifier of the operation the current task is running, if it was
+/// submitted with [`crate::Client::submit`].
+pub(crate) fn current() -> Option<OperationId> {
+    CURRENT_OPERATION.try_with(|id| *id).ok()
+}
+
+/// Operation whose identifier is known before it completes. Awaiting it
+/// returns the outcome of the operation.
+pub struct SubmittedOperation<A> {
+    id: OperationId,
+    future: BoxFuture<'static, crate::Result<A>>,
+}
+
+impl<A> SubmittedOperation<A> {
+    pub(crate) fn new<Fut>(future: Fut) -> Self
+    where
+        Fut: Future<Output = crate::Result<A>> + Send + 'static,
+    {
+        SubmittedOperation::with_id(OperationId::new(), future)
+    }
+
+    pub(crate) fn with_id<Fut>(id: OperationId, future: Fut) -> Self
+    where
+        Fut: Future<Output = crate::Result<A>> + Send + 'static,
+    {
+        SubmittedOperation {
+            id,
+            future: Box::pin(CURRENT_OPERATION.scope(id, future)),
+        }
+    }
+
+    pub fn id(&self) -> OperationId {
+        self.id
+    }
+}
+
+impl<A> Future for SubmittedOperation<A> {
+    type Output = crate::Result<A>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        self.future.as_mut().poll(cx)
+    }
+}
+
+impl<A> fmt::Debug for SubmittedOperation<A> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SubmittedOperation")
+            .field("id", &self.id)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{current, OperationId, SubmittedOperation};
+    use uuid::Uuid;
+
+    #[tokio::test]
+    async fn operation_id_is_visible_while_running() {
+        assert!(current().is_none());
+
+        let operation = SubmittedOperation::new(async { Ok(current()) });
+        let id = operation.id();
+
+        assert_eq!(operation.await.unwrap(), Some(id));
+        assert!(current().is_none());
+    }
+
+    #[tokio::test]
+    async fn provided_operation_id_is_kept() {
+        let uuid = Uuid::new_v4();
+        let operation = SubmittedOperation::with_id(uuid.into(), async { Ok(current()) });
+
+        assert_eq!(operation.id().as_uuid(), uuid);
+        assert_eq!(operation.await.unwrap(), Some(OperationId::from(uuid)));
+    }
+}
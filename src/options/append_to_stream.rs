@@ -11,6 +11,7 @@ use futures::Stream;
 pub struct AppendToStreamOptions {
     pub(crate) version: ExpectedStreamRevision,
     pub(crate) credentials: Option<Credentials>,
+    pub(crate) verify_no_prior_incarnation: bool,
 }
 
 impl Default for AppendToStreamOptions {
@@ -18,6 +19,7 @@ impl Default for AppendToStreamOptions {
         Self {
             version: ExpectedStreamRevision::Any(Empty {}),
             credentials: None,
+            verify_no_prior_incarnation: false,
         }
     }
 }
@@ -43,12 +45,50 @@ impl AppendToStreamOptions {
 
         Self { version, ..self }
     }
+
+    /// When the expected revision is `ExpectedRevision::NoStream`, checks the
+    /// stream metadata before appending. A soft-deleted stream can be
+    /// recreated with `NoStream`, its new events following the deleted ones.
+    /// If such a prior incarnation exists, the append fails with
+    /// [`crate::Error::PriorStreamIncarnation`] without writing anything.
+    ///
+    /// The check costs an extra read and isn't atomic with the append.
+    pub fn verify_no_prior_incarnation(self) -> Self {
+        Self {
+            verify_no_prior_incarnation: true,
+            ..self
+        }
+    }
 }
 
+/// Events sent to the server as they are produced by a stream, instead of
+/// being collected before the append starts.
+///
+/// Appending a `Vec<EventData>` keeps every event in memory, in their encoded
+/// form, until the server acknowledges the write, so the append can be sent
+/// again if needed. With `Streaming`, only the event being sent is held in
+/// memory, on top of what the HTTP/2 transport buffers, which suits imports of
+/// large events. The downside is that such an append is never retried, even
+/// when the server is too busy or the `notLeaderBehavior` setting is `retry`.
 pub struct Streaming<S>(S);
 
+impl<S> Streaming<S>
+where
+    S: Stream<Item = EventData> + Send + Sync,
+{
+    pub fn new(events: S) -> Self {
+        Streaming(events)
+    }
+}
+
 pub trait ToEvents: Sealed {
     type Events: Stream<Item = EventData> + Send + Sync;
+
+    /// If the events are sent as they are produced, without being collected
+    /// first.
+    #[doc(hidden)]
+    const STREAMED: bool = false;
+
     fn into_events(self) -> Self::Events;
 }
 
@@ -74,6 +114,8 @@ where
 {
     type Events = S;
 
+    const STREAMED: bool = true;
+
     fn into_events(self) -> Self::Events {
         self.0
     }
@@ -0,0 +1,37 @@
+/// Options of a catch-up subscription, see [`crate::Client::catch_up_stream`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CatchUpOptions {
+    pub(crate) page_size: usize,
+    pub(crate) live_buffer_size: usize,
+}
+
+impl Default for CatchUpOptions {
+    fn default() -> Self {
+        Self {
+            page_size: 500,
+            live_buffer_size: 10_000,
+        }
+    }
+}
+
+impl CatchUpOptions {
+    /// Number of historical events read at once. The next page is only read
+    /// once the current one is consumed. Default: `500`.
+    pub fn page_size(self, page_size: usize) -> Self {
+        Self {
+            page_size: page_size.max(1),
+            ..self
+        }
+    }
+
+    /// Maximum number of live events kept aside while historical events are
+    /// replayed. The subscription fails with
+    /// [`crate::Error::CatchUpBufferOverflow`] when exceeded. Default:
+    /// `10_000`.
+    pub fn live_buffer_size(self, live_buffer_size: usize) -> Self {
+        Self {
+            live_buffer_size,
+            ..self
+        }
+    }
+}
@@ -0,0 +1,38 @@
+use std::time::Duration;
+
+/// Options of the checkpoint debouncing, see
+/// [`crate::Client::debounce_checkpoints`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CheckpointFlushOptions {
+    pub(crate) flush_interval: Duration,
+    pub(crate) max_pending: usize,
+}
+
+impl Default for CheckpointFlushOptions {
+    fn default() -> Self {
+        Self {
+            flush_interval: Duration::from_secs(1),
+            max_pending: 100,
+        }
+    }
+}
+
+impl CheckpointFlushOptions {
+    /// Longest period a checkpoint waits before being persisted.
+    /// Default: `1s`.
+    pub fn checkpoint_flush_interval(self, flush_interval: Duration) -> Self {
+        Self {
+            flush_interval,
+            ..self
+        }
+    }
+
+    /// Number of positions after which the latest one is persisted, without
+    /// waiting for the flush interval. Default: `100`.
+    pub fn max_pending(self, max_pending: usize) -> Self {
+        Self {
+            max_pending: max_pending.max(1),
+            ..self
+        }
+    }
+}
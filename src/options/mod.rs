@@ -1,4 +1,6 @@
 pub mod append_to_stream;
+pub mod catch_up;
+pub mod checkpoint_flush;
 pub mod delete_stream;
 pub mod persistent_subscription;
 pub mod projections;
@@ -1,4 +1,4 @@
-use crate::{Credentials, PersistentSubscriptionSettings, StreamPosition};
+use crate::{Credentials, PersistentSubscriptionSettings, StreamPosition, UnsettledEventPolicy};
 
 #[derive(Clone)]
 pub struct PersistentSubscriptionOptions {
@@ -37,17 +37,11 @@ impl PersistentSubscriptionOptions {
     }
 }
 
-#[derive(Clone)]
+#[derive(Clone, Default)]
 pub struct DeletePersistentSubscriptionOptions {
     pub(crate) credentials: Option<Credentials>,
 }
 
-impl Default for DeletePersistentSubscriptionOptions {
-    fn default() -> Self {
-        Self { credentials: None }
-    }
-}
-
 impl DeletePersistentSubscriptionOptions {
     /// Performs the command with the given credentials.
     pub fn authenticated(self, value: Credentials) -> Self {
@@ -61,6 +55,7 @@ impl DeletePersistentSubscriptionOptions {
 pub struct ConnectToPersistentSubscription {
     pub(crate) credentials: Option<Credentials>,
     pub(crate) batch_size: usize,
+    pub(crate) unsettled_event_policy: UnsettledEventPolicy,
 }
 
 impl Default for ConnectToPersistentSubscription {
@@ -68,6 +63,7 @@ impl Default for ConnectToPersistentSubscription {
         Self {
             credentials: None,
             batch_size: 10,
+            unsettled_event_policy: Default::default(),
         }
     }
 }
@@ -85,4 +81,14 @@ impl ConnectToPersistentSubscription {
     pub fn batch_size(self, batch_size: usize) -> Self {
         Self { batch_size, ..self }
     }
+
+    /// What happens to an event delivered by
+    /// `Client::connect_persistent_subscription_stream` when its handle is
+    /// dropped without being acknowledged.
+    pub fn unsettled_event_policy(self, unsettled_event_policy: UnsettledEventPolicy) -> Self {
+        Self {
+            unsettled_event_policy,
+            ..self
+        }
+    }
 }
@@ -1,4 +1,7 @@
-use crate::{Credentials, Position, ReadDirection, StreamPosition};
+use crate::{
+    Checkpoint, Credentials, EventExclusion, Position, ReadDirection, StreamPosition,
+    SubscriptionFilter,
+};
 
 #[derive(Clone)]
 pub struct ReadAllOptions {
@@ -6,6 +9,8 @@ pub struct ReadAllOptions {
     pub(crate) direction: ReadDirection,
     pub(crate) position: StreamPosition<Position>,
     pub(crate) resolve_link_tos: bool,
+    pub(crate) exclusion: EventExclusion,
+    pub(crate) filter: Option<SubscriptionFilter>,
 }
 
 impl Default for ReadAllOptions {
@@ -15,6 +20,8 @@ impl Default for ReadAllOptions {
             direction: ReadDirection::Forward,
             position: StreamPosition::Start,
             resolve_link_tos: false,
+            exclusion: EventExclusion::default(),
+            filter: None,
         }
     }
 }
@@ -64,6 +71,11 @@ impl ReadAllOptions {
         }
     }
 
+    /// Starts the read at a previously stored checkpoint.
+    pub fn checkpoint(self, checkpoint: &Checkpoint) -> Self {
+        self.position(StreamPosition::Position(checkpoint.position))
+    }
+
     /// When using projections, you can have links placed into another stream.
     /// If you set `true`, the server will resolve those links and will return
     /// the event that the link points to. Default: [NoResolution](../types/enum.LinkTos.html).
@@ -73,4 +85,34 @@ impl ReadAllOptions {
             ..self
         }
     }
+
+    /// Filters events or streams server-side. The number of events to read
+    /// counts the matching events only. With a [`SubscriptionFilter::max`]
+    /// search window, the server reports the position it reached every time
+    /// it searched that many events, see [`crate::Client::read_all_filtered`].
+    pub fn filter(self, filter: SubscriptionFilter) -> Self {
+        Self {
+            filter: Some(filter),
+            ..self
+        }
+    }
+
+    /// Skips system events, those whose type or stream starts with `$`,
+    /// metadata events aside. The filtering happens client-side.
+    /// Skipped events still count toward the number of events to read.
+    /// [`crate::Client::read_all_filtered`] reports the position of each
+    /// skipped event as a `ReadAllEvent::Checkpoint`, so a paged read
+    /// resumes after them.
+    pub fn exclude_system_events(mut self) -> Self {
+        self.exclusion.system = true;
+        self
+    }
+
+    /// Skips stream metadata events. The filtering happens client-side.
+    /// See [`ReadAllOptions::exclude_system_events`] for the positions of the
+    /// skipped events.
+    pub fn exclude_metadata(mut self) -> Self {
+        self.exclusion.metadata = true;
+        self
+    }
 }
@@ -1,5 +1,7 @@
 use crate::options::retry::RetryOptions;
-use crate::{Credentials, Position, StreamPosition, SubscriptionFilter};
+use crate::{
+    Checkpoint, Credentials, EventExclusion, Position, StreamPosition, SubscriptionFilter,
+};
 
 #[derive(Clone)]
 pub struct SubscribeToAllOptions {
@@ -8,6 +10,9 @@ pub struct SubscribeToAllOptions {
     pub(crate) resolve_link_tos: bool,
     pub(crate) filter: Option<SubscriptionFilter>,
     pub(crate) retry: Option<RetryOptions>,
+    pub(crate) raw_delivery: bool,
+    pub(crate) notify_caught_up: bool,
+    pub(crate) exclusion: EventExclusion,
 }
 
 impl Default for SubscribeToAllOptions {
@@ -18,6 +23,9 @@ impl Default for SubscribeToAllOptions {
             position: StreamPosition::Start,
             resolve_link_tos: false,
             retry: None,
+            raw_delivery: false,
+            notify_caught_up: false,
+            exclusion: EventExclusion::default(),
         }
     }
 }
@@ -37,6 +45,11 @@ impl SubscribeToAllOptions {
         Self { position, ..self }
     }
 
+    /// Resumes the subscription from a previously stored checkpoint.
+    pub fn checkpoint(self, checkpoint: &Checkpoint) -> Self {
+        self.position(StreamPosition::Position(checkpoint.position))
+    }
+
     /// Filters events or streams based upon a predicate.
     pub fn filter(self, filter: SubscriptionFilter) -> Self {
         Self {
@@ -63,4 +76,42 @@ impl SubscribeToAllOptions {
             ..self
         }
     }
+
+    /// Delivers events as `SubEvent::RawEventAppeared` instead of
+    /// `SubEvent::EventAppeared`, so each event carries the transport
+    /// information it was received with. Useful when debugging or routing
+    /// events based on the connection they came from.
+    pub fn raw_delivery(self) -> Self {
+        Self {
+            raw_delivery: true,
+            ..self
+        }
+    }
+
+    /// Emits `SubEvent::CaughtUp` once the subscription has delivered every
+    /// event that existed when it started, and switches to live events. The
+    /// client reads the last event of `$all` before subscribing to know where
+    /// the catch-up phase ends.
+    pub fn notify_caught_up(self) -> Self {
+        Self {
+            notify_caught_up: true,
+            ..self
+        }
+    }
+
+    /// Skips system events, those whose type or stream starts with `$`,
+    /// metadata events aside. The filtering happens client-side.
+    /// A `SubEvent::Checkpoint` carrying the position of each skipped event
+    /// is emitted instead, so progress tracking keeps moving forward.
+    pub fn exclude_system_events(mut self) -> Self {
+        self.exclusion.system = true;
+        self
+    }
+
+    /// Skips stream metadata events. The filtering happens client-side.
+    /// A `SubEvent::Checkpoint` is emitted instead of each skipped event.
+    pub fn exclude_metadata(mut self) -> Self {
+        self.exclusion.metadata = true;
+        self
+    }
 }
@@ -1,5 +1,5 @@
 use crate::options::retry::RetryOptions;
-use crate::{Credentials, StreamPosition};
+use crate::{Checkpoint, Credentials, EventExclusion, StreamPosition};
 
 #[derive(Clone)]
 pub struct SubscribeToStreamOptions {
@@ -7,6 +7,9 @@ pub struct SubscribeToStreamOptions {
     pub(crate) position: StreamPosition<u64>,
     pub(crate) resolve_link_tos: bool,
     pub(crate) retry: Option<RetryOptions>,
+    pub(crate) raw_delivery: bool,
+    pub(crate) notify_caught_up: bool,
+    pub(crate) exclusion: EventExclusion,
 }
 
 impl Default for SubscribeToStreamOptions {
@@ -16,6 +19,9 @@ impl Default for SubscribeToStreamOptions {
             position: StreamPosition::Start,
             resolve_link_tos: false,
             retry: None,
+            raw_delivery: false,
+            notify_caught_up: false,
+            exclusion: EventExclusion::default(),
         }
     }
 }
@@ -39,6 +45,16 @@ impl SubscribeToStreamOptions {
         Self { position, ..self }
     }
 
+    /// Resumes the subscription from a previously stored checkpoint. The
+    /// checkpoint must hold a stream revision, otherwise the subscription
+    /// starts from the beginning of the stream.
+    pub fn checkpoint(self, checkpoint: &Checkpoint) -> Self {
+        match checkpoint.revision {
+            Some(revision) => self.position(StreamPosition::Position(revision)),
+            None => self.position(StreamPosition::Start),
+        }
+    }
+
     /// When using projections, you can have links placed into another stream.
     /// If you set `true`, the server will resolve those links and will return
     /// the event that the link points to. Default: [NoResolution](../types/enum.LinkTos.html).
@@ -57,4 +73,42 @@ impl SubscribeToStreamOptions {
             ..self
         }
     }
+
+    /// Delivers events as `SubEvent::RawEventAppeared` instead of
+    /// `SubEvent::EventAppeared`, so each event carries the transport
+    /// information it was received with. Useful when debugging or routing
+    /// events based on the connection they came from.
+    pub fn raw_delivery(self) -> Self {
+        Self {
+            raw_delivery: true,
+            ..self
+        }
+    }
+
+    /// Emits `SubEvent::CaughtUp` once the subscription has delivered every
+    /// event that existed when it started, and switches to live events. The
+    /// client reads the last event of the stream before subscribing to know where
+    /// the catch-up phase ends.
+    pub fn notify_caught_up(self) -> Self {
+        Self {
+            notify_caught_up: true,
+            ..self
+        }
+    }
+
+    /// Skips system events, those whose type or stream starts with `$`,
+    /// metadata events aside, which is useful with resolved links. The
+    /// filtering happens client-side. A `SubEvent::Checkpoint` carrying the
+    /// position of each skipped event is emitted instead.
+    pub fn exclude_system_events(mut self) -> Self {
+        self.exclusion.system = true;
+        self
+    }
+
+    /// Skips stream metadata events. The filtering happens client-side.
+    /// A `SubEvent::Checkpoint` is emitted instead of each skipped event.
+    pub fn exclude_metadata(mut self) -> Self {
+        self.exclusion.metadata = true;
+        self
+    }
 }
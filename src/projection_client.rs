@@ -1,11 +1,12 @@
 use crate::event_store::client::projections;
 use crate::event_store::client::shared::Empty;
 use crate::grpc::{ClientSettings, GrpcClient};
+use crate::metrics::OperationKind;
 use crate::options::projections::{
     CreateProjectionOptions, DeleteProjectionOptions, GetResultProjectionOptions,
     GetStateProjectionOptions, UpdateProjectionOptions,
 };
-use crate::Credentials;
+use crate::{Credentials, ProjectionError, SystemClock};
 use futures::stream::BoxStream;
 use serde::de::DeserializeOwned;
 
@@ -43,16 +44,23 @@ pub struct ProjectionStatus {
 #[derive(Clone)]
 pub struct ProjectionClient {
     client: GrpcClient,
+    #[allow(dead_code)]
     settings: ClientSettings,
 }
 
 impl ProjectionClient {
     pub async fn new(settings: ClientSettings) -> Result<Self, Box<dyn std::error::Error>> {
-        let client = GrpcClient::create(settings.clone()).await?;
+        let client =
+            GrpcClient::create(settings.clone(), None, std::sync::Arc::new(SystemClock)).await?;
 
         Ok(ProjectionClient { client, settings })
     }
 
+    /// Manages projections through the connection of an existing client.
+    pub(crate) fn from_parts(client: GrpcClient, settings: ClientSettings) -> Self {
+        ProjectionClient { client, settings }
+    }
+
     pub async fn create<Name>(
         &self,
         name: Name,
@@ -74,7 +82,8 @@ impl ProjectionClient {
                 )),
             },
         )
-        .await?;
+        .await
+        .map_err(|e| ProjectionError::from_error(name.as_ref(), e))?;
 
         // TODO - create projection RPC call needs to be fixed upstream where the emit options
         // will be added to the API. Right now, do an extra RPC call to implement it.
@@ -96,17 +105,17 @@ impl ProjectionClient {
             options: Some(options),
         };
 
-        let mut req = tonic::Request::new(req);
-
-        crate::commands::configure_auth_req(&mut req, credentials.cloned());
-
         self.client
-            .execute(|handle| async move {
-                let mut client =
-                    projections::projections_client::ProjectionsClient::new(handle.channel);
-                let _ = client.create(req).await?;
+            .execute(OperationKind::Projection, |handle| {
+                let req = crate::commands::new_request(req.clone(), credentials.cloned());
 
-                Ok(())
+                async move {
+                    let mut client =
+                        projections::projections_client::ProjectionsClient::new(handle.channel);
+                    let _ = client.create(req).await?;
+
+                    Ok(())
+                }
             })
             .await
     }
@@ -137,20 +146,25 @@ impl ProjectionClient {
             options: Some(req_options),
         };
 
-        let mut req = tonic::Request::new(req);
-
-        crate::commands::configure_auth_req(&mut req, options.credentials.as_ref().cloned());
-
         self.client
-            .execute(|handle| async move {
-                let mut client =
-                    projections::projections_client::ProjectionsClient::new(handle.channel.clone());
+            .execute(OperationKind::Projection, |handle| {
+                let req = crate::commands::new_request(
+                    req.clone(),
+                    options.credentials.as_ref().cloned(),
+                );
+
+                async move {
+                    let mut client = projections::projections_client::ProjectionsClient::new(
+                        handle.channel.clone(),
+                    );
 
-                let _ = client.update(req).await?;
+                    let _ = client.update(req).await?;
 
-                Ok(())
+                    Ok(())
+                }
             })
             .await
+            .map_err(|e| ProjectionError::from_error(name.as_ref(), e))
     }
 
     pub async fn delete<Name>(
@@ -172,20 +186,25 @@ impl ProjectionClient {
             options: Some(req_options),
         };
 
-        let mut req = tonic::Request::new(req);
-
-        crate::commands::configure_auth_req(&mut req, options.credentials.as_ref().cloned());
-
         self.client
-            .execute(|handle| async move {
-                let mut client =
-                    projections::projections_client::ProjectionsClient::new(handle.channel.clone());
+            .execute(OperationKind::Projection, |handle| {
+                let req = crate::commands::new_request(
+                    req.clone(),
+                    options.credentials.as_ref().cloned(),
+                );
+
+                async move {
+                    let mut client = projections::projections_client::ProjectionsClient::new(
+                        handle.channel.clone(),
+                    );
 
-                let _ = client.delete(req).await?;
+                    let _ = client.delete(req).await?;
 
-                Ok(())
+                    Ok(())
+                }
             })
             .await
+            .map_err(|e| ProjectionError::from_error(name.as_ref(), e))
     }
 
     pub async fn get_status<Name>(
@@ -197,10 +216,17 @@ impl ProjectionClient {
         Name: AsRef<str>,
     {
         use futures::TryStreamExt;
-        self.statistics(StatsFor::Name(name.as_ref().to_string()), credentials)
-            .await?
-            .try_next()
+
+        let status = async {
+            self.statistics(StatsFor::Name(name.as_ref().to_string()), credentials)
+                .await?
+                .try_next()
+                .await
+        };
+
+        status
             .await
+            .map_err(|e| ProjectionError::from_error(name.as_ref(), e))
     }
 
     pub async fn list(
@@ -231,66 +257,66 @@ impl ProjectionClient {
             options: Some(options),
         };
 
-        let mut req = tonic::Request::new(req);
-
-        crate::commands::configure_auth_req(&mut req, credentials.cloned());
-
         self.client
-            .execute(|handle| async move {
-                let mut client =
-                    projections::projections_client::ProjectionsClient::new(handle.channel.clone());
+            .execute(OperationKind::Projection, |handle| {
+                let req = crate::commands::new_request(req.clone(), credentials.cloned());
 
-                let mut stream = client.statistics(req).await?.into_inner();
+                async move {
+                    let mut client =
+                        projections::projections_client::ProjectionsClient::new(handle.channel.clone());
 
-                let stream = async_stream::stream! {
-                    loop {
-                        match stream.try_next().await {
-                            Err(e) => {
-                                let e = crate::Error::from_grpc(e);
+                    let mut stream = client.statistics(req).await?.into_inner();
 
-                                handle.report_error(e.clone()).await;
-                                yield Err(e);
-                                break;
-                            }
+                    let stream = async_stream::stream! {
+                        loop {
+                            match stream.try_next().await {
+                                Err(e) => {
+                                    let e = crate::Error::from_grpc(e);
 
-                            Ok(resp) => {
-                                if let Some(resp) = resp {
-                                    let details = resp.details.expect("to be defined");
-                                    let details = ProjectionStatus {
-                                        core_processing_time: details.core_processing_time,
-                                        version: details.version,
-                                        epoch: details.epoch,
-                                        effective_name: details.effective_name,
-                                        writes_in_progress: details.writes_in_progress,
-                                        reads_in_progress: details.reads_in_progress,
-                                        partitions_cached: details.partitions_cached,
-                                        status: details.status,
-                                        state_reason: details.state_reason,
-                                        name: details.name,
-                                        mode: details.mode,
-                                        position: details.position,
-                                        progress: details.progress,
-                                        last_checkpoint: details.last_checkpoint,
-                                        events_processed_after_restart: details.events_processed_after_restart,
-                                        checkpoint_status: details.checkpoint_status,
-                                        buffered_events: details.buffered_events,
-                                        write_pending_events_after_checkpoint: details.write_pending_events_after_checkpoint,
-                                        write_pending_events_before_checkpoint: details.write_pending_events_before_checkpoint,
-                                    };
-
-                                    yield Ok(details);
-                                    continue;
+                                    handle.report_error(e.clone()).await;
+                                    yield Err(e);
+                                    break;
                                 }
 
-                                break;
+                                Ok(resp) => {
+                                    if let Some(resp) = resp {
+                                        let details = resp.details.expect("to be defined");
+                                        let details = ProjectionStatus {
+                                            core_processing_time: details.core_processing_time,
+                                            version: details.version,
+                                            epoch: details.epoch,
+                                            effective_name: details.effective_name,
+                                            writes_in_progress: details.writes_in_progress,
+                                            reads_in_progress: details.reads_in_progress,
+                                            partitions_cached: details.partitions_cached,
+                                            status: details.status,
+                                            state_reason: details.state_reason,
+                                            name: details.name,
+                                            mode: details.mode,
+                                            position: details.position,
+                                            progress: details.progress,
+                                            last_checkpoint: details.last_checkpoint,
+                                            events_processed_after_restart: details.events_processed_after_restart,
+                                            checkpoint_status: details.checkpoint_status,
+                                            buffered_events: details.buffered_events,
+                                            write_pending_events_after_checkpoint: details.write_pending_events_after_checkpoint,
+                                            write_pending_events_before_checkpoint: details.write_pending_events_before_checkpoint,
+                                        };
+
+                                        yield Ok(details);
+                                        continue;
+                                    }
+
+                                    break;
+                                }
                             }
                         }
-                    }
-                };
+                    };
 
-                let stream: BoxStream<crate::Result<ProjectionStatus>> = Box::pin(stream);
+                    let stream: BoxStream<crate::Result<ProjectionStatus>> = Box::pin(stream);
 
-                Ok(stream)
+                    Ok(stream)
+                }
             })
             .await
     }
@@ -311,20 +337,35 @@ impl ProjectionClient {
             options: Some(options),
         };
 
-        let mut req = tonic::Request::new(req);
-
-        crate::commands::configure_auth_req(&mut req, credentials.cloned());
-
         self.client
-            .execute(|handle| async move {
-                let mut client =
-                    projections::projections_client::ProjectionsClient::new(handle.channel.clone());
+            .execute(OperationKind::Projection, |handle| {
+                let req = crate::commands::new_request(req.clone(), credentials.cloned());
+
+                async move {
+                    let mut client = projections::projections_client::ProjectionsClient::new(
+                        handle.channel.clone(),
+                    );
 
-                let _ = client.enable(req).await?;
+                    let _ = client.enable(req).await?;
 
-                Ok(())
+                    Ok(())
+                }
             })
             .await
+            .map_err(|e| ProjectionError::from_error(name.as_ref(), e))?;
+
+        // Enabling a faulted projection succeeds, its status tells if it
+        // faulted again.
+        match self.get_status(name.as_ref(), credentials).await? {
+            Some(status) if status.status.contains("Faulted") => {
+                Err(crate::Error::Projection(ProjectionError::Faulted {
+                    name: name.as_ref().to_string(),
+                    reason: status.state_reason,
+                }))
+            }
+
+            _ => Ok(()),
+        }
     }
 
     pub async fn reset<Name>(
@@ -344,20 +385,22 @@ impl ProjectionClient {
             options: Some(req_options),
         };
 
-        let mut req = tonic::Request::new(req);
-
-        crate::commands::configure_auth_req(&mut req, credentials.cloned());
-
         self.client
-            .execute(|handle| async move {
-                let mut client =
-                    projections::projections_client::ProjectionsClient::new(handle.channel.clone());
+            .execute(OperationKind::Projection, |handle| {
+                let req = crate::commands::new_request(req.clone(), credentials.cloned());
+
+                async move {
+                    let mut client = projections::projections_client::ProjectionsClient::new(
+                        handle.channel.clone(),
+                    );
 
-                let _ = client.reset(req).await?;
+                    let _ = client.reset(req).await?;
 
-                Ok(())
+                    Ok(())
+                }
             })
             .await
+            .map_err(|e| ProjectionError::from_error(name.as_ref(), e))
     }
 
     pub async fn disable<Name>(
@@ -402,20 +445,22 @@ impl ProjectionClient {
             options: Some(options),
         };
 
-        let mut req = tonic::Request::new(req);
-
-        crate::commands::configure_auth_req(&mut req, credentials.cloned());
-
         self.client
-            .execute(|handle| async move {
-                let mut client =
-                    projections::projections_client::ProjectionsClient::new(handle.channel.clone());
+            .execute(OperationKind::Projection, |handle| {
+                let req = crate::commands::new_request(req.clone(), credentials.cloned());
 
-                let _ = client.disable(req).await?;
+                async move {
+                    let mut client = projections::projections_client::ProjectionsClient::new(
+                        handle.channel.clone(),
+                    );
 
-                Ok(())
+                    let _ = client.disable(req).await?;
+
+                    Ok(())
+                }
             })
             .await
+            .map_err(|e| ProjectionError::from_error(name.as_ref(), e))
     }
 
     pub async fn get_state<Name, A>(
@@ -436,24 +481,29 @@ impl ProjectionClient {
             options: Some(req_options),
         };
 
-        let mut req = tonic::Request::new(req);
-
-        crate::commands::configure_auth_req(&mut req, options.credentials.as_ref().cloned());
-
         self.client
-            .execute(|handle| async move {
-                let mut client =
-                    projections::projections_client::ProjectionsClient::new(handle.channel.clone());
-
-                let resp = client.state(req).await?.into_inner();
-                let value = resp
-                    .state
-                    .map(parse_value)
-                    .unwrap_or(serde_json::Value::Null);
-
-                Ok(serde_json::from_value(value))
+            .execute(OperationKind::Projection, |handle| {
+                let req = crate::commands::new_request(
+                    req.clone(),
+                    options.credentials.as_ref().cloned(),
+                );
+
+                async move {
+                    let mut client = projections::projections_client::ProjectionsClient::new(
+                        handle.channel.clone(),
+                    );
+
+                    let resp = client.state(req).await?.into_inner();
+                    let value = resp
+                        .state
+                        .map(parse_value)
+                        .unwrap_or(serde_json::Value::Null);
+
+                    Ok(serde_json::from_value(value))
+                }
             })
             .await
+            .map_err(|e| ProjectionError::from_error(name.as_ref(), e))
     }
 
     pub async fn get_result<Name, A>(
@@ -474,38 +524,45 @@ impl ProjectionClient {
             options: Some(req_options),
         };
 
-        let mut req = tonic::Request::new(req);
-
-        crate::commands::configure_auth_req(&mut req, options.credentials.as_ref().cloned());
-
         self.client
-            .execute(|handle| async move {
-                let mut client =
-                    projections::projections_client::ProjectionsClient::new(handle.channel.clone());
-
-                let resp = client.result(req).await?.into_inner();
-                let value = resp
-                    .result
-                    .map(parse_value)
-                    .unwrap_or(serde_json::Value::Null);
-
-                Ok(serde_json::from_value(value))
+            .execute(OperationKind::Projection, |handle| {
+                let req = crate::commands::new_request(
+                    req.clone(),
+                    options.credentials.as_ref().cloned(),
+                );
+
+                async move {
+                    let mut client = projections::projections_client::ProjectionsClient::new(
+                        handle.channel.clone(),
+                    );
+
+                    let resp = client.result(req).await?.into_inner();
+                    let value = resp
+                        .result
+                        .map(parse_value)
+                        .unwrap_or(serde_json::Value::Null);
+
+                    Ok(serde_json::from_value(value))
+                }
             })
             .await
+            .map_err(|e| ProjectionError::from_error(name.as_ref(), e))
     }
 
     pub async fn restart_subsystem(&self, credentials: Option<&Credentials>) -> crate::Result<()> {
-        let mut req = tonic::Request::new(Empty {});
-
-        crate::commands::configure_auth_req(&mut req, credentials.cloned());
+        let req = Empty {};
 
         self.client
-            .execute(|handle| async {
-                let mut client =
-                    projections::projections_client::ProjectionsClient::new(handle.channel);
-                let _ = client.restart_subsystem(req).await?;
+            .execute(OperationKind::Projection, |handle| {
+                let req = crate::commands::new_request(req.clone(), credentials.cloned());
+
+                async move {
+                    let mut client =
+                        projections::projections_client::ProjectionsClient::new(handle.channel);
+                    let _ = client.restart_subsystem(req).await?;
 
-                Ok(())
+                    Ok(())
+                }
             })
             .await
     }
@@ -0,0 +1,267 @@
+//! Opt-in cache of `read_stream` results, enabled with the `readCacheSize`
+//! setting.
+//!
+//! Entries expire after the `readCacheTtl` setting duration. The client drops
+//! every entry of a stream it writes to, deletes or sees a new event of
+//! through a subscription. Reading backward from the end of a stream also
+//! reveals its last event number: when it changed, the stream entries are
+//! dropped as well.
+use crate::options::read_stream::ReadStreamOptions;
+use crate::types::{ReadDirection, ResolvedEvent, StreamPosition};
+use bytes::Bytes;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Identifies a read, two reads with the same key returning the same events.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub(crate) struct ReadKey {
+    stream: String,
+    position: StreamPosition<u64>,
+    direction: ReadDirection,
+    count: usize,
+    resolve_link_tos: bool,
+    // Access control lists can make the same read differ from one user to
+    // another. The password is part of the key so a read with wrong
+    // credentials never gets a result the server checked for someone else.
+    credentials: Option<(Bytes, Bytes)>,
+}
+
+impl ReadKey {
+    pub(crate) fn new(stream: &str, options: &ReadStreamOptions, count: usize) -> Self {
+        ReadKey {
+            stream: stream.to_string(),
+            position: options.position,
+            direction: options.direction,
+            count,
+            resolve_link_tos: options.resolve_link_tos,
+            credentials: options
+                .credentials
+                .as_ref()
+                .map(|c| (c.login.clone(), c.password.clone())),
+        }
+    }
+}
+
+struct Entry {
+    stored_at: Instant,
+    last_used: u64,
+    events: Vec<ResolvedEvent>,
+}
+
+#[derive(Default)]
+struct State {
+    entries: HashMap<ReadKey, Entry>,
+    last_event_numbers: HashMap<String, u64>,
+    uses: u64,
+}
+
+pub(crate) struct ReadCache {
+    capacity: usize,
+    ttl: Duration,
+    state: Mutex<State>,
+}
+
+impl ReadCache {
+    pub(crate) fn new(capacity: usize, ttl: Duration) -> Self {
+        ReadCache {
+            capacity,
+            ttl,
+            state: Mutex::new(State::default()),
+        }
+    }
+
+    pub(crate) fn is_enabled(&self) -> bool {
+        self.capacity > 0
+    }
+
+    pub(crate) fn get(&self, key: &ReadKey, now: Instant) -> Option<Vec<ResolvedEvent>> {
+        let mut state = self.state.lock().unwrap();
+        let ttl = self.ttl;
+
+        state.uses += 1;
+        let uses = state.uses;
+
+        match state.entries.get_mut(key) {
+            Some(entry) if now.saturating_duration_since(entry.stored_at) < ttl => {
+                entry.last_used = uses;
+
+                Some(entry.events.clone())
+            }
+
+            Some(_) => {
+                state.entries.remove(key);
+
+                None
+            }
+
+            None => None,
+        }
+    }
+
+    pub(crate) fn insert(&self, key: ReadKey, events: &[ResolvedEvent], now: Instant) {
+        if !self.is_enabled() {
+            return;
+        }
+
+        let mut state = self.state.lock().unwrap();
+
+        if key.position == StreamPosition::End {
+            if let Some(last) = events.first() {
+                let last = last.get_original_event().revision;
+
+                if let Some(known) = state.last_event_numbers.insert(key.stream.clone(), last) {
+                    if known != last {
+                        debug!("Stream {} changed, dropping its cached reads", key.stream);
+                        state.entries.retain(|k, _| k.stream != key.stream);
+                    }
+                }
+            }
+        }
+
+        if !state.entries.contains_key(&key) && state.entries.len() >= self.capacity {
+            let lru = state
+                .entries
+                .iter()
+                .min_by_key(|(_, entry)| entry.last_used)
+                .map(|(k, _)| k.clone());
+
+            if let Some(lru) = lru {
+                state.entries.remove(&lru);
+            }
+        }
+
+        state.uses += 1;
+
+        let entry = Entry {
+            stored_at: now,
+            last_used: state.uses,
+            events: events.to_vec(),
+        };
+
+        state.entries.insert(key, entry);
+    }
+
+    /// Drops every cached read of the given stream.
+    pub(crate) fn invalidate(&self, stream: &str) {
+        if !self.is_enabled() {
+            return;
+        }
+
+        let mut state = self.state.lock().unwrap();
+
+        state.entries.retain(|key, _| key.stream != stream);
+        state.last_event_numbers.remove(stream);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ReadCache, ReadKey};
+    use crate::types::{Credentials, Position, RecordedEvent, ResolvedEvent, StreamPosition};
+    use crate::ReadStreamOptions;
+    use std::time::{Duration, Instant};
+
+    fn event(stream: &str, revision: u64) -> ResolvedEvent {
+        ResolvedEvent {
+            event: Some(RecordedEvent {
+                stream_id: stream.to_string(),
+                id: uuid::Uuid::nil(),
+                revision,
+                event_type: "test".to_string(),
+                data: Default::default(),
+                metadata: Default::default(),
+                custom_metadata: Default::default(),
+                is_json: false,
+                position: Position::start(),
+            }),
+            link: None,
+            commit_position: None,
+        }
+    }
+
+    fn key(stream: &str, count: usize) -> ReadKey {
+        ReadKey::new(stream, &ReadStreamOptions::default(), count)
+    }
+
+    #[test]
+    fn entries_expire_after_ttl() {
+        let cache = ReadCache::new(4, Duration::from_secs(1));
+        let now = Instant::now();
+
+        cache.insert(key("foo", 10), &[event("foo", 0)], now);
+
+        assert_eq!(cache.get(&key("foo", 10), now).map(|e| e.len()), Some(1));
+        assert!(cache.get(&key("foo", 5), now).is_none());
+        assert!(cache
+            .get(&key("foo", 10), now + Duration::from_secs(1))
+            .is_none());
+    }
+
+    #[test]
+    fn least_recently_used_entry_is_evicted() {
+        let cache = ReadCache::new(2, Duration::from_secs(60));
+        let now = Instant::now();
+
+        cache.insert(key("a", 1), &[], now);
+        cache.insert(key("b", 1), &[], now);
+        assert!(cache.get(&key("a", 1), now).is_some());
+        cache.insert(key("c", 1), &[], now);
+
+        assert!(cache.get(&key("a", 1), now).is_some());
+        assert!(cache.get(&key("b", 1), now).is_none());
+        assert!(cache.get(&key("c", 1), now).is_some());
+    }
+
+    #[test]
+    fn stream_entries_are_invalidated() {
+        let cache = ReadCache::new(4, Duration::from_secs(60));
+        let now = Instant::now();
+
+        cache.insert(key("foo", 1), &[], now);
+        cache.insert(key("bar", 1), &[], now);
+        cache.invalidate("foo");
+
+        assert!(cache.get(&key("foo", 1), now).is_none());
+        assert!(cache.get(&key("bar", 1), now).is_some());
+    }
+
+    #[test]
+    fn last_event_number_change_invalidates_stream() {
+        let cache = ReadCache::new(4, Duration::from_secs(60));
+        let now = Instant::now();
+        let last = ReadStreamOptions::default().position(StreamPosition::End);
+
+        cache.insert(key("foo", 10), &[event("foo", 0)], now);
+        cache.insert(ReadKey::new("foo", &last, 1), &[event("foo", 3)], now);
+        assert!(cache.get(&key("foo", 10), now).is_some());
+
+        cache.insert(ReadKey::new("foo", &last, 2), &[event("foo", 4)], now);
+        assert!(cache.get(&key("foo", 10), now).is_none());
+    }
+
+    #[test]
+    fn wrong_password_misses_the_cache() {
+        let cache = ReadCache::new(4, Duration::from_secs(60));
+        let now = Instant::now();
+        let admin =
+            ReadStreamOptions::default().authenticated(Credentials::new("admin", "changeit"));
+        let wrong = ReadStreamOptions::default().authenticated(Credentials::new("admin", "wrong"));
+
+        cache.insert(ReadKey::new("foo", &admin, 1), &[event("foo", 0)], now);
+
+        assert!(cache.get(&ReadKey::new("foo", &admin, 1), now).is_some());
+        assert!(cache.get(&ReadKey::new("foo", &wrong, 1), now).is_none());
+        assert!(cache.get(&key("foo", 1), now).is_none());
+    }
+
+    #[test]
+    fn disabled_cache_stores_nothing() {
+        let cache = ReadCache::new(0, Duration::from_secs(60));
+        let now = Instant::now();
+
+        cache.insert(key("foo", 1), &[], now);
+
+        assert!(cache.get(&key("foo", 1), now).is_none());
+    }
+}
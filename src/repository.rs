@@ -0,0 +1,228 @@
+//! Event-sourced aggregates stored one per stream, see [`Repository`].
+//!
+//! An aggregate is loaded by reading its stream from the start and applying
+//! each event to its default state. New events are appended expecting the
+//! revision the aggregate was loaded at, so a concurrent writer makes the
+//! save fail instead of being silently overwritten. [`Repository::execute`]
+//! handles that case by loading the aggregate again and running the command
+//! against the fresh state.
+use crate::options::append_to_stream::AppendToStreamOptions;
+use crate::options::read_stream::ReadStreamOptions;
+use crate::types::{
+    All, EventData, ExpectedRevision, ReadResult, RecordedEvent, WriteResult, WrongExpectedVersion,
+};
+use crate::Client;
+use futures::TryStreamExt;
+use std::marker::PhantomData;
+use thiserror::Error;
+
+/// State built from the events of a stream.
+pub trait Aggregate: Default {
+    /// Event the aggregate is built from.
+    type Event;
+
+    /// Name of the stream holding the events of the aggregate with the given
+    /// id, `order-{id}` for instance.
+    fn stream_id(id: &str) -> String;
+
+    /// Updates the state with an event, either read from the stream or just
+    /// saved.
+    fn apply(&mut self, event: Self::Event);
+
+    /// Decodes an event read from the stream. Events that can't be decoded
+    /// should fail with [`crate::Error::InternalParsingError`].
+    fn decode(event: &RecordedEvent) -> crate::Result<Self::Event>;
+
+    /// Encodes an event to append to the stream.
+    fn encode(event: &Self::Event) -> crate::Result<EventData>;
+}
+
+/// An aggregate along with the revision of the stream it was loaded at.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Loaded<A> {
+    state: A,
+    revision: Option<u64>,
+}
+
+impl<A> Loaded<A> {
+    pub fn state(&self) -> &A {
+        &self.state
+    }
+
+    pub fn into_state(self) -> A {
+        self.state
+    }
+
+    /// Revision of the last event applied to the aggregate, `None` if its
+    /// stream doesn't exist yet.
+    pub fn revision(&self) -> Option<u64> {
+        self.revision
+    }
+
+    fn expected_revision(&self) -> ExpectedRevision {
+        match self.revision {
+            Some(revision) => ExpectedRevision::Exact(revision),
+            None => ExpectedRevision::NoStream,
+        }
+    }
+}
+
+/// Returned by [`Repository::execute`].
+#[derive(Error, Debug)]
+pub enum RepositoryError<E> {
+    /// The command rejected the aggregate state.
+    #[error("The command was rejected: {0}")]
+    Rejected(E),
+    /// The stream kept being written to between loading the aggregate and
+    /// saving its new events.
+    #[error("Stream {stream} kept changing, gave up after {attempts} attempts: {conflict}")]
+    Conflict {
+        stream: String,
+        attempts: usize,
+        conflict: WrongExpectedVersion,
+    },
+    #[error(transparent)]
+    Error(#[from] crate::Error),
+}
+
+/// Loads and saves aggregates of type `A` through a client.
+pub struct Repository<A> {
+    client: Client,
+    max_retries: usize,
+    _aggregate: PhantomData<fn() -> A>,
+}
+
+impl<A> Clone for Repository<A> {
+    fn clone(&self) -> Self {
+        Repository {
+            client: self.client.clone(),
+            max_retries: self.max_retries,
+            _aggregate: PhantomData,
+        }
+    }
+}
+
+impl<A: Aggregate> Repository<A> {
+    pub fn new(client: Client) -> Self {
+        Repository {
+            client,
+            max_retries: 3,
+            _aggregate: PhantomData,
+        }
+    }
+
+    /// Times [`Repository::execute`] runs a command again after a
+    /// concurrent write to the stream. Default: `3`.
+    pub fn max_retries(self, max_retries: usize) -> Self {
+        Self {
+            max_retries,
+            ..self
+        }
+    }
+
+    /// Reads the stream of an aggregate and applies its events. An aggregate
+    /// whose stream doesn't exist is loaded in its default state.
+    pub async fn load(&self, id: &str) -> crate::Result<Loaded<A>> {
+        let stream = A::stream_id(id);
+        let mut loaded = Loaded {
+            state: A::default(),
+            revision: None,
+        };
+
+        let mut events = match self
+            .client
+            .read_stream(stream.as_str(), &ReadStreamOptions::default(), All)
+            .await?
+        {
+            ReadResult::Ok(events) => events,
+            ReadResult::StreamNotFound(_) => return Ok(loaded),
+        };
+
+        while let Some(event) = events.try_next().await? {
+            let event = event.get_original_event();
+
+            loaded.state.apply(A::decode(event)?);
+            loaded.revision = Some(event.revision);
+        }
+
+        Ok(loaded)
+    }
+
+    /// Appends events to the stream of an aggregate, expecting the stream to
+    /// be at the revision the aggregate was loaded at. On success, the events
+    /// are applied to the aggregate.
+    pub async fn save(
+        &self,
+        id: &str,
+        loaded: &mut Loaded<A>,
+        events: Vec<A::Event>,
+    ) -> crate::Result<Result<WriteResult, WrongExpectedVersion>> {
+        let data = events
+            .iter()
+            .map(A::encode)
+            .collect::<crate::Result<Vec<_>>>()?;
+        let options =
+            AppendToStreamOptions::default().expected_revision(loaded.expected_revision());
+
+        let result = self
+            .client
+            .append_to_stream(A::stream_id(id), &options, data)
+            .await?;
+
+        if let Ok(ref result) = result {
+            for event in events {
+                loaded.state.apply(event);
+            }
+
+            loaded.revision = Some(result.next_expected_version);
+        }
+
+        Ok(result)
+    }
+
+    /// Loads an aggregate, runs a command deciding which events to append
+    /// from its state and saves them. When another writer appended to the
+    /// stream in the meantime, the aggregate is loaded again and the command
+    /// runs against the new state, up to the configured number of retries.
+    /// Returns the aggregate with the new events applied.
+    pub async fn execute<F, E>(
+        &self,
+        id: &str,
+        mut command: F,
+    ) -> Result<Loaded<A>, RepositoryError<E>>
+    where
+        F: FnMut(&A) -> Result<Vec<A::Event>, E>,
+    {
+        let mut attempts = 0;
+
+        loop {
+            attempts += 1;
+
+            let mut loaded = self.load(id).await?;
+            let events = command(&loaded.state).map_err(RepositoryError::Rejected)?;
+
+            if events.is_empty() {
+                return Ok(loaded);
+            }
+
+            match self.save(id, &mut loaded, events).await? {
+                Ok(_) => return Ok(loaded),
+
+                Err(conflict) if attempts > self.max_retries => {
+                    return Err(RepositoryError::Conflict {
+                        stream: A::stream_id(id),
+                        attempts,
+                        conflict,
+                    });
+                }
+
+                Err(conflict) => {
+                    debug!(
+                        "Aggregate {} changed concurrently, running the command again: {}",
+                        id, conflict
+                    );
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,67 @@
+//! Decision to send an operation again after the server didn't handle it,
+//! see [`RetryPolicy`].
+//!
+//! The client first works out what its settings call for: retrying on the
+//! leader up to `notLeaderRetryLimit`, backing off from a busy server, or
+//! resending an idempotent write that timed out. The policy gets that
+//! suggestion and has the final say. Operations that can't be sent again,
+//! because their request can't be rebuilt or isn't idempotent, never reach
+//! the policy.
+use crate::metrics::OperationKind;
+use crate::types::RetryReason;
+use std::time::Duration;
+
+/// What to do with an operation the server didn't handle.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RetryDecision {
+    /// Sends the operation again once the delay elapsed.
+    Retry(Duration),
+    /// Fails the operation with the error the server returned.
+    GiveUp,
+}
+
+/// Operation the server didn't handle, handed to a [`RetryPolicy`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RetryAttempt {
+    pub(crate) attempt: usize,
+    pub(crate) kind: OperationKind,
+    pub(crate) reason: RetryReason,
+    pub(crate) suggested: RetryDecision,
+}
+
+impl RetryAttempt {
+    /// Number of the retry being decided, `1` when the operation failed for
+    /// the first time. Retries of every reason are counted.
+    pub fn attempt(&self) -> usize {
+        self.attempt
+    }
+
+    pub fn kind(&self) -> OperationKind {
+        self.kind
+    }
+
+    /// Why the server didn't handle the operation.
+    pub fn reason(&self) -> RetryReason {
+        self.reason
+    }
+
+    /// Decision the client settings lead to.
+    pub fn suggested(&self) -> RetryDecision {
+        self.suggested
+    }
+}
+
+/// Decides whether an operation the server didn't handle is sent again.
+pub trait RetryPolicy: Send + Sync + 'static {
+    fn should_retry(&self, attempt: &RetryAttempt) -> RetryDecision;
+}
+
+/// Policy following the client settings. That's the policy used by default.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct SettingsRetryPolicy;
+
+impl RetryPolicy for SettingsRetryPolicy {
+    fn should_retry(&self, attempt: &RetryAttempt) -> RetryDecision {
+        attempt.suggested
+    }
+}
@@ -0,0 +1,105 @@
+//! Per-stream locks serializing the appends a client issues to the same
+//! stream, enabled with the `serializeStreamWrites` setting.
+//!
+//! The locks only order the appends of the tasks sharing a client, or its
+//! clones: they don't protect against writers in other processes, which
+//! optimistic concurrency still has to handle. Appends to different streams
+//! never wait for each other. A lock is dropped once no append holds or
+//! waits for it.
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, Weak};
+use tokio::sync::OwnedMutexGuard;
+
+type StreamLock = tokio::sync::Mutex<()>;
+
+/// Number of locks from which the locks nobody uses anymore are evicted.
+const MIN_EVICTION_THRESHOLD: usize = 64;
+
+struct State {
+    locks: HashMap<String, Weak<StreamLock>>,
+    evict_at: usize,
+}
+
+pub(crate) struct StreamLocks {
+    state: Mutex<State>,
+}
+
+impl Default for StreamLocks {
+    fn default() -> Self {
+        StreamLocks {
+            state: Mutex::new(State {
+                locks: HashMap::new(),
+                evict_at: MIN_EVICTION_THRESHOLD,
+            }),
+        }
+    }
+}
+
+impl StreamLocks {
+    /// Waits for the appends to the stream issued before to complete. The
+    /// stream is locked until the guard is dropped.
+    pub(crate) async fn lock(&self, stream: &str) -> OwnedMutexGuard<()> {
+        let lock = {
+            let mut state = self.state.lock().unwrap();
+
+            match state.locks.get(stream).and_then(Weak::upgrade) {
+                Some(lock) => lock,
+                None => {
+                    if state.locks.len() >= state.evict_at {
+                        state.locks.retain(|_, lock| lock.strong_count() > 0);
+                        state.evict_at = (state.locks.len() * 2).max(MIN_EVICTION_THRESHOLD);
+                    }
+
+                    let lock = Arc::new(StreamLock::new(()));
+
+                    state
+                        .locks
+                        .insert(stream.to_string(), Arc::downgrade(&lock));
+
+                    lock
+                }
+            }
+        };
+
+        lock.lock_owned().await
+    }
+
+    #[cfg(test)]
+    fn len(&self) -> usize {
+        self.state.lock().unwrap().locks.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{StreamLocks, MIN_EVICTION_THRESHOLD};
+    use futures::FutureExt;
+
+    #[tokio::test]
+    async fn appends_to_the_same_stream_are_serialized() {
+        let locks = StreamLocks::default();
+        let first = locks.lock("orders").await;
+        let mut second = Box::pin(locks.lock("orders"));
+
+        assert!((&mut second).now_or_never().is_none());
+        assert!(locks.lock("invoices").now_or_never().is_some());
+
+        drop(first);
+
+        assert!(second.now_or_never().is_some());
+    }
+
+    #[tokio::test]
+    async fn idle_locks_are_evicted() {
+        let locks = StreamLocks::default();
+        let held = locks.lock("held").await;
+
+        for idx in 0..MIN_EVICTION_THRESHOLD * 4 {
+            drop(locks.lock(&format!("stream-{}", idx)).await);
+        }
+
+        assert!(locks.len() <= MIN_EVICTION_THRESHOLD);
+
+        drop(held);
+    }
+}
@@ -0,0 +1,688 @@
+//! Inventory of the subscriptions a client has open, allowing to cancel them,
+//! on a controlled shutdown for instance.
+//!
+//! Each subscription session is registered when the server accepts it and
+//! deregistered once its stream is dropped. A subscription that resubscribes
+//! after a transient failure is registered again as a new session.
+//!
+//! The client doesn't buffer the events of a subscription: the server pushes
+//! them as the HTTP/2 flow control allows, so a consumer that lags behind
+//! makes the events wait on the server rather than in memory. Once a
+//! subscription is live, the lag of each new event it delivers is the time
+//! it took to get to the consumer, beyond the time the least delayed event of
+//! the session took. Comparing delays to that one leaves out the network
+//! latency and the clock skew between the client and the server. A lag over
+//! the `slowConsumerThreshold` setting flags the subscription as a slow
+//! consumer, and over the `slowConsumerDropAfter` setting drops it.
+use crate::clock::Clock;
+use crate::event_store::client::persistent;
+use crate::grpc::ClientSettings;
+use crate::metrics::{Metrics, OperationKind};
+use crate::types::{RawEvent, ResolvedEvent, SubEvent, SubscriptionDropReason};
+use futures::channel::{mpsc, oneshot};
+use futures::future::{self, Either};
+use futures::stream::{BoxStream, StreamExt};
+use futures::FutureExt;
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime};
+
+/// Identifies a subscription session of a client.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct SubscriptionId(u64);
+
+impl fmt::Display for SubscriptionId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+/// What a subscription session is subscribed to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SubscriptionTarget {
+    Stream(String),
+    All,
+    Persistent { stream: String, group: String },
+}
+
+impl SubscriptionTarget {
+    fn operation_kind(&self) -> OperationKind {
+        match self {
+            SubscriptionTarget::Stream(_) => OperationKind::SubscribeToStream,
+            SubscriptionTarget::All => OperationKind::SubscribeToAll,
+            SubscriptionTarget::Persistent { .. } => OperationKind::ConnectPersistentSubscription,
+        }
+    }
+}
+
+/// Snapshot of an open subscription session.
+#[derive(Debug, Clone)]
+pub struct SubscriptionInfo {
+    pub id: SubscriptionId,
+    pub target: SubscriptionTarget,
+    /// Time elapsed since the server accepted the subscription.
+    pub age: Duration,
+    /// Number of events the subscription delivered so far.
+    pub events_delivered: u64,
+    /// How late the last event delivered reached the consumer, `None` if
+    /// the consumer is waiting for the next event or the subscription isn't
+    /// live yet.
+    pub lag: Option<Duration>,
+    /// The lag of the last event delivered exceeded the
+    /// `slowConsumerThreshold` setting.
+    pub slow_consumer: bool,
+}
+
+/// Slow consumer state of a subscription session.
+#[derive(Default)]
+struct Backlog {
+    /// The subscription caught up: from then on, late events mean the
+    /// consumer lags behind.
+    live: bool,
+    /// When the least delayed event was delivered, and when it was created.
+    baseline: Option<(Instant, SystemTime)>,
+    /// Creation time of the newest event delivered. Older events, redelivered
+    /// by a persistent subscription for instance, aren't measured.
+    newest: Option<SystemTime>,
+    lag: Option<Duration>,
+    slow: bool,
+}
+
+struct Entry {
+    target: SubscriptionTarget,
+    started_at: Instant,
+    delivered: Arc<AtomicU64>,
+    backlog: Arc<Mutex<Backlog>>,
+    cancel: oneshot::Sender<()>,
+    /// Requests of a persistent subscription, closed on cancellation so the
+    /// gRPC call ends on both sides.
+    requests: Option<mpsc::Sender<persistent::ReadReq>>,
+}
+
+impl Entry {
+    fn cancel(self) -> bool {
+        if let Some(mut requests) = self.requests {
+            requests.close_channel();
+        }
+
+        self.cancel.send(()).is_ok()
+    }
+}
+
+#[derive(Default)]
+pub(crate) struct SubscriptionRegistry {
+    next_id: AtomicU64,
+    entries: Mutex<HashMap<SubscriptionId, Entry>>,
+    slow_consumer_threshold: Duration,
+    slow_consumer_drop_after: Duration,
+    metrics: Arc<Metrics>,
+}
+
+/// Removes a session from the registry once its stream is dropped.
+struct Deregister {
+    registry: Arc<SubscriptionRegistry>,
+    id: SubscriptionId,
+}
+
+impl Drop for Deregister {
+    fn drop(&mut self) {
+        self.registry.entries.lock().unwrap().remove(&self.id);
+    }
+}
+
+impl SubscriptionRegistry {
+    pub(crate) fn new(settings: &ClientSettings, metrics: Arc<Metrics>) -> Self {
+        SubscriptionRegistry {
+            slow_consumer_threshold: settings.slow_consumer_threshold,
+            slow_consumer_drop_after: settings.slow_consumer_drop_after,
+            metrics,
+            ..Default::default()
+        }
+    }
+
+    /// Whether the slow consumer detection is enabled. Subscriptions then need
+    /// to report when they catch up, with [`SubEvent::CaughtUp`].
+    pub(crate) fn detects_slow_consumers(&self) -> bool {
+        self.slow_consumer_threshold != Duration::default()
+            || self.slow_consumer_drop_after != Duration::default()
+    }
+
+    /// Registers a subscription session. Once cancelled, the returned stream
+    /// fails with [`crate::Error::Cancelled`] and ends, dropping the
+    /// underlying gRPC call, which unsubscribes from the server.
+    /// [`SubEvent::CaughtUp`] marks the session live and is only passed on
+    /// when `notify_caught_up` is set.
+    pub(crate) fn track<'a>(
+        self: &Arc<Self>,
+        target: SubscriptionTarget,
+        clock: Arc<dyn Clock>,
+        notify_caught_up: bool,
+        events: BoxStream<'a, crate::Result<SubEvent>>,
+    ) -> BoxStream<'a, crate::Result<SubEvent>> {
+        self.register(target, clock, notify_caught_up, None, events)
+    }
+
+    /// Registers a persistent subscription session, see
+    /// [`SubscriptionRegistry::track`]. Cancelling it also closes the
+    /// channel of its requests: acks and nacks sent afterwards are dropped.
+    pub(crate) fn track_persistent<'a>(
+        self: &Arc<Self>,
+        target: SubscriptionTarget,
+        clock: Arc<dyn Clock>,
+        requests: mpsc::Sender<persistent::ReadReq>,
+        events: BoxStream<'a, crate::Result<SubEvent>>,
+    ) -> BoxStream<'a, crate::Result<SubEvent>> {
+        self.register(target, clock, true, Some(requests), events)
+    }
+
+    fn register<'a>(
+        self: &Arc<Self>,
+        target: SubscriptionTarget,
+        clock: Arc<dyn Clock>,
+        notify_caught_up: bool,
+        requests: Option<mpsc::Sender<persistent::ReadReq>>,
+        mut events: BoxStream<'a, crate::Result<SubEvent>>,
+    ) -> BoxStream<'a, crate::Result<SubEvent>> {
+        let id = SubscriptionId(self.next_id.fetch_add(1, Ordering::Relaxed));
+        let delivered = Arc::new(AtomicU64::new(0));
+        let backlog = Arc::new(Mutex::new(Backlog {
+            // A persistent subscription has no catch-up phase: the events it
+            // didn't deliver yet are all waiting for its consumers.
+            live: matches!(target, SubscriptionTarget::Persistent { .. }),
+            ..Default::default()
+        }));
+        let (cancel, cancelled) = oneshot::channel();
+        let kind = target.operation_kind();
+
+        debug!("Subscription {} to {:?} registered", id, target);
+
+        self.entries.lock().unwrap().insert(
+            id,
+            Entry {
+                target,
+                started_at: clock.now(),
+                delivered: delivered.clone(),
+                backlog: backlog.clone(),
+                cancel,
+                requests,
+            },
+        );
+
+        let guard = Deregister {
+            registry: self.clone(),
+            id,
+        };
+
+        let registry = self.clone();
+        let stream = async_stream::stream! {
+            let _guard = guard;
+            let mut cancelled = cancelled;
+
+            loop {
+                if (&mut cancelled).now_or_never().is_some() {
+                    debug!("Subscription {} cancelled", id);
+                    yield Err(crate::Error::Cancelled);
+                    break;
+                }
+
+                let mut next = events.next();
+
+                let item = match (&mut next).now_or_never() {
+                    Some(item) => item,
+
+                    None => {
+                        registry.kept_up(id, &backlog);
+
+                        match future::select(next, &mut cancelled).await {
+                            Either::Left((item, _)) => item,
+                            Either::Right(_) => {
+                                debug!("Subscription {} cancelled", id);
+                                yield Err(crate::Error::Cancelled);
+                                break;
+                            }
+                        }
+                    }
+                };
+
+                match item {
+                    Some(item) => {
+                        match item {
+                            Ok(SubEvent::EventAppeared(ref event))
+                            | Ok(SubEvent::RawEventAppeared(RawEvent { ref event, .. })) => {
+                                if registry.lagging(id, kind, &backlog, event, clock.now()) {
+                                    yield Err(crate::Error::SubscriptionDropped(SubscriptionDropReason::SlowConsumer));
+                                    break;
+                                }
+
+                                delivered.fetch_add(1, Ordering::Relaxed);
+                            }
+
+                            Ok(SubEvent::CaughtUp) => {
+                                backlog.lock().unwrap().live = true;
+
+                                if !notify_caught_up {
+                                    continue;
+                                }
+                            }
+
+                            _ => {}
+                        }
+
+                        yield item;
+                    }
+
+                    None => break,
+                }
+            }
+        };
+
+        Box::pin(stream)
+    }
+
+    /// Records the lag of an event about to be delivered. Returns `true` if
+    /// the subscription has to be dropped.
+    fn lagging(
+        &self,
+        id: SubscriptionId,
+        kind: OperationKind,
+        backlog: &Mutex<Backlog>,
+        event: &ResolvedEvent,
+        now: Instant,
+    ) -> bool {
+        let mut backlog = backlog.lock().unwrap();
+
+        let created = match event.get_original_event().created() {
+            Some(created) if backlog.live => created,
+            _ => return false,
+        };
+
+        if matches!(backlog.newest, Some(newest) if created < newest) {
+            return false;
+        }
+
+        backlog.newest = Some(created);
+
+        let lag = match backlog.baseline {
+            Some((delivered_at, baseline)) => {
+                let waited = now.saturating_duration_since(delivered_at);
+                let written = created.duration_since(baseline).unwrap_or_default();
+
+                waited
+                    .checked_sub(written)
+                    .filter(|lag| *lag > Duration::default())
+            }
+
+            None => None,
+        };
+
+        let lag = match lag {
+            Some(lag) => lag,
+
+            // That event reached the consumer sooner than the baseline one,
+            // it becomes the baseline.
+            None => {
+                backlog.baseline = Some((now, created));
+                Duration::default()
+            }
+        };
+
+        backlog.lag = Some(lag);
+
+        if self.slow_consumer_threshold != Duration::default() {
+            if lag > self.slow_consumer_threshold && !backlog.slow {
+                backlog.slow = true;
+                self.metrics.slow_consumer(kind);
+
+                warn!(
+                    target: "eventstore::slow_consumer",
+                    "Subscription {} is a slow consumer, events reach it {:?} late",
+                    id,
+                    lag
+                );
+            } else if lag <= self.slow_consumer_threshold && backlog.slow {
+                backlog.slow = false;
+
+                info!(
+                    target: "eventstore::slow_consumer",
+                    "Subscription {} caught up with its backlog",
+                    id
+                );
+            }
+        }
+
+        if self.slow_consumer_drop_after != Duration::default()
+            && lag > self.slow_consumer_drop_after
+        {
+            error!(
+                "Subscription {} dropped, events reach it {:?} late",
+                id, lag
+            );
+
+            return true;
+        }
+
+        false
+    }
+
+    /// Records the consumer had to wait for the next event.
+    fn kept_up(&self, id: SubscriptionId, backlog: &Mutex<Backlog>) {
+        let mut backlog = backlog.lock().unwrap();
+
+        backlog.lag = None;
+
+        if backlog.slow {
+            backlog.slow = false;
+
+            info!(
+                target: "eventstore::slow_consumer",
+                "Subscription {} caught up with its backlog",
+                id
+            );
+        }
+    }
+
+    pub(crate) fn list(&self, now: Instant) -> Vec<SubscriptionInfo> {
+        let entries = self.entries.lock().unwrap();
+        let mut sessions: Vec<_> = entries
+            .iter()
+            .map(|(id, entry)| {
+                let backlog = entry.backlog.lock().unwrap();
+
+                SubscriptionInfo {
+                    id: *id,
+                    target: entry.target.clone(),
+                    age: now.saturating_duration_since(entry.started_at),
+                    events_delivered: entry.delivered.load(Ordering::Relaxed),
+                    lag: backlog.lag,
+                    slow_consumer: backlog.slow,
+                }
+            })
+            .collect();
+
+        sessions.sort_by_key(|session| session.id);
+
+        sessions
+    }
+
+    /// Cancels a subscription session, returning `false` if it's not open
+    /// anymore.
+    pub(crate) fn cancel(&self, id: SubscriptionId) -> bool {
+        match self.entries.lock().unwrap().remove(&id) {
+            Some(entry) => entry.cancel(),
+            None => false,
+        }
+    }
+
+    /// Cancels every open subscription session, returning how many were.
+    pub(crate) fn cancel_all(&self) -> usize {
+        let mut entries = std::mem::take(&mut *self.entries.lock().unwrap());
+
+        entries
+            .drain()
+            .map(|(_, entry)| entry.cancel())
+            .filter(|cancelled| *cancelled)
+            .count()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{SubscriptionRegistry, SubscriptionTarget};
+    use crate::clock::{Clock, TestClock};
+    use crate::metrics::OperationKind;
+    use crate::types::{Position, RecordedEvent, ResolvedEvent, SubEvent, SubscriptionDropReason};
+    use futures::stream::{self, StreamExt};
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn cancelled_subscription_fails_and_deregisters() {
+        let registry = Arc::new(SubscriptionRegistry::default());
+        let clock = TestClock::new();
+        let started_at = clock.now();
+        let events = stream::iter(vec![Ok(SubEvent::CaughtUp)])
+            .chain(stream::pending())
+            .boxed();
+        let mut sub = registry.track(SubscriptionTarget::All, Arc::new(clock), true, events);
+
+        assert!(matches!(sub.next().await, Some(Ok(SubEvent::CaughtUp))));
+
+        let sessions = registry.list(started_at + Duration::from_secs(2));
+
+        assert_eq!(sessions.len(), 1);
+        assert_eq!(sessions[0].target, SubscriptionTarget::All);
+        assert_eq!(sessions[0].age, Duration::from_secs(2));
+        assert_eq!(sessions[0].events_delivered, 0);
+
+        assert!(registry.cancel(sessions[0].id));
+        assert!(matches!(
+            sub.next().await,
+            Some(Err(crate::Error::Cancelled))
+        ));
+        assert!(sub.next().await.is_none());
+        assert!(!registry.cancel(sessions[0].id));
+    }
+
+    #[tokio::test]
+    async fn cancelled_persistent_subscription_closes_its_requests() {
+        let registry = Arc::new(SubscriptionRegistry::default());
+        let clock: Arc<dyn Clock> = Arc::new(TestClock::new());
+        let (requests, mut received) = futures::channel::mpsc::channel(1);
+        let _sub = registry.track_persistent(
+            SubscriptionTarget::Persistent {
+                stream: "orders".to_string(),
+                group: "billing".to_string(),
+            },
+            clock.clone(),
+            requests.clone(),
+            stream::pending().boxed(),
+        );
+
+        assert_eq!(registry.cancel_all(), 1);
+        assert!(requests.is_closed());
+        assert!(received.next().await.is_none());
+    }
+
+    #[tokio::test]
+    async fn dropped_subscription_is_deregistered() {
+        let registry = Arc::new(SubscriptionRegistry::default());
+        let clock: Arc<dyn Clock> = Arc::new(TestClock::new());
+        let first = registry.track(
+            SubscriptionTarget::Stream("foo".to_string()),
+            clock.clone(),
+            true,
+            stream::pending().boxed(),
+        );
+        let _second = registry.track(
+            SubscriptionTarget::Stream("bar".to_string()),
+            clock.clone(),
+            true,
+            stream::pending().boxed(),
+        );
+
+        drop(first);
+
+        assert_eq!(registry.list(clock.now()).len(), 1);
+        assert_eq!(registry.cancel_all(), 1);
+        assert!(registry.list(clock.now()).is_empty());
+    }
+
+    /// An event of a live subscription, created at the given number of
+    /// seconds after the epoch.
+    fn event_created_at(secs: u64) -> crate::Result<SubEvent> {
+        let created = Duration::from_secs(secs).as_nanos() / 100;
+
+        Ok(SubEvent::EventAppeared(ResolvedEvent {
+            event: Some(RecordedEvent {
+                stream_id: "orders".to_string(),
+                id: uuid::Uuid::nil(),
+                revision: secs,
+                event_type: "test".to_string(),
+                data: Default::default(),
+                metadata: vec![("created".to_string(), created.to_string())]
+                    .into_iter()
+                    .collect(),
+                custom_metadata: Default::default(),
+                is_json: false,
+                position: Position::start(),
+            }),
+            link: None,
+            commit_position: None,
+        }))
+    }
+
+    #[tokio::test]
+    async fn lagging_live_subscription_is_flagged_then_dropped() {
+        let registry = Arc::new(SubscriptionRegistry {
+            slow_consumer_threshold: Duration::from_secs(5),
+            slow_consumer_drop_after: Duration::from_secs(30),
+            ..Default::default()
+        });
+        let clock = TestClock::new();
+        let events = stream::iter(vec![Ok(SubEvent::CaughtUp)])
+            .chain(stream::iter(0..).map(event_created_at))
+            .boxed();
+        let mut sub = registry.track(
+            SubscriptionTarget::All,
+            Arc::new(clock.clone()),
+            false,
+            events,
+        );
+
+        // An event is written every second but the consumer handles one
+        // every 2 seconds, getting a second further behind each time. The
+        // caught-up notification wasn't asked for, so it isn't passed on.
+        for _ in 0..7 {
+            assert!(matches!(
+                sub.next().await,
+                Some(Ok(SubEvent::EventAppeared(_)))
+            ));
+            clock.advance(Duration::from_secs(2));
+        }
+
+        let session = registry.list(clock.now()).remove(0);
+
+        assert_eq!(session.lag, Some(Duration::from_secs(6)));
+        assert!(session.slow_consumer);
+        assert_eq!(
+            registry
+                .metrics
+                .snapshot()
+                .get(OperationKind::SubscribeToAll)
+                .slow_consumers,
+            1
+        );
+
+        for _ in 0..24 {
+            assert!(matches!(
+                sub.next().await,
+                Some(Ok(SubEvent::EventAppeared(_)))
+            ));
+            clock.advance(Duration::from_secs(2));
+        }
+
+        assert!(matches!(
+            sub.next().await,
+            Some(Err(crate::Error::SubscriptionDropped(
+                SubscriptionDropReason::SlowConsumer
+            )))
+        ));
+        assert!(sub.next().await.is_none());
+    }
+
+    #[tokio::test]
+    async fn fast_consumer_under_constant_load_is_not_flagged() {
+        let registry = Arc::new(SubscriptionRegistry {
+            slow_consumer_threshold: Duration::from_secs(5),
+            slow_consumer_drop_after: Duration::from_secs(30),
+            ..Default::default()
+        });
+        let clock = TestClock::new();
+        let events = stream::iter(vec![Ok(SubEvent::CaughtUp)])
+            .chain(stream::iter(0..).map(event_created_at))
+            .boxed();
+        let mut sub = registry.track(
+            SubscriptionTarget::All,
+            Arc::new(clock.clone()),
+            true,
+            events,
+        );
+
+        assert!(matches!(sub.next().await, Some(Ok(SubEvent::CaughtUp))));
+
+        // The next event is always there already, but the consumer handles
+        // them as fast as they are written.
+        for _ in 0..60 {
+            assert!(matches!(
+                sub.next().await,
+                Some(Ok(SubEvent::EventAppeared(_)))
+            ));
+            clock.advance(Duration::from_secs(1));
+        }
+
+        let session = registry.list(clock.now()).remove(0);
+
+        assert_eq!(session.lag, Some(Duration::default()));
+        assert!(!session.slow_consumer);
+    }
+
+    #[tokio::test]
+    async fn consumer_catching_up_clears_the_flag() {
+        let registry = Arc::new(SubscriptionRegistry {
+            slow_consumer_threshold: Duration::from_secs(5),
+            ..Default::default()
+        });
+        let clock = TestClock::new();
+        let (mut sender, receiver) = futures::channel::mpsc::unbounded();
+        let mut sub = registry.track(
+            SubscriptionTarget::Persistent {
+                stream: "orders".to_string(),
+                group: "billing".to_string(),
+            },
+            Arc::new(clock.clone()),
+            true,
+            receiver.boxed(),
+        );
+
+        sender.start_send(event_created_at(0)).unwrap();
+        sender.start_send(event_created_at(1)).unwrap();
+        sub.next().await;
+        clock.advance(Duration::from_secs(7));
+        sub.next().await;
+
+        assert!(registry.list(clock.now())[0].slow_consumer);
+
+        // A retried event is older than the ones already delivered, its
+        // delay doesn't tell how late the consumer is.
+        sender.start_send(event_created_at(0)).unwrap();
+        sub.next().await;
+
+        assert_eq!(
+            registry.list(clock.now())[0].lag,
+            Some(Duration::from_secs(6))
+        );
+
+        // The consumer waits for the next event.
+        let next = tokio::spawn(async move {
+            let event = sub.next().await;
+
+            (event.is_some(), sub)
+        });
+        tokio::task::yield_now().await;
+
+        assert!(!registry.list(clock.now())[0].slow_consumer);
+
+        sender.start_send(event_created_at(8)).unwrap();
+
+        let (delivered, _sub) = next.await.unwrap();
+
+        assert!(delivered);
+
+        let session = registry.list(clock.now()).remove(0);
+
+        assert!(!session.slow_consumer);
+        assert_eq!(session.lag, Some(Duration::default()));
+    }
+}
@@ -0,0 +1,554 @@
+//! In-memory stand-in for the connection of a [`GrpcClient`], letting unit
+//! tests exercise the operation paths deterministically, without a server.
+//!
+//! The connection task hands out channels that never connect. An operation
+//! gets its responses from a [`Script`] instead of the server, and the
+//! requests the operation sends to the connection task, reconnecting after a
+//! failure or moving to a leader for instance, are recorded as
+//! [`ConnectionEvent`]s. Backoff delays run on a [`TestClock`] that
+//! [`TestTransport::drive`] moves forward.
+use crate::clock::TestClock;
+use crate::grpc::{ClientSettings, GrpcClient, Handle, Msg};
+use crate::listener::OperationListener;
+use crate::retry_policy::RetryPolicy;
+use crate::types::Endpoint;
+use futures::channel::mpsc;
+use futures::stream::StreamExt;
+use std::collections::VecDeque;
+use std::future::Future;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::watch;
+use tonic::metadata::MetadataValue;
+use tonic::{Code, Status};
+
+/// Request an operation sent to the connection task.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum ConnectionEvent {
+    /// The operation asked for a channel.
+    ChannelRequested,
+    /// The operation asked to select a node again, after the node failed.
+    Reconnect(Option<Endpoint>),
+    /// The operation asked to connect to the advertised leader.
+    NotLeader(Endpoint),
+}
+
+pub(crate) struct TestTransport {
+    client: GrpcClient,
+    clock: TestClock,
+    events: Arc<Mutex<Vec<ConnectionEvent>>>,
+}
+
+impl TestTransport {
+    pub(crate) fn new(settings: ClientSettings) -> Self {
+        let (sender, mut receiver) = mpsc::unbounded();
+        let (aborted_sender, aborted) = watch::channel(());
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let recorded = events.clone();
+        let channel = tonic::transport::Endpoint::from_static("http://127.0.0.1:1")
+            .connect_lazy()
+            .expect("the endpoint is valid");
+        let handles = sender.clone();
+
+        let task = tokio::spawn(async move {
+            while let Some(msg) = receiver.next().await {
+                let event = match msg {
+                    Msg::GetChannel(reply) => {
+                        let _ = reply.send(Ok(Handle::new(
+                            channel.clone(),
+                            handles.clone(),
+                            aborted.clone(),
+                        )));
+
+                        ConnectionEvent::ChannelRequested
+                    }
+
+                    Msg::CreateChannel(_, seed) => ConnectionEvent::Reconnect(seed),
+                    Msg::NotLeader(_, leader) => ConnectionEvent::NotLeader(leader),
+                    Msg::TopologyChanged(_) => continue,
+                };
+
+                recorded.lock().unwrap().push(event);
+            }
+        });
+
+        let clock = TestClock::new();
+        let client = GrpcClient::from_connection(settings, sender, task, aborted_sender)
+            .with_clock(Arc::new(clock.clone()));
+
+        TestTransport {
+            client,
+            clock,
+            events,
+        }
+    }
+
+    /// Makes the client decide retries with the given policy.
+    pub(crate) fn with_retry_policy(self, policy: Arc<dyn RetryPolicy>) -> Self {
+        TestTransport {
+            client: self.client.with_retry_policy(policy),
+            ..self
+        }
+    }
+
+    /// Notifies the given listener of the lifecycle of every operation.
+    pub(crate) fn with_listener(self, listener: Arc<dyn OperationListener>) -> Self {
+        TestTransport {
+            client: self.client.with_listener(listener),
+            ..self
+        }
+    }
+
+    pub(crate) fn client(&self) -> &GrpcClient {
+        &self.client
+    }
+
+    /// Requests sent to the connection task so far.
+    pub(crate) fn events(&self) -> Vec<ConnectionEvent> {
+        self.events.lock().unwrap().clone()
+    }
+
+    /// Runs an operation to completion, moving the clock forward whenever
+    /// the operation waits for it.
+    pub(crate) async fn drive<Fut>(&self, operation: Fut) -> Fut::Output
+    where
+        Fut: Future + Send + 'static,
+        Fut::Output: Send + 'static,
+    {
+        let mut operation = tokio::spawn(operation);
+
+        loop {
+            tokio::select! {
+                output = &mut operation => return output.expect("the operation panicked"),
+                _ = tokio::task::yield_now() => {
+                    if self.clock.pending_sleeps() > 0 {
+                        self.clock.advance(Duration::from_secs(60));
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Responses an operation gets in place of the server ones, in order. Once
+/// the script is exhausted, the operation fails with an `unimplemented`
+/// status.
+pub(crate) struct Script<A> {
+    responses: Mutex<VecDeque<Result<A, Status>>>,
+    calls: AtomicUsize,
+}
+
+impl<A> Script<A> {
+    pub(crate) fn new(responses: Vec<Result<A, Status>>) -> Arc<Self> {
+        Arc::new(Script {
+            responses: Mutex::new(responses.into()),
+            calls: AtomicUsize::new(0),
+        })
+    }
+
+    pub(crate) fn respond(&self) -> Result<A, Status> {
+        self.calls.fetch_add(1, Ordering::SeqCst);
+
+        self.responses
+            .lock()
+            .unwrap()
+            .pop_front()
+            .unwrap_or_else(|| Err(Status::unimplemented("the script is exhausted")))
+    }
+
+    /// Number of times the operation was sent.
+    pub(crate) fn calls(&self) -> usize {
+        self.calls.load(Ordering::SeqCst)
+    }
+}
+
+/// Status of a node that isn't the leader, advertising the given one.
+pub(crate) fn not_leader(leader: &Endpoint) -> Status {
+    let mut status = Status::new(Code::NotFound, "Leader info available");
+    let metadata = status.metadata_mut();
+
+    metadata.insert("exception", MetadataValue::from_static("not-leader"));
+    metadata.insert(
+        "leader-endpoint-host",
+        leader.host.parse().expect("valid header value"),
+    );
+    metadata.insert(
+        "leader-endpoint-port",
+        leader.port.to_string().parse().expect("valid header value"),
+    );
+
+    status
+}
+
+/// Status of a node too busy to handle the operation.
+pub(crate) fn server_busy() -> Status {
+    Status::unavailable("Server is too busy")
+}
+
+/// Status of a node that went unavailable.
+pub(crate) fn node_unavailable() -> Status {
+    Status::unavailable("Connection refused")
+}
+
+/// Status of a write the server timed out, as the server reports it.
+pub(crate) fn write_timeout() -> Status {
+    Status::aborted("Operation timed out")
+}
+
+/// Status of an operation whose deadline was exceeded.
+pub(crate) fn deadline_exceeded() -> Status {
+    Status::deadline_exceeded("Deadline exceeded")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        deadline_exceeded, node_unavailable, not_leader, server_busy, write_timeout,
+        ConnectionEvent, Script, TestTransport,
+    };
+    use crate::grpc::ClientSettings;
+    use crate::listener::{OperationListener, OperationOutcome};
+    use crate::metrics::OperationKind;
+    use crate::operation::OperationId;
+    use crate::retry_policy::{RetryAttempt, RetryDecision, RetryPolicy};
+    use crate::types::{Endpoint, RetryReason, WriteTimeoutPhase};
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    fn settings(params: &str) -> ClientSettings {
+        format!("esdb://localhost:2113?tls=false{}", params)
+            .parse()
+            .unwrap()
+    }
+
+    async fn run(
+        transport: &TestTransport,
+        script: std::sync::Arc<Script<u32>>,
+    ) -> crate::Result<u32> {
+        let client = transport.client().clone();
+
+        transport
+            .drive(async move {
+                client
+                    .execute(OperationKind::AppendToStream, |_| {
+                        let script = script.clone();
+
+                        async move { script.respond() }
+                    })
+                    .await
+            })
+            .await
+    }
+
+    #[tokio::test]
+    async fn not_leader_is_retried_on_the_leader() {
+        let transport = TestTransport::new(settings("&notLeaderBehavior=retry"));
+        let leader = Endpoint {
+            host: "node2".to_string(),
+            port: 2113,
+        };
+        let script = Script::new(vec![Err(not_leader(&leader)), Ok(42)]);
+
+        assert_eq!(run(&transport, script.clone()).await.unwrap(), 42);
+        assert_eq!(script.calls(), 2);
+        assert_eq!(
+            transport.events(),
+            vec![
+                ConnectionEvent::ChannelRequested,
+                ConnectionEvent::NotLeader(leader),
+                ConnectionEvent::ChannelRequested,
+            ]
+        );
+
+        let stats = transport
+            .client()
+            .operation_stats()
+            .get(OperationKind::AppendToStream);
+
+        assert_eq!((stats.retried, stats.not_handled.not_leader), (1, 1));
+        assert_eq!(stats.last_retry_reason, Some(RetryReason::NotLeader));
+    }
+
+    async fn write(
+        transport: &TestTransport,
+        idempotent: bool,
+        script: std::sync::Arc<Script<u32>>,
+    ) -> crate::Result<u32> {
+        let client = transport.client().clone();
+
+        transport
+            .drive(async move {
+                client
+                    .execute_with_retries(OperationKind::AppendToStream, idempotent, |_| {
+                        let script = script.clone();
+
+                        async move { script.respond() }
+                    })
+                    .await
+                    .map(|(a, _)| a)
+            })
+            .await
+    }
+
+    #[tokio::test]
+    async fn write_timeout_is_retried_when_idempotent() {
+        let transport = TestTransport::new(settings("&writeTimeoutRetryLimit=2"));
+        let script = Script::new(vec![Err(write_timeout()), Err(write_timeout()), Ok(3)]);
+
+        assert_eq!(write(&transport, true, script.clone()).await.unwrap(), 3);
+        assert_eq!(script.calls(), 3);
+
+        let stats = transport
+            .client()
+            .operation_stats()
+            .get(OperationKind::AppendToStream);
+
+        assert_eq!((stats.retried, stats.not_handled.write_timeout), (2, 2));
+        assert_eq!(stats.last_retry_reason, Some(RetryReason::WriteTimeout));
+
+        let script = Script::new(vec![Err(write_timeout()); 3]);
+
+        assert!(matches!(
+            write(&transport, true, script.clone()).await,
+            Err(crate::Error::WriteTimeout { .. })
+        ));
+        assert_eq!(script.calls(), 3);
+    }
+
+    #[tokio::test]
+    async fn write_timeout_surfaces_when_not_idempotent() {
+        let transport = TestTransport::new(settings(""));
+        let script = Script::new(vec![Err(write_timeout()), Ok(3)]);
+
+        assert!(matches!(
+            write(&transport, false, script.clone()).await,
+            Err(crate::Error::WriteTimeout {
+                phase: WriteTimeoutPhase::Unknown
+            })
+        ));
+        assert_eq!(script.calls(), 1);
+
+        let stats = transport
+            .client()
+            .operation_stats()
+            .get(OperationKind::AppendToStream);
+
+        assert_eq!((stats.failed, stats.timed_out, stats.retried), (1, 1, 0));
+    }
+
+    #[tokio::test]
+    async fn busy_server_is_retried_after_a_delay() {
+        let transport = TestTransport::new(settings(""));
+        let script = Script::new(vec![Err(server_busy()), Err(server_busy()), Ok(7)]);
+
+        assert_eq!(run(&transport, script.clone()).await.unwrap(), 7);
+        assert_eq!(script.calls(), 3);
+
+        let stats = transport
+            .client()
+            .operation_stats()
+            .get(OperationKind::AppendToStream);
+
+        assert_eq!((stats.retried, stats.not_handled.server_busy), (2, 2));
+    }
+
+    /// Gives up busy server retries after the first one, and retries
+    /// anything else right away.
+    struct OneBusyRetry;
+
+    impl RetryPolicy for OneBusyRetry {
+        fn should_retry(&self, attempt: &RetryAttempt) -> RetryDecision {
+            match attempt.reason() {
+                RetryReason::ServerBusy if attempt.attempt() > 1 => RetryDecision::GiveUp,
+                _ => RetryDecision::Retry(Duration::default()),
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn retry_policy_decides_over_the_settings() {
+        let transport = TestTransport::new(settings("")).with_retry_policy(Arc::new(OneBusyRetry));
+        let script = Script::new(vec![Err(server_busy()), Err(server_busy()), Ok(7)]);
+
+        assert!(matches!(
+            run(&transport, script.clone()).await,
+            Err(crate::Error::ServerBusy)
+        ));
+        assert_eq!(script.calls(), 2);
+
+        // Non-idempotent writes are never resent, whatever the policy says.
+        let script = Script::new(vec![Err(write_timeout()), Ok(3)]);
+
+        assert!(write(&transport, false, script.clone()).await.is_err());
+        assert_eq!(script.calls(), 1);
+
+        let transport = TestTransport::new(settings("&writeTimeoutRetryLimit=0"))
+            .with_retry_policy(Arc::new(OneBusyRetry));
+        let script = Script::new(vec![Err(write_timeout()), Ok(3)]);
+
+        assert_eq!(write(&transport, true, script.clone()).await.unwrap(), 3);
+        assert_eq!(script.calls(), 2);
+    }
+
+    #[derive(Default)]
+    struct Recorder {
+        transitions: std::sync::Mutex<Vec<String>>,
+    }
+
+    impl OperationListener for Recorder {
+        fn on_issued(&self, kind: OperationKind, _: Option<OperationId>) {
+            self.record(format!("issued {:?}", kind));
+        }
+
+        fn on_retried(&self, _: OperationKind, _: Option<OperationId>, reason: RetryReason) {
+            self.record(format!("retried {:?}", reason));
+        }
+
+        fn on_completed(
+            &self,
+            _: OperationKind,
+            _: Option<OperationId>,
+            outcome: OperationOutcome<'_>,
+            _: Duration,
+        ) {
+            match outcome {
+                OperationOutcome::Succeeded => self.record("completed".to_string()),
+                OperationOutcome::Failed(e) => self.record(format!("failed: {}", e)),
+            }
+        }
+    }
+
+    impl Recorder {
+        fn record(&self, transition: String) {
+            self.transitions.lock().unwrap().push(transition);
+        }
+    }
+
+    #[tokio::test]
+    async fn listener_sees_every_transition() {
+        let recorder = Arc::new(Recorder::default());
+        let transport = TestTransport::new(settings("")).with_listener(recorder.clone());
+        let script = Script::new(vec![Err(server_busy()), Ok(7)]);
+
+        assert_eq!(run(&transport, script).await.unwrap(), 7);
+
+        let script = Script::new(vec![Err(node_unavailable())]);
+
+        assert!(run(&transport, script).await.is_err());
+        assert_eq!(
+            *recorder.transitions.lock().unwrap(),
+            vec![
+                "issued AppendToStream",
+                "retried ServerBusy",
+                "completed",
+                "issued AppendToStream",
+                "failed: Server-side error.",
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn unavailable_node_triggers_a_reconnection() {
+        let transport = TestTransport::new(settings(""));
+        let script = Script::new(vec![Err(node_unavailable())]);
+
+        assert!(matches!(
+            run(&transport, script.clone()).await,
+            Err(crate::Error::ServerError(_))
+        ));
+        assert_eq!(script.calls(), 1);
+
+        // The reconnection request is sent right before the operation fails.
+        tokio::task::yield_now().await;
+
+        assert_eq!(
+            transport.events(),
+            vec![
+                ConnectionEvent::ChannelRequested,
+                ConnectionEvent::Reconnect(None),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn operation_fails_past_the_default_deadline() {
+        let transport = TestTransport::new(settings("&defaultDeadline=1000"));
+        let client = transport.client().clone();
+        let result = transport
+            .drive(async move {
+                client
+                    .execute(OperationKind::AppendToStream, |_| {
+                        futures::future::pending::<Result<u32, tonic::Status>>()
+                    })
+                    .await
+            })
+            .await;
+
+        assert!(matches!(result, Err(crate::Error::DeadlineExceeded)));
+
+        let stats = transport
+            .client()
+            .operation_stats()
+            .get(OperationKind::AppendToStream);
+
+        assert_eq!((stats.failed, stats.timed_out), (1, 1));
+    }
+
+    #[tokio::test]
+    async fn reads_are_replayed_once_reconnected() {
+        let transport = TestTransport::new(settings("&reconnectRetryLimit=1"));
+        let script = Script::new(vec![Err(node_unavailable()), Ok(5)]);
+        let client = transport.client().clone();
+        let replayed = script.clone();
+        let result = transport
+            .drive(async move {
+                client
+                    .execute(OperationKind::ReadStream, |_| {
+                        let script = replayed.clone();
+
+                        async move { script.respond() }
+                    })
+                    .await
+            })
+            .await;
+
+        assert_eq!(result.unwrap(), 5);
+        assert_eq!(
+            transport.events(),
+            vec![
+                ConnectionEvent::ChannelRequested,
+                ConnectionEvent::Reconnect(None),
+                ConnectionEvent::ChannelRequested,
+            ]
+        );
+
+        let stats = transport
+            .client()
+            .operation_stats()
+            .get(OperationKind::ReadStream);
+
+        assert_eq!((stats.retried, stats.not_handled.node_unavailable), (1, 1));
+
+        // The server might have written a non-idempotent append already.
+        let script = Script::new(vec![Err(node_unavailable()), Ok(5)]);
+
+        assert!(write(&transport, false, script.clone()).await.is_err());
+        assert_eq!(script.calls(), 1);
+    }
+
+    #[tokio::test]
+    async fn deadline_is_counted_as_timeout() {
+        let transport = TestTransport::new(settings(""));
+        let script = Script::new(vec![Err(deadline_exceeded())]);
+
+        assert!(run(&transport, script).await.is_err());
+
+        let stats = transport
+            .client()
+            .operation_stats()
+            .get(OperationKind::AppendToStream);
+
+        assert_eq!((stats.failed, stats.timed_out, stats.retried), (1, 1, 0));
+    }
+}
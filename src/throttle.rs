@@ -0,0 +1,181 @@
+//! Adaptive throttling applied when the server reports being too busy to
+//! handle operations.
+//!
+//! Every time the server turns down an operation because it's too busy, the
+//! waiting period before re-sending it grows by a fixed step while the number
+//! of operations allowed in flight is halved. Every successful operation
+//! shrinks the waiting period by a step and lets one more operation in
+//! flight, until the throttle is back to its initial state.
+use std::sync::Mutex;
+use std::time::Duration;
+use tokio::sync::Notify;
+
+const DELAY_STEP: Duration = Duration::from_millis(100);
+const MAX_DELAY: Duration = Duration::from_secs(5);
+
+/// Default maximum number of operations allowed in flight when the throttle
+/// is not engaged, see the `maxInFlight` setting.
+pub(crate) const DEFAULT_MAX_WINDOW: usize = 1_024;
+
+/// Maximum number of times an operation is re-sent because the server was too
+/// busy to handle it.
+pub(crate) const MAX_BUSY_RETRIES: usize = 10;
+
+/// Snapshot of the client adaptive throttling state.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ThrottleState {
+    /// Waiting period before re-sending an operation the server was too busy
+    /// to handle.
+    pub delay: Duration,
+
+    /// Maximum number of operations allowed in flight.
+    pub window: usize,
+
+    /// Number of operations currently in flight.
+    pub in_flight: usize,
+}
+
+impl ThrottleState {
+    fn on_busy(&mut self) {
+        self.delay = std::cmp::min(self.delay + DELAY_STEP, MAX_DELAY);
+        self.window = std::cmp::max(self.window / 2, 1);
+    }
+
+    fn on_success(&mut self, max_window: usize) {
+        self.delay = self.delay.saturating_sub(DELAY_STEP);
+        self.window = std::cmp::min(self.window + 1, max_window);
+    }
+}
+
+pub(crate) struct Throttle {
+    max_window: usize,
+    state: Mutex<ThrottleState>,
+    notify: Notify,
+}
+
+impl Throttle {
+    pub(crate) fn new(max_window: usize) -> Self {
+        Throttle {
+            max_window,
+            state: Mutex::new(ThrottleState {
+                delay: Duration::default(),
+                window: max_window,
+                in_flight: 0,
+            }),
+            notify: Notify::new(),
+        }
+    }
+
+    /// Waits until the current window lets another operation in flight.
+    pub(crate) async fn acquire(&self) -> Permit<'_> {
+        loop {
+            let notified = self.notify.notified();
+
+            {
+                let mut state = self.state.lock().unwrap();
+
+                if state.in_flight < state.window {
+                    state.in_flight += 1;
+
+                    return Permit { throttle: self };
+                }
+            }
+
+            notified.await;
+        }
+    }
+
+    /// Records a busy response and returns how long to wait before re-sending
+    /// the operation.
+    pub(crate) fn on_busy(&self) -> Duration {
+        let mut state = self.state.lock().unwrap();
+
+        state.on_busy();
+
+        warn!(
+            "Server too busy, throttling: delay {:?}, window {}",
+            state.delay, state.window
+        );
+
+        state.delay
+    }
+
+    pub(crate) fn on_success(&self) {
+        let mut state = self.state.lock().unwrap();
+        let window = state.window;
+
+        state.on_success(self.max_window);
+
+        if state.window > window {
+            self.notify.notify_waiters();
+        }
+    }
+
+    pub(crate) fn state(&self) -> ThrottleState {
+        *self.state.lock().unwrap()
+    }
+}
+
+/// Keeps an operation accounted as in flight until dropped.
+pub(crate) struct Permit<'a> {
+    throttle: &'a Throttle,
+}
+
+impl Drop for Permit<'_> {
+    fn drop(&mut self) {
+        self.throttle.state.lock().unwrap().in_flight -= 1;
+        self.throttle.notify.notify_waiters();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Throttle, DELAY_STEP, MAX_DELAY};
+    use std::time::Duration;
+
+    #[test]
+    fn busy_increases_delay_and_shrinks_window() {
+        let throttle = Throttle::new(16);
+
+        assert_eq!(throttle.on_busy(), DELAY_STEP);
+        assert_eq!(throttle.on_busy(), DELAY_STEP * 2);
+
+        let state = throttle.state();
+        assert_eq!(state.window, 4);
+        assert_eq!(state.delay, DELAY_STEP * 2);
+    }
+
+    #[test]
+    fn busy_is_bounded() {
+        let throttle = Throttle::new(16);
+
+        for _ in 0..100 {
+            throttle.on_busy();
+        }
+
+        let state = throttle.state();
+        assert_eq!(state.window, 1);
+        assert_eq!(state.delay, MAX_DELAY);
+    }
+
+    #[test]
+    fn success_recovers_gradually() {
+        let throttle = Throttle::new(16);
+
+        throttle.on_busy();
+        throttle.on_busy();
+        throttle.on_success();
+
+        let state = throttle.state();
+        assert_eq!(state.window, 5);
+        assert_eq!(state.delay, DELAY_STEP);
+
+        for _ in 0..100 {
+            throttle.on_success();
+        }
+
+        let state = throttle.state();
+        assert_eq!(state.window, 16);
+        assert_eq!(state.delay, Duration::default());
+    }
+}
@@ -0,0 +1,163 @@
+//! Opt-in detection of a skew between the server clock and the local one,
+//! enabled with the `serverTimeSkewThreshold` setting.
+//!
+//! Every time the client gets a channel to a node, it reads the gossip of that
+//! node and compares the timestamp the node stamped its own member information
+//! with to the local clock. A node refreshes that timestamp every gossip
+//! interval, which bounds the precision of the measure.
+use crate::gossip::{Gossip, MemberInfo};
+use crate::grpc::{create_channel, ClientSettings, CurrentNode};
+use crate::types::{ticks_to_system_time, Endpoint};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime};
+use tokio::sync::watch;
+
+/// Difference between the clock of the node the client is connected to and
+/// the local clock.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ServerTimeSkew {
+    /// The server clock is ahead of the local one.
+    Ahead(Duration),
+    /// The server clock is behind the local one.
+    Behind(Duration),
+}
+
+impl ServerTimeSkew {
+    pub(crate) fn between(server: SystemTime, local: SystemTime) -> Self {
+        match server.duration_since(local) {
+            Ok(ahead) => ServerTimeSkew::Ahead(ahead),
+            Err(err) => ServerTimeSkew::Behind(err.duration()),
+        }
+    }
+
+    /// Absolute difference between the two clocks.
+    pub fn amount(&self) -> Duration {
+        match self {
+            ServerTimeSkew::Ahead(amount) | ServerTimeSkew::Behind(amount) => *amount,
+        }
+    }
+}
+
+/// Last skew measured by a client, shared between its clones.
+pub(crate) type SharedTimeSkew = Arc<Mutex<Option<ServerTimeSkew>>>;
+
+/// Measures the skew every time the connection gets a channel to a node. The
+/// task ends with the connection.
+pub(crate) fn spawn_monitor(
+    settings: Arc<ClientSettings>,
+    mut current: watch::Receiver<Option<CurrentNode>>,
+    skew: SharedTimeSkew,
+) {
+    tokio::spawn(async move {
+        while current.changed().await.is_ok() {
+            let node = current.borrow().clone();
+
+            if let Some(node) = node {
+                if let Some(measured) = measure(&settings, &node.endpoint).await {
+                    if measured.amount() > settings.server_time_skew_threshold {
+                        warn!(
+                            "Clock of node {:?} differs from the local clock: {:?}",
+                            node.endpoint, measured
+                        );
+                    }
+
+                    *skew.lock().unwrap() = Some(measured);
+                }
+            }
+        }
+    });
+}
+
+async fn measure(settings: &ClientSettings, endpoint: &Endpoint) -> Option<ServerTimeSkew> {
+    let channel = match create_channel(settings, endpoint).await {
+        Ok(channel) => channel,
+        Err(err) => {
+            debug!(
+                "Failed to create a channel to measure the time skew: {}",
+                err
+            );
+            return None;
+        }
+    };
+
+    let members =
+        match tokio::time::timeout(settings.gossip_timeout, Gossip::create(channel).read()).await {
+            Ok(Ok(members)) => members,
+            Ok(Err(err)) => {
+                debug!("Failed to read gossip to measure the time skew: {}", err);
+                return None;
+            }
+            Err(_) => {
+                debug!("Gossip request timeout while measuring the time skew");
+                return None;
+            }
+        };
+
+    let server = node_time(&members, endpoint)?;
+
+    Some(ServerTimeSkew::between(server, SystemTime::now()))
+}
+
+/// Timestamp of the node member information. The advertised endpoint of a
+/// node can differ from the one the client knows it by, in which case the
+/// freshest timestamp is the one the node stamped itself.
+fn node_time(members: &[MemberInfo], endpoint: &Endpoint) -> Option<SystemTime> {
+    let member = members
+        .iter()
+        .find(|member| member.http_end_point == *endpoint)
+        .or_else(|| {
+            members
+                .iter()
+                .filter(|member| member.is_alive)
+                .max_by_key(|member| member.time_stamp)
+        })?;
+
+    ticks_to_system_time(member.time_stamp)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{node_time, ServerTimeSkew};
+    use crate::gossip::{MemberInfo, VNodeState};
+    use crate::types::{ticks_to_system_time, Endpoint};
+    use std::time::{Duration, UNIX_EPOCH};
+
+    fn member(port: u32, time_stamp: i64) -> MemberInfo {
+        MemberInfo {
+            instance_id: uuid::Uuid::nil(),
+            time_stamp,
+            state: VNodeState::Follower,
+            is_alive: true,
+            http_end_point: Endpoint {
+                host: "localhost".to_string(),
+                port,
+            },
+        }
+    }
+
+    #[test]
+    fn skew_direction_follows_server_clock() {
+        let local = UNIX_EPOCH + Duration::from_secs(100);
+
+        assert_eq!(
+            ServerTimeSkew::between(local + Duration::from_secs(3), local),
+            ServerTimeSkew::Ahead(Duration::from_secs(3))
+        );
+        assert_eq!(
+            ServerTimeSkew::between(local - Duration::from_secs(5), local).amount(),
+            Duration::from_secs(5)
+        );
+    }
+
+    #[test]
+    fn node_time_prefers_matching_endpoint() {
+        let members = vec![member(1, 10), member(2, 30), member(3, 20)];
+        let endpoint = |port| Endpoint {
+            host: "localhost".to_string(),
+            port,
+        };
+
+        assert_eq!(node_time(&members, &endpoint(3)), ticks_to_system_time(20));
+        assert_eq!(node_time(&members, &endpoint(4)), ticks_to_system_time(30));
+    }
+}
@@ -0,0 +1,284 @@
+//! Cluster topology discovery that can be shared between several clients.
+//!
+//! Without a shared topology, every client runs its own gossip discovery.
+//! When clients share a [`ClusterTopology`], a single gossip poll informs all
+//! of them and each client then picks the node that suits its own
+//! [`NodePreference`].
+use crate::clock::{Clock, SystemClock};
+use crate::gossip::{Gossip, MemberInfo, VNodeState};
+use crate::grpc::{create_channel, ClientSettings};
+use crate::types::Endpoint;
+use crate::NodePreference;
+use rand::rngs::SmallRng;
+use rand::seq::SliceRandom;
+use rand::SeedableRng;
+use std::sync::Arc;
+use std::time::Instant;
+use tokio::sync::{watch, Mutex};
+
+struct Snapshot {
+    polled_at: Instant,
+    members: Vec<MemberInfo>,
+}
+
+struct Inner {
+    settings: ClientSettings,
+    clock: Arc<dyn Clock>,
+    snapshot: Mutex<Option<Snapshot>>,
+    changes: watch::Sender<Vec<MemberInfo>>,
+}
+
+/// Gossip discovery shared between several clients of the same cluster.
+///
+/// The topology is discovered using the seeds (or the DNS domain) of the
+/// settings it's created with. A gossip result is reused by every client for
+/// the `discoveryInterval` setting duration. When the topology changes, each
+/// client decides on its own whether it needs to connect to another node,
+/// based on its `nodePreference` setting.
+///
+/// ```no_run
+/// # use eventstore::{Client, ClientSettings, ClusterTopology};
+/// # async fn f() -> Result<(), Box<dyn std::error::Error>> {
+/// let settings: ClientSettings = "esdb://node1:2113,node2:2113,node3:2113".parse()?;
+/// let topology = ClusterTopology::new(settings.clone());
+///
+/// let writer = Client::with_topology(settings, topology.clone()).await?;
+/// let reader = Client::with_topology(
+///     "esdb://node1:2113,node2:2113,node3:2113?nodePreference=follower".parse()?,
+///     topology,
+/// )
+/// .await?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone)]
+pub struct ClusterTopology {
+    inner: Arc<Inner>,
+}
+
+impl ClusterTopology {
+    pub fn new(settings: ClientSettings) -> Self {
+        ClusterTopology::with_clock(settings, Arc::new(SystemClock))
+    }
+
+    /// Creates a topology that measures the age of its gossip results with
+    /// the given clock.
+    pub fn with_clock(settings: ClientSettings, clock: Arc<dyn Clock>) -> Self {
+        let (changes, _) = watch::channel(Vec::new());
+
+        ClusterTopology {
+            inner: Arc::new(Inner {
+                settings,
+                clock,
+                snapshot: Mutex::new(None),
+                changes,
+            }),
+        }
+    }
+
+    pub(crate) fn subscribe(&self) -> watch::Receiver<Vec<MemberInfo>> {
+        self.inner.changes.subscribe()
+    }
+
+    /// Returns the cluster members, polling gossip only if the last known
+    /// topology is older than the discovery interval. Concurrent callers wait
+    /// for the same poll.
+    pub(crate) async fn members(&self) -> Option<Vec<MemberInfo>> {
+        let mut snapshot = self.inner.snapshot.lock().await;
+
+        if let Some(current) = snapshot.as_ref() {
+            if self.inner.clock.now() - current.polled_at < self.inner.settings.discovery_interval {
+                return Some(current.members.clone());
+            }
+        }
+
+        let candidates = self.candidates(snapshot.as_ref());
+        let members = poll_gossip(&self.inner.settings, candidates).await?;
+        let changed = match snapshot.as_ref() {
+            Some(current) => !same_topology(&current.members, &members),
+            None => true,
+        };
+
+        *snapshot = Some(Snapshot {
+            polled_at: self.inner.clock.now(),
+            members: members.clone(),
+        });
+
+        if changed {
+            debug!("Cluster topology changed: {:?}", members);
+            let _ = self.inner.changes.send(members.clone());
+        }
+
+        Some(members)
+    }
+
+    /// Discards the last known topology so the next lookup polls gossip
+    /// from the seeds again.
+    pub(crate) async fn invalidate(&self) {
+        *self.inner.snapshot.lock().await = None;
+    }
+
+    fn candidates(&self, snapshot: Option<&Snapshot>) -> Vec<Endpoint> {
+        let mut rng = SmallRng::from_entropy();
+        let mut candidates = match snapshot {
+            Some(snapshot) => snapshot
+                .members
+                .iter()
+                .filter(|member| member.is_alive && member.state != VNodeState::Manager)
+                .map(|member| member.http_end_point.clone())
+                .collect(),
+            None => Vec::new(),
+        };
+
+        candidates.shuffle(&mut rng);
+
+        let mut seeds = if self.inner.settings.dns_discover {
+            self.inner.settings.hosts.iter().take(1).cloned().collect()
+        } else {
+            self.inner.settings.hosts.clone()
+        };
+
+        seeds.shuffle(&mut rng);
+        seeds.retain(|seed| !candidates.contains(seed));
+        candidates.extend(seeds);
+
+        candidates
+    }
+}
+
+async fn poll_gossip(
+    settings: &ClientSettings,
+    candidates: Vec<Endpoint>,
+) -> Option<Vec<MemberInfo>> {
+    for candidate in candidates {
+        match create_channel(settings, &candidate).await {
+            Ok(channel) => {
+                let gossip_client = Gossip::create(channel);
+
+                debug!("Calling gossip endpoint on: {:?}", candidate);
+                match tokio::time::timeout(settings.gossip_timeout, gossip_client.read()).await {
+                    Ok(Ok(members)) => return Some(members),
+                    Ok(Err(err)) => debug!(
+                        "Failed to retrieve gossip information from candidate {:?}: {}",
+                        candidate, err
+                    ),
+                    Err(_) => warn!("Gossip request timeout for candidate: {:?}", candidate),
+                }
+            }
+
+            Err(err) => debug!(
+                "Failed to create gRPC channel for candidate {:?}: {}",
+                candidate, err
+            ),
+        }
+    }
+
+    None
+}
+
+fn same_topology(a: &[MemberInfo], b: &[MemberInfo]) -> bool {
+    let key = |member: &MemberInfo| (member.http_end_point.clone(), member.state, member.is_alive);
+
+    let mut a: Vec<_> = a.iter().map(key).collect();
+    let mut b: Vec<_> = b.iter().map(key).collect();
+
+    a.sort_by(|x, y| x.0.cmp(&y.0));
+    b.sort_by(|x, y| x.0.cmp(&y.0));
+
+    a == b
+}
+
+/// Checks if the node a client is connected to still suits its preference
+/// given the latest topology.
+pub(crate) fn node_suits_preference(
+    preference: NodePreference,
+    current: &Endpoint,
+    members: &[MemberInfo],
+) -> bool {
+    let member = match members
+        .iter()
+        .find(|member| member.http_end_point == *current)
+    {
+        Some(member) => member,
+        None => return false,
+    };
+
+    if !member.is_alive
+        || matches!(
+            member.state,
+            VNodeState::Manager | VNodeState::ShuttingDown | VNodeState::Shutdown
+        )
+    {
+        return false;
+    }
+
+    let wanted = match preference {
+        NodePreference::Random => return true,
+        NodePreference::Leader => VNodeState::Leader,
+        NodePreference::Follower => VNodeState::Follower,
+        NodePreference::ReadOnlyReplica => VNodeState::ReadOnlyReplica,
+    };
+
+    member.state == wanted
+        || !members
+            .iter()
+            .any(|member| member.is_alive && member.state == wanted)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::node_suits_preference;
+    use crate::gossip::{MemberInfo, VNodeState};
+    use crate::types::Endpoint;
+    use crate::NodePreference;
+
+    fn member(port: u32, state: VNodeState) -> MemberInfo {
+        MemberInfo {
+            instance_id: uuid::Uuid::nil(),
+            time_stamp: 0,
+            state,
+            is_alive: true,
+            http_end_point: Endpoint {
+                host: "localhost".to_string(),
+                port,
+            },
+        }
+    }
+
+    #[test]
+    fn leader_change_requires_reconnection() {
+        let current = member(1, VNodeState::Leader).http_end_point;
+        let members = vec![
+            member(1, VNodeState::Follower),
+            member(2, VNodeState::Leader),
+        ];
+
+        assert!(!node_suits_preference(
+            NodePreference::Leader,
+            &current,
+            &members
+        ));
+        assert!(node_suits_preference(
+            NodePreference::Follower,
+            &current,
+            &members
+        ));
+        assert!(node_suits_preference(
+            NodePreference::Random,
+            &current,
+            &members
+        ));
+    }
+
+    #[test]
+    fn missing_node_requires_reconnection() {
+        let current = member(3, VNodeState::Follower).http_end_point;
+        let members = vec![member(1, VNodeState::Follower)];
+
+        assert!(!node_suits_preference(
+            NodePreference::Random,
+            &current,
+            &members
+        ));
+    }
+}
@@ -1,8 +1,9 @@
-#![allow(clippy::large_enum_variant)]
+// `Default` is implemented by hand on enums, deriving it needs Rust 1.62.
+#![allow(clippy::large_enum_variant, clippy::derivable_impls)]
 //! Common types used across the library.
 use std::cmp::Ordering;
 use std::collections::HashMap;
-use std::time::Duration;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
 use crate::private::Sealed;
 use async_trait::async_trait;
@@ -166,17 +167,259 @@ impl Ord for Position {
     }
 }
 
+/// Current version of the [`Checkpoint`] string format.
+const CHECKPOINT_FORMAT_VERSION: &str = "v1";
+
+/// A subscription bookmark meant to be durably stored by the consumer and
+/// used later on to resume reading or subscribing.
+///
+/// A checkpoint renders as a compact, versioned string, for example
+/// `v1:1024:1024` or `v1:1024:1024:42` when it also holds a stream revision.
+///
+/// ```
+/// # use eventstore::{Checkpoint, Position};
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let checkpoint = Checkpoint::new(Position { commit: 1024, prepare: 1024 }).revision(42);
+/// let stored = checkpoint.to_string();
+///
+/// assert_eq!(stored.parse::<Checkpoint>()?, checkpoint);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Checkpoint {
+    /// Position in the transaction file.
+    pub position: Position,
+
+    /// Revision of the event in its stream, if known.
+    pub revision: Option<u64>,
+}
+
+impl Checkpoint {
+    /// Creates a checkpoint pointing to the given position.
+    pub fn new(position: Position) -> Self {
+        Checkpoint {
+            position,
+            revision: None,
+        }
+    }
+
+    /// Also records the stream revision of the event.
+    pub fn revision(self, revision: u64) -> Self {
+        Checkpoint {
+            revision: Some(revision),
+            ..self
+        }
+    }
+
+    /// Creates a checkpoint pointing to the original event of the given
+    /// resolved event.
+    pub fn from_event(event: &ResolvedEvent) -> Self {
+        let event = event.get_original_event();
+
+        Checkpoint::new(event.position).revision(event.revision)
+    }
+}
+
+impl From<Position> for Checkpoint {
+    fn from(position: Position) -> Self {
+        Checkpoint::new(position)
+    }
+}
+
+impl std::fmt::Display for Checkpoint {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}:{}:{}",
+            CHECKPOINT_FORMAT_VERSION, self.position.commit, self.position.prepare
+        )?;
+
+        if let Some(revision) = self.revision {
+            write!(f, ":{}", revision)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Error returned when parsing a [`Checkpoint`] string.
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum CheckpointParseError {
+    #[error("Unsupported checkpoint format version: {0}")]
+    UnsupportedVersion(String),
+    #[error("Malformed checkpoint: {0}")]
+    Malformed(String),
+}
+
+impl std::str::FromStr for Checkpoint {
+    type Err = CheckpointParseError;
+
+    fn from_str(input: &str) -> std::result::Result<Self, Self::Err> {
+        let mut parts = input.split(':');
+        let malformed = || CheckpointParseError::Malformed(input.to_string());
+
+        match parts.next() {
+            Some(CHECKPOINT_FORMAT_VERSION) => {}
+            Some(version) if !version.is_empty() => {
+                return Err(CheckpointParseError::UnsupportedVersion(
+                    version.to_string(),
+                ))
+            }
+            _ => return Err(malformed()),
+        }
+
+        let mut next_number = || -> std::result::Result<Option<u64>, CheckpointParseError> {
+            parts
+                .next()
+                .map(|part| part.parse().map_err(|_| malformed()))
+                .transpose()
+        };
+
+        let commit = next_number()?.ok_or_else(malformed)?;
+        let prepare = next_number()?.ok_or_else(malformed)?;
+        let revision = next_number()?;
+
+        if parts.next().is_some() {
+            return Err(malformed());
+        }
+
+        Ok(Checkpoint {
+            position: Position { commit, prepare },
+            revision,
+        })
+    }
+}
+
+#[cfg(test)]
+mod checkpoint_tests {
+    use super::{Checkpoint, CheckpointParseError, Position};
+
+    #[test]
+    fn checkpoint_round_trip() {
+        let position = Position {
+            commit: 42,
+            prepare: 41,
+        };
+
+        for checkpoint in [
+            Checkpoint::new(position),
+            Checkpoint::new(position).revision(7),
+        ] {
+            assert_eq!(checkpoint.to_string().parse(), Ok(checkpoint));
+        }
+
+        assert_eq!(Checkpoint::new(position).to_string(), "v1:42:41");
+    }
+
+    #[test]
+    fn checkpoint_rejects_invalid_input() {
+        assert_eq!(
+            "v2:42:41".parse::<Checkpoint>(),
+            Err(CheckpointParseError::UnsupportedVersion("v2".to_string()))
+        );
+
+        for input in ["", "v1", "v1:42", "v1:42:foo", "v1:42:41:7:9"] {
+            assert_eq!(
+                input.parse::<Checkpoint>(),
+                Err(CheckpointParseError::Malformed(input.to_string()))
+            );
+        }
+    }
+}
+
+/// Returned after deleting a stream.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct DeleteResult {
+    /// `Position` of the delete in the transaction file, if the server gave
+    /// one.
+    pub position: Option<Position>,
+}
+
 /// Returned after writing to a stream.
-#[derive(Debug)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
 pub struct WriteResult {
-    /// Next expected version of the stream.
+    /// Next expected version of the stream, to use with
+    /// `ExpectedRevision::Exact` on the following write.
     pub next_expected_version: u64,
 
-    /// `Position` of the write.
+    /// `Position` of the write in the transaction file.
     pub position: Position,
+
+    /// Retries the write went through before succeeding.
+    pub retries: Retries,
+}
+
+/// Why an operation was sent to the server again.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum RetryReason {
+    /// The node wasn't the leader. The operation was sent again to the
+    /// advertised leader.
+    NotLeader,
+    /// The server was too busy to handle the operation.
+    ServerBusy,
+    /// The server timed out writing the events of an idempotent append.
+    WriteTimeout,
+    /// The node went unavailable. The operation was sent again once the
+    /// client reconnected.
+    NodeUnavailable,
+}
+
+/// Phase of a write the server timed out in.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum WriteTimeoutPhase {
+    /// The events weren't written to the transaction log in time.
+    Prepare,
+    /// The write wasn't acknowledged by a quorum of nodes in time.
+    Commit,
+    /// The follower didn't hear back from the leader it forwarded the write
+    /// to in time.
+    Forward,
+    /// The server didn't say which phase timed out.
+    Unknown,
+}
+
+impl std::fmt::Display for WriteTimeoutPhase {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            WriteTimeoutPhase::Prepare => write!(f, "prepare"),
+            WriteTimeoutPhase::Commit => write!(f, "commit"),
+            WriteTimeoutPhase::Forward => write!(f, "forward"),
+            WriteTimeoutPhase::Unknown => write!(f, "unknown"),
+        }
+    }
+}
+
+/// Retries an operation went through, useful to correlate latency spikes
+/// with leader elections or overloaded nodes.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub struct Retries {
+    /// Number of times the operation was sent again.
+    pub count: usize,
+
+    /// Reason of the last retry, `None` if the operation wasn't retried.
+    pub last_reason: Option<RetryReason>,
+}
+
+impl WriteResult {
+    /// Returns a checkpoint pointing to the last event of the write.
+    pub fn checkpoint(&self) -> Checkpoint {
+        Checkpoint::new(self.position).revision(self.next_expected_version)
+    }
+}
+
+/// Returned by [`Client::append_or_create`](crate::Client::append_or_create).
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct AppendOrCreateResult {
+    /// Outcome of the write.
+    pub result: WriteResult,
+
+    /// `true` if the write created the stream, `false` if the stream already
+    /// existed and the events were appended to it.
+    pub created: bool,
 }
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum StreamPosition<A> {
     Start,
     End,
@@ -206,7 +449,7 @@ pub struct ReadEventResult {
 }
 
 /// Represents a previously written event.
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct RecordedEvent {
     /// The event stream that events belongs to.
     pub stream_id: String,
@@ -244,10 +487,78 @@ impl RecordedEvent {
     {
         serde_json::from_slice(&self.data[..])
     }
+
+    /// The user-defined metadata of this event, as written.
+    pub fn as_metadata_bytes(&self) -> Bytes {
+        self.custom_metadata.clone()
+    }
+
+    /// Content type of the user-defined metadata, `None` if the event has
+    /// none. The protocol only carries the content type of the payload, so
+    /// this one is inferred from the metadata itself: `application/json` if
+    /// it's valid JSON, `application/octet-stream` otherwise.
+    pub fn metadata_content_type(&self) -> Option<&'static str> {
+        if self.custom_metadata.is_empty() {
+            return None;
+        }
+
+        let is_json =
+            serde_json::from_slice::<serde::de::IgnoredAny>(&self.custom_metadata).is_ok();
+
+        if is_json {
+            Some("application/json")
+        } else {
+            Some("application/octet-stream")
+        }
+    }
+
+    /// When the server wrote this event. The server always assigns that
+    /// timestamp at append time, see [`RecordedEvent::original_created`] for
+    /// events imported with their original timestamp.
+    pub fn created(&self) -> Option<SystemTime> {
+        let ticks = self.metadata.get("created")?.parse().ok()?;
+
+        ticks_to_system_time(ticks)
+    }
+
+    /// The timestamp supplied with [`EventData::created`] when this event was
+    /// written. Falls back to [`RecordedEvent::created`] if the event was
+    /// written without one.
+    pub fn original_created(&self) -> Option<SystemTime> {
+        let original = serde_json::from_slice::<serde_json::Value>(&self.custom_metadata)
+            .ok()
+            .and_then(|value| value.get(ORIGINAL_CREATED_METADATA_KEY)?.as_i64())
+            .and_then(ticks_to_system_time);
+
+        original.or_else(|| self.created())
+    }
+}
+
+/// Custom metadata key under which [`EventData::created`] timestamps are
+/// stored.
+pub const ORIGINAL_CREATED_METADATA_KEY: &str = "$originalCreated";
+
+// EventStoreDB expresses timestamps as 100-nanosecond ticks since the Unix
+// epoch. Returns `None` for ticks out of the range of `SystemTime`.
+pub(crate) fn ticks_to_system_time(ticks: i64) -> Option<SystemTime> {
+    let nanos = Duration::from_nanos(ticks.unsigned_abs().checked_mul(100)?);
+
+    if ticks >= 0 {
+        UNIX_EPOCH.checked_add(nanos)
+    } else {
+        UNIX_EPOCH.checked_sub(nanos)
+    }
+}
+
+fn system_time_to_ticks(time: SystemTime) -> i64 {
+    match time.duration_since(UNIX_EPOCH) {
+        Ok(elapsed) => (elapsed.as_nanos() / 100) as i64,
+        Err(e) => -((e.duration().as_nanos() / 100) as i64),
+    }
 }
 
 /// A structure representing a single event or an resolved link event.
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct ResolvedEvent {
     /// The event, or the resolved link event if this `ResolvedEvent` is a link
     /// event.
@@ -281,6 +592,39 @@ impl ResolvedEvent {
     pub fn get_original_stream_id(&self) -> &str {
         &self.get_original_event().stream_id
     }
+
+    /// Returns the stream id of the link if this `ResolvedEvent` was resolved
+    /// from a link, otherwise the stream id of the event itself. That's the
+    /// stream to key on when tracking the progress of a read or subscription.
+    pub fn original_stream_id(&self) -> &str {
+        self.get_original_stream_id()
+    }
+
+    /// Returns the revision of the link if this `ResolvedEvent` was resolved
+    /// from a link, otherwise the revision of the event itself.
+    pub fn original_event_number(&self) -> u64 {
+        self.get_original_event().revision
+    }
+}
+
+/// Kinds of events the client skips when reading or subscribing to `$all`.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct EventExclusion {
+    pub(crate) system: bool,
+    pub(crate) metadata: bool,
+}
+
+impl EventExclusion {
+    pub(crate) fn excludes(&self, event: &ResolvedEvent) -> bool {
+        let event = event.get_original_event();
+        let metadata = event.event_type == "$metadata" || event.stream_id.starts_with("$$");
+
+        if metadata {
+            return self.metadata;
+        }
+
+        self.system && (event.event_type.starts_with('$') || event.stream_id.starts_with('$'))
+    }
 }
 
 /// Represents stream metadata as a series of properties for system data and
@@ -307,7 +651,7 @@ pub struct VersionedMetadata {
 
 /// Represents the direction of read operation (both from '$all' and a regular
 /// stream).
-#[derive(Copy, Clone, Debug)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
 pub(crate) enum ReadDirection {
     Forward,
     Backward,
@@ -337,6 +681,7 @@ pub struct EventData {
     pub(crate) id_opt: Option<Uuid>,
     pub(crate) metadata: HashMap<String, String>,
     pub(crate) custom_metadata: Option<Bytes>,
+    pub(crate) created: Option<SystemTime>,
 }
 
 impl EventData {
@@ -356,6 +701,7 @@ impl EventData {
             id_opt: None,
             metadata,
             custom_metadata: None,
+            created: None,
         })
     }
 
@@ -376,9 +722,22 @@ impl EventData {
             id_opt: None,
             metadata,
             custom_metadata: None,
+            created: None,
         }
     }
 
+    /// Creates a link event pointing to the event `event_number` of
+    /// `stream`. A read with `resolve_link_tos` set resolves the link to
+    /// that event, the link itself being kept in [`ResolvedEvent::link`].
+    pub fn link_to<S>(event_number: u64, stream: S) -> Self
+    where
+        S: AsRef<str>,
+    {
+        let payload = format!("{}@{}", event_number, stream.as_ref());
+
+        EventData::binary("$>", Bytes::from(payload))
+    }
+
     /// Set an id to this event. By default, the id will be generated
     pub fn id(self, value: Uuid) -> Self {
         EventData {
@@ -399,13 +758,56 @@ impl EventData {
         })
     }
 
-    /// Assigns a raw binary metadata to this event.
+    /// Assigns a raw binary metadata to this event, Avro for instance. The
+    /// metadata is stored as is, independently of the payload content type.
     pub fn metadata(self, payload: Bytes) -> EventData {
         EventData {
             custom_metadata: Some(payload),
             ..self
         }
     }
+
+    /// Records when this event originally happened, which is useful when
+    /// importing historical events.
+    ///
+    /// The protocol doesn't let clients choose the `created` timestamp of an
+    /// event: the server keeps assigning it at append time. Instead, the
+    /// timestamp is stored in the event custom metadata, under the
+    /// [`ORIGINAL_CREATED_METADATA_KEY`] key, as ticks since the Unix epoch.
+    /// It can be read back with [`RecordedEvent::original_created`].
+    ///
+    /// This requires the custom metadata to be either empty or a JSON object.
+    /// If the custom metadata is anything else, the timestamp is not written.
+    pub fn created(self, timestamp: SystemTime) -> EventData {
+        EventData {
+            created: Some(timestamp),
+            ..self
+        }
+    }
+
+    /// Returns the custom metadata as it will be sent to the server.
+    pub(crate) fn encoded_custom_metadata(&self) -> Option<Bytes> {
+        let created = match self.created {
+            Some(created) => system_time_to_ticks(created),
+            None => return self.custom_metadata.clone(),
+        };
+
+        let mut metadata = match self.custom_metadata.as_ref() {
+            None => serde_json::Map::new(),
+            Some(bytes) if bytes.is_empty() => serde_json::Map::new(),
+            Some(bytes) => match serde_json::from_slice(bytes) {
+                Ok(serde_json::Value::Object(metadata)) => metadata,
+                _ => {
+                    warn!("Custom metadata is not a JSON object, original created timestamp is not written");
+                    return self.custom_metadata.clone();
+                }
+            },
+        };
+
+        metadata.insert(ORIGINAL_CREATED_METADATA_KEY.to_string(), created.into());
+
+        serde_json::to_vec(&metadata).ok().map(Bytes::from)
+    }
 }
 
 /// Used to facilitate the creation of a stream's metadata.
@@ -437,7 +839,8 @@ impl StreamMetadataBuilder {
 
     /// Sets a sliding window based on dates. When data reaches a certain age
     /// it disappears automatically from the stream and is considered
-    /// eligible for scavenging.
+    /// eligible for scavenging. The server works in seconds, the fraction of
+    /// a second is dropped.
     pub fn max_age(self, value: Duration) -> StreamMetadataBuilder {
         StreamMetadataBuilder {
             max_age: Some(value),
@@ -457,6 +860,7 @@ impl StreamMetadataBuilder {
     /// are infinitely cacheable but the head by default will not cache. It
     /// may be preferable in some situations to set a small amount of caching
     /// on the head to allow intermediaries to handle polls (say 10 seconds).
+    /// The server works in seconds, the fraction of a second is dropped.
     pub fn cache_control(self, value: Duration) -> StreamMetadataBuilder {
         StreamMetadataBuilder {
             cache_control: Some(value),
@@ -562,8 +966,9 @@ fn serialize_duration<S>(
 where
     S: Serializer,
 {
-    if let Some(ref duration) = src.as_ref() {
-        serializer.serialize_u64(duration.as_millis() as u64)
+    // The server expects the durations of the metadata in seconds.
+    if let Some(duration) = src.as_ref() {
+        serializer.serialize_u64(duration.as_secs())
     } else {
         serializer.serialize_none()
     }
@@ -604,7 +1009,7 @@ impl<'de> Visitor<'de> for DurationVisitor {
     type Value = Option<Duration>;
 
     fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
-        write!(formatter, "a time duration in milliseconds")
+        write!(formatter, "a time duration in seconds")
     }
 
     fn visit_none<E>(self) -> std::result::Result<Self::Value, E> {
@@ -612,7 +1017,7 @@ impl<'de> Visitor<'de> for DurationVisitor {
     }
 
     fn visit_u64<E>(self, value: u64) -> std::result::Result<Self::Value, E> {
-        Ok(Some(Duration::from_millis(value)))
+        Ok(Some(Duration::from_secs(value)))
     }
 }
 
@@ -801,7 +1206,7 @@ fn serialize_roles<S>(
 where
     S: Serializer,
 {
-    if let Some(ref roles) = src.as_ref() {
+    if let Some(roles) = src.as_ref() {
         if roles.len() == 1 {
             serializer.serialize_str(roles.first().unwrap().as_str())
         } else {
@@ -862,109 +1267,507 @@ where
     deserializer.deserialize_any(RolesVisitor)
 }
 
-#[cfg(test)]
-mod metadata_tests {
-    use std::time::Duration;
+impl StreamAcl {
+    /// Role every user belongs to, including anonymous ones.
+    pub const ALL: &'static str = "$all";
 
-    use super::{Acl, StreamAclBuilder, StreamMetadata, StreamMetadataBuilder};
+    /// Role of the administrators of the server.
+    pub const ADMINS: &'static str = "$admins";
 
-    #[test]
-    fn isomorphic_1() -> Result<(), Box<dyn std::error::Error>> {
-        let acl = StreamAclBuilder::new()
-            .add_read_roles("admin")
-            .add_write_roles("admin")
-            .add_delete_roles("admin")
-            .add_meta_read_roles("admin")
-            .add_meta_write_roles("admin")
-            .build();
+    fn with_role(role: &str) -> Self {
+        let roles = Some(vec![role.to_string()]);
 
-        let expected = StreamMetadataBuilder::new()
-            .max_age(Duration::from_secs(2))
-            .cache_control(Duration::from_secs(15))
-            .truncate_before(1)
-            .max_count(12)
-            .acl(Acl::Stream(acl))
-            .insert_custom_property("foo", "bar")
-            .build();
+        StreamAcl {
+            read_roles: roles.clone(),
+            write_roles: roles.clone(),
+            delete_roles: roles.clone(),
+            meta_read_roles: roles.clone(),
+            meta_write_roles: roles,
+        }
+    }
 
-        let actual: StreamMetadata =
-            serde_json::from_slice(serde_json::to_vec(&expected)?.as_slice())?;
+    /// Access control list the server applies by default to user streams.
+    pub fn default_user_stream_acl() -> Self {
+        StreamAcl::with_role(StreamAcl::ALL)
+    }
 
-        assert_eq!(expected, actual);
+    /// Access control list the server applies by default to system streams,
+    /// those starting with `$`.
+    pub fn default_system_stream_acl() -> Self {
+        StreamAcl::with_role(StreamAcl::ADMINS)
+    }
 
-        Ok(())
+    /// Fills the role lists that are not defined with the ones of `fallback`.
+    pub(crate) fn or(self, fallback: &StreamAcl) -> Self {
+        StreamAcl {
+            read_roles: self.read_roles.or_else(|| fallback.read_roles.clone()),
+            write_roles: self.write_roles.or_else(|| fallback.write_roles.clone()),
+            delete_roles: self.delete_roles.or_else(|| fallback.delete_roles.clone()),
+            meta_read_roles: self
+                .meta_read_roles
+                .or_else(|| fallback.meta_read_roles.clone()),
+            meta_write_roles: self
+                .meta_write_roles
+                .or_else(|| fallback.meta_write_roles.clone()),
+        }
     }
 
-    #[test]
-    fn isomorphic_2() -> Result<(), Box<dyn std::error::Error>> {
-        let expected = StreamMetadataBuilder::new()
-            .max_age(Duration::from_secs(2))
-            .cache_control(Duration::from_secs(15))
-            .truncate_before(1)
-            .max_count(12)
-            .acl(Acl::UserStream)
-            .insert_custom_property("foo", "bar")
-            .build();
+    /// Checks if the given credentials are allowed to read the stream.
+    ///
+    /// The client doesn't know which groups a user belongs to, so only the
+    /// `$all` role, the user's own login and the `$admins` role for the
+    /// default `admin` user are matched. A user granted access through one of
+    /// its groups is reported as not allowed.
+    pub fn can_read(&self, credentials: Option<&Credentials>) -> bool {
+        roles_allow(self.read_roles.as_deref(), credentials)
+    }
 
-        let actual: StreamMetadata =
-            serde_json::from_slice(serde_json::to_vec(&expected)?.as_slice())?;
+    /// Checks if the given credentials are allowed to write to the stream.
+    /// Group membership is not resolved, see [`StreamAcl::can_read`].
+    pub fn can_write(&self, credentials: Option<&Credentials>) -> bool {
+        roles_allow(self.write_roles.as_deref(), credentials)
+    }
+}
 
-        assert_eq!(expected, actual);
+/// Login of the administrator account every server comes with, the only
+/// known member of the `$admins` group.
+const DEFAULT_ADMIN_LOGIN: &str = "admin";
 
-        Ok(())
-    }
+fn roles_allow(roles: Option<&[String]>, credentials: Option<&Credentials>) -> bool {
+    let login = credentials.and_then(|creds| std::str::from_utf8(&creds.login).ok());
 
-    #[test]
-    fn isomorphic_3() -> Result<(), Box<dyn std::error::Error>> {
-        let expected = StreamMetadataBuilder::new()
-            .max_age(Duration::from_secs(2))
-            .cache_control(Duration::from_secs(15))
-            .truncate_before(1)
-            .max_count(12)
-            .acl(Acl::SystemStream)
-            .insert_custom_property("foo", "bar")
-            .build();
+    roles.unwrap_or_default().iter().any(|role| {
+        role == StreamAcl::ALL
+            || (login.is_some() && Some(role.as_str()) == login)
+            || (role == StreamAcl::ADMINS && login == Some(DEFAULT_ADMIN_LOGIN))
+    })
+}
 
-        let actual: StreamMetadata =
-            serde_json::from_slice(serde_json::to_vec(&expected)?.as_slice())?;
+/// Server-wide settings stored in the `$settings` stream, holding the access
+/// control lists applied to streams that don't define their own.
+#[derive(Default, Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct SystemSettings {
+    /// Default access control list of user streams.
+    #[serde(
+        rename = "$userStreamAcl",
+        skip_serializing_if = "Option::is_none",
+        default
+    )]
+    pub user_stream_acl: Option<StreamAcl>,
 
-        assert_eq!(expected, actual);
+    /// Default access control list of system streams, those starting with `$`.
+    #[serde(
+        rename = "$systemStreamAcl",
+        skip_serializing_if = "Option::is_none",
+        default
+    )]
+    pub system_stream_acl: Option<StreamAcl>,
+}
 
-        Ok(())
+impl SystemSettings {
+    pub fn new() -> Self {
+        SystemSettings::default()
     }
 
-    #[test]
-    fn metadata_spec() -> Result<(), Box<dyn std::error::Error>> {
-        let content = r#"
-        {
-            "$maxCount": 12,
-            "$maxAge": 2000,
-            "$tb": 1,
-            "$cacheControl": 15000,
-            "$acl": {
-                "$r": "admin",
-                "$w": "admin",
-                "$d": "admin",
-                "$mr": "admin",
-                "$mw": "admin"
-            },
-            "foo": "bar"
+    /// Sets the default access control list of user streams.
+    pub fn user_stream_acl(self, acl: StreamAcl) -> Self {
+        SystemSettings {
+            user_stream_acl: Some(acl),
+            ..self
         }
-        "#;
+    }
 
-        let acl = StreamAclBuilder::new()
-            .add_read_roles("admin")
-            .add_write_roles("admin")
-            .add_delete_roles("admin")
-            .add_meta_read_roles("admin")
-            .add_meta_write_roles("admin")
-            .build();
+    /// Sets the default access control list of system streams.
+    pub fn system_stream_acl(self, acl: StreamAcl) -> Self {
+        SystemSettings {
+            system_stream_acl: Some(acl),
+            ..self
+        }
+    }
 
-        let expected = StreamMetadataBuilder::new()
-            .max_age(Duration::from_secs(2))
-            .cache_control(Duration::from_secs(15))
-            .truncate_before(1)
-            .max_count(12)
+    /// Resolves the effective access control list of a stream given its
+    /// metadata.
+    pub(crate) fn effective_acl(&self, stream: &str, acl: Option<Acl>) -> StreamAcl {
+        let user = self
+            .user_stream_acl
+            .clone()
+            .unwrap_or_default()
+            .or(&StreamAcl::default_user_stream_acl());
+        let system = self
+            .system_stream_acl
+            .clone()
+            .unwrap_or_default()
+            .or(&StreamAcl::default_system_stream_acl());
+        let default = if stream.starts_with('$') {
+            system.clone()
+        } else {
+            user.clone()
+        };
+
+        match acl {
+            Some(Acl::Stream(acl)) => acl.or(&default),
+            Some(Acl::UserStream) => user,
+            Some(Acl::SystemStream) => system,
+            None => default,
+        }
+    }
+}
+
+#[cfg(test)]
+mod resolved_event_tests {
+    use super::{Position, RecordedEvent, ResolvedEvent};
+
+    fn recorded(stream_id: &str, revision: u64) -> RecordedEvent {
+        RecordedEvent {
+            stream_id: stream_id.to_string(),
+            id: uuid::Uuid::new_v4(),
+            revision,
+            event_type: "foo".to_string(),
+            data: Default::default(),
+            metadata: Default::default(),
+            custom_metadata: Default::default(),
+            is_json: false,
+            position: Position::start(),
+        }
+    }
+
+    #[test]
+    fn metadata_content_type_is_inferred() {
+        let with_metadata = |custom_metadata: &'static [u8]| RecordedEvent {
+            custom_metadata: bytes::Bytes::from_static(custom_metadata),
+            ..recorded("user-1", 0)
+        };
+
+        assert_eq!(recorded("user-1", 0).metadata_content_type(), None);
+        assert_eq!(
+            with_metadata(br#"{"source":"import"}"#).metadata_content_type(),
+            Some("application/json")
+        );
+
+        let avro = with_metadata(&[0x4f, 0x62, 0x6a, 0x01, 0xff]);
+
+        assert_eq!(
+            avro.metadata_content_type(),
+            Some("application/octet-stream")
+        );
+        assert_eq!(
+            &avro.as_metadata_bytes()[..],
+            &[0x4f, 0x62, 0x6a, 0x01, 0xff]
+        );
+    }
+
+    #[test]
+    fn link_coordinates_when_resolved() {
+        let event = ResolvedEvent {
+            event: Some(recorded("user-1", 3)),
+            link: Some(recorded("$ce-user", 42)),
+            commit_position: None,
+        };
+
+        assert!(event.is_resolved());
+        assert_eq!(event.original_stream_id(), "$ce-user");
+        assert_eq!(event.original_event_number(), 42);
+    }
+
+    #[test]
+    fn exclusion_of_system_and_metadata_events() {
+        use super::EventExclusion;
+
+        let event = |stream_id: &str, event_type: &str| ResolvedEvent {
+            event: Some(RecordedEvent {
+                event_type: event_type.to_string(),
+                ..recorded(stream_id, 0)
+            }),
+            link: None,
+            commit_position: None,
+        };
+
+        let user = event("user-1", "created");
+        let system = event("$stats-127.0.0.1:2113", "$statsCollected");
+        let metadata = event("$$user-1", "$metadata");
+
+        let exclusion = EventExclusion {
+            system: true,
+            metadata: false,
+        };
+        assert!(!exclusion.excludes(&user));
+        assert!(exclusion.excludes(&system));
+        assert!(!exclusion.excludes(&metadata));
+
+        let exclusion = EventExclusion {
+            system: false,
+            metadata: true,
+        };
+        assert!(!exclusion.excludes(&system));
+        assert!(exclusion.excludes(&metadata));
+    }
+
+    #[test]
+    fn event_coordinates_when_direct() {
+        let event = ResolvedEvent {
+            event: Some(recorded("user-1", 3)),
+            link: None,
+            commit_position: None,
+        };
+
+        assert!(!event.is_resolved());
+        assert_eq!(event.original_stream_id(), "user-1");
+        assert_eq!(event.original_event_number(), 3);
+    }
+}
+
+#[cfg(test)]
+mod created_tests {
+    use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+    use bytes::Bytes;
+
+    use super::{ticks_to_system_time, EventData, ORIGINAL_CREATED_METADATA_KEY};
+
+    #[test]
+    fn created_merged_into_json_metadata() -> Result<(), Box<dyn std::error::Error>> {
+        let created = UNIX_EPOCH + Duration::from_secs(1_600_000_000);
+        let event = EventData::binary("foo", Bytes::new())
+            .metadata_as_json(serde_json::json!({ "foo": "bar" }))?
+            .created(created);
+
+        let metadata: serde_json::Value =
+            serde_json::from_slice(&event.encoded_custom_metadata().unwrap())?;
+
+        assert_eq!(metadata["foo"], "bar");
+        assert_eq!(
+            metadata[ORIGINAL_CREATED_METADATA_KEY],
+            16_000_000_000_000_000i64
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn created_without_metadata() -> Result<(), Box<dyn std::error::Error>> {
+        let event = EventData::binary("foo", Bytes::new()).created(SystemTime::now());
+        let metadata: serde_json::Value =
+            serde_json::from_slice(&event.encoded_custom_metadata().unwrap())?;
+
+        assert!(metadata[ORIGINAL_CREATED_METADATA_KEY].is_i64());
+
+        Ok(())
+    }
+
+    #[test]
+    fn created_ignored_with_binary_metadata() {
+        let binary = Bytes::from_static(&[0xde, 0xad, 0xbe, 0xef]);
+        let event = EventData::binary("foo", Bytes::new())
+            .metadata(binary.clone())
+            .created(SystemTime::now());
+
+        assert_eq!(event.encoded_custom_metadata(), Some(binary));
+    }
+    #[test]
+    fn out_of_range_ticks_have_no_time() {
+        assert_eq!(
+            ticks_to_system_time(-10_000_000),
+            Some(UNIX_EPOCH - Duration::from_secs(1))
+        );
+        assert_eq!(ticks_to_system_time(i64::MAX), None);
+        assert_eq!(ticks_to_system_time(i64::MIN), None);
+    }
+}
+
+#[cfg(test)]
+mod link_tests {
+    use super::EventData;
+
+    #[test]
+    fn link_payload_points_to_the_event() {
+        let link = EventData::link_to(42, "orders-1");
+
+        assert_eq!(link.payload.as_ref(), b"42@orders-1");
+        assert_eq!(link.metadata["type"], "$>");
+    }
+}
+
+#[cfg(test)]
+mod acl_tests {
+    use super::{Acl, Credentials, StreamAcl, StreamAclBuilder, SystemSettings};
+
+    #[test]
+    fn missing_metadata_resolves_to_defaults() {
+        let settings = SystemSettings::default();
+
+        assert_eq!(
+            settings.effective_acl("foo", None),
+            StreamAcl::default_user_stream_acl()
+        );
+        assert_eq!(
+            settings.effective_acl("$foo", None),
+            StreamAcl::default_system_stream_acl()
+        );
+    }
+
+    #[test]
+    fn stream_acl_is_completed_with_defaults() {
+        let settings: SystemSettings =
+            serde_json::from_str(r#"{ "$userStreamAcl": { "$r": "$all", "$w": "ops" } }"#).unwrap();
+        let acl = StreamAclBuilder::new().add_read_roles("reporting").build();
+        let effective = settings.effective_acl("foo", Some(Acl::Stream(acl)));
+
+        assert_eq!(effective.read_roles, Some(vec!["reporting".to_string()]));
+        assert_eq!(effective.write_roles, Some(vec!["ops".to_string()]));
+        assert_eq!(effective.delete_roles, Some(vec!["$all".to_string()]));
+    }
+
+    #[test]
+    fn system_settings_serialization() -> Result<(), Box<dyn std::error::Error>> {
+        let settings = SystemSettings::new()
+            .user_stream_acl(StreamAclBuilder::new().add_write_roles("ops").build());
+        let json = serde_json::to_value(&settings)?;
+
+        assert_eq!(
+            json,
+            serde_json::json!({ "$userStreamAcl": { "$w": "ops" } })
+        );
+        assert_eq!(serde_json::from_value::<SystemSettings>(json)?, settings);
+
+        Ok(())
+    }
+
+    #[test]
+    fn credentials_are_matched_against_roles() {
+        let acl = StreamAclBuilder::new()
+            .add_read_roles("ouro")
+            .add_write_roles("$admins")
+            .build();
+        let ouro = Credentials::new("ouro", "changeit");
+        let other = Credentials::new("other", "changeit");
+
+        assert!(acl.can_read(Some(&ouro)));
+        assert!(!acl.can_read(Some(&other)));
+        assert!(!acl.can_read(None));
+        assert!(!acl.can_write(Some(&ouro)));
+        assert!(acl.can_write(Some(&Credentials::new("admin", "changeit"))));
+        assert!(StreamAcl::default_user_stream_acl().can_write(None));
+    }
+}
+
+#[cfg(test)]
+mod metadata_tests {
+    use std::time::Duration;
+
+    use super::{Acl, StreamAcl, StreamAclBuilder, StreamMetadata, StreamMetadataBuilder};
+
+    #[test]
+    fn isomorphic_1() -> Result<(), Box<dyn std::error::Error>> {
+        let acl = StreamAclBuilder::new()
+            .add_read_roles("admin")
+            .add_write_roles("admin")
+            .add_delete_roles("admin")
+            .add_meta_read_roles("admin")
+            .add_meta_write_roles("admin")
+            .build();
+
+        let expected = StreamMetadataBuilder::new()
+            .max_age(Duration::from_secs(2))
+            .cache_control(Duration::from_secs(15))
+            .truncate_before(1)
+            .max_count(12)
+            .acl(Acl::Stream(acl))
+            .insert_custom_property("foo", "bar")
+            .build();
+
+        let actual: StreamMetadata =
+            serde_json::from_slice(serde_json::to_vec(&expected)?.as_slice())?;
+
+        assert_eq!(expected, actual);
+
+        Ok(())
+    }
+
+    #[test]
+    fn isomorphic_2() -> Result<(), Box<dyn std::error::Error>> {
+        let expected = StreamMetadataBuilder::new()
+            .max_age(Duration::from_secs(2))
+            .cache_control(Duration::from_secs(15))
+            .truncate_before(1)
+            .max_count(12)
+            .acl(Acl::UserStream)
+            .insert_custom_property("foo", "bar")
+            .build();
+
+        let actual: StreamMetadata =
+            serde_json::from_slice(serde_json::to_vec(&expected)?.as_slice())?;
+
+        assert_eq!(expected, actual);
+
+        Ok(())
+    }
+
+    #[test]
+    fn isomorphic_3() -> Result<(), Box<dyn std::error::Error>> {
+        let expected = StreamMetadataBuilder::new()
+            .max_age(Duration::from_secs(2))
+            .cache_control(Duration::from_secs(15))
+            .truncate_before(1)
+            .max_count(12)
+            .acl(Acl::SystemStream)
+            .insert_custom_property("foo", "bar")
+            .build();
+
+        let actual: StreamMetadata =
+            serde_json::from_slice(serde_json::to_vec(&expected)?.as_slice())?;
+
+        assert_eq!(expected, actual);
+
+        Ok(())
+    }
+
+    #[test]
+    fn durations_are_serialized_in_seconds() -> Result<(), Box<dyn std::error::Error>> {
+        let metadata = StreamMetadataBuilder::new()
+            .max_age(Duration::from_millis(90_500))
+            .cache_control(Duration::from_secs(15))
+            .build();
+
+        let json = serde_json::to_value(&metadata)?;
+
+        assert_eq!(json["$maxAge"], 90);
+        assert_eq!(json["$cacheControl"], 15);
+
+        Ok(())
+    }
+
+    #[test]
+    fn metadata_spec() -> Result<(), Box<dyn std::error::Error>> {
+        let content = r#"
+        {
+            "$maxCount": 12,
+            "$maxAge": 2,
+            "$tb": 1,
+            "$cacheControl": 15,
+            "$acl": {
+                "$r": "admin",
+                "$w": "admin",
+                "$d": "admin",
+                "$mr": "admin",
+                "$mw": "admin"
+            },
+            "foo": "bar"
+        }
+        "#;
+
+        let acl = StreamAclBuilder::new()
+            .add_read_roles("admin")
+            .add_write_roles("admin")
+            .add_delete_roles("admin")
+            .add_meta_read_roles("admin")
+            .add_meta_write_roles("admin")
+            .build();
+
+        let expected = StreamMetadataBuilder::new()
+            .max_age(Duration::from_secs(2))
+            .cache_control(Duration::from_secs(15))
+            .truncate_before(1)
+            .max_count(12)
             .acl(Acl::Stream(acl))
             .insert_custom_property("foo", "bar")
             .build();
@@ -975,6 +1778,25 @@ mod metadata_tests {
 
         Ok(())
     }
+
+    #[test]
+    fn special_roles_are_serialized_in_acl() -> Result<(), Box<dyn std::error::Error>> {
+        let acl = StreamAclBuilder::new()
+            .add_read_roles(StreamAcl::ALL)
+            .add_write_roles(StreamAcl::ADMINS)
+            .add_write_roles("ops")
+            .build();
+
+        let metadata = StreamMetadataBuilder::new().acl(Acl::Stream(acl)).build();
+        let actual: serde_json::Value = serde_json::to_value(&metadata)?;
+
+        assert_eq!(
+            actual["$acl"],
+            serde_json::json!({ "$r": "$all", "$w": ["$admins", "ops"] })
+        );
+
+        Ok(())
+    }
 }
 
 /// Read part of a persistent subscription, isomorphic to a stream of events.
@@ -1007,6 +1829,53 @@ pub enum SubEvent {
     /// Indicates a checkpoint has been created. Related to subscription to $all when
     /// filters are used.
     Checkpoint(Position),
+
+    /// An event notification from the server, along with the transport
+    /// information it was delivered with. Only emitted when the subscription
+    /// uses raw delivery mode, in place of `EventAppeared`.
+    RawEventAppeared(RawEvent),
+
+    /// Indicates the subscription delivered every event that existed when it
+    /// started and is now live. Only emitted once, when the subscription was
+    /// asked to notify it.
+    CaughtUp,
+
+    /// Indicates the subscription was dropped by a transient failure, a
+    /// connection loss or a failover for instance, and is resubscribing from
+    /// the last event it delivered. Events delivered before aren't delivered
+    /// again. Only emitted when the subscription has a retry policy.
+    Resubscribing,
+}
+
+/// Item of a filtered read of the $all stream.
+#[derive(Debug)]
+pub enum ReadAllEvent {
+    /// An event matching the filter.
+    EventAppeared(ResolvedEvent),
+
+    /// Position the server reached after searching its window without
+    /// finding enough matching events. A read started from that position
+    /// resumes the search where it stopped.
+    Checkpoint(Position),
+}
+
+/// An event delivered by a subscription in raw delivery mode.
+#[derive(Debug)]
+pub struct RawEvent {
+    pub event: ResolvedEvent,
+    pub transport: TransportInfo,
+}
+
+/// Transport information about how a subscription event was delivered.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TransportInfo {
+    /// Id of the gRPC connection the event was received on. A new id is
+    /// assigned each time the client reconnects to a node.
+    pub connection_id: Uuid,
+
+    /// Subscription id confirmed by the server. `None` if the event was
+    /// received before the subscription confirmation.
+    pub subscription_id: Option<String>,
 }
 
 #[derive(Debug)]
@@ -1034,6 +1903,27 @@ pub enum NakAction {
     Stop,
 }
 
+/// What happens to a persistent subscription event when its [`AckHandle`] is
+/// dropped without being acknowledged.
+///
+/// [`AckHandle`]: crate::AckHandle
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum UnsettledEventPolicy {
+    /// Nothing is sent, the server retries the event once its message timeout
+    /// elapses.
+    Ignore,
+
+    /// The event is negatively acknowledged so the server retries it right
+    /// away.
+    NackRetry,
+}
+
+impl Default for UnsettledEventPolicy {
+    fn default() -> Self {
+        UnsettledEventPolicy::Ignore
+    }
+}
+
 /// System supported consumer strategies for use with persistent subscriptions.
 #[derive(Copy, Clone, PartialEq, Eq, Debug)]
 pub enum SystemConsumerStrategy {
@@ -1060,8 +1950,30 @@ pub enum SystemConsumerStrategy {
     Pinned,
 }
 
+impl SystemConsumerStrategy {
+    /// Name of the strategy on the server side.
+    pub(crate) fn name(self) -> &'static str {
+        match self {
+            SystemConsumerStrategy::DispatchToSingle => "DispatchToSingle",
+            SystemConsumerStrategy::RoundRobin => "RoundRobin",
+            SystemConsumerStrategy::Pinned => "Pinned",
+        }
+    }
+
+    pub(crate) fn from_name(name: &str) -> Option<Self> {
+        [
+            SystemConsumerStrategy::DispatchToSingle,
+            SystemConsumerStrategy::RoundRobin,
+            SystemConsumerStrategy::Pinned,
+        ]
+        .iter()
+        .copied()
+        .find(|strategy| strategy.name().eq_ignore_ascii_case(name))
+    }
+}
+
 /// Gathers every persistent subscription property.
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct PersistentSubscriptionSettings {
     /// Whether or not the persistent subscription should resolve link
     /// events to their linked events.
@@ -1108,8 +2020,8 @@ pub struct PersistentSubscriptionSettings {
     pub named_consumer_strategy: SystemConsumerStrategy,
 }
 
-impl PersistentSubscriptionSettings {
-    pub fn default() -> PersistentSubscriptionSettings {
+impl Default for PersistentSubscriptionSettings {
+    fn default() -> PersistentSubscriptionSettings {
         PersistentSubscriptionSettings {
             resolve_link_tos: false,
             revision: 0,
@@ -1128,9 +2040,423 @@ impl PersistentSubscriptionSettings {
     }
 }
 
-impl Default for PersistentSubscriptionSettings {
-    fn default() -> PersistentSubscriptionSettings {
-        PersistentSubscriptionSettings::default()
+/// Last known position of a persistent subscription group, taken from its
+/// checkpoint stream.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PersistentSubscriptionCheckpoint {
+    /// Last event number the group checkpointed.
+    Checkpointed(u64),
+
+    /// The group never checkpointed, it starts from its configured position.
+    NotCheckpointed { start_from: StreamPosition<u64> },
+}
+
+impl PersistentSubscriptionCheckpoint {
+    /// Revision a group created to take over from this one should start
+    /// from, see [`PersistentSubscriptionSettings::revision`]. `None` means
+    /// the end of the stream.
+    pub fn start_revision(&self) -> Option<u64> {
+        match *self {
+            PersistentSubscriptionCheckpoint::Checkpointed(revision) => Some(revision + 1),
+            PersistentSubscriptionCheckpoint::NotCheckpointed { start_from } => match start_from {
+                StreamPosition::Start => Some(0),
+                StreamPosition::Position(revision) => Some(revision),
+                StreamPosition::End => None,
+            },
+        }
+    }
+
+    /// Parses the payload of a `$SubscriptionCheckpoint` event. Depending on
+    /// the server version, the event number is written as a JSON number or
+    /// string.
+    pub(crate) fn parse(data: &[u8]) -> Option<u64> {
+        match serde_json::from_slice::<serde_json::Value>(data).ok()? {
+            serde_json::Value::Number(number) => number.as_u64(),
+            serde_json::Value::String(number) => number.parse().ok(),
+            _ => None,
+        }
+    }
+}
+
+/// Content of the `$persistentSubscriptionConfig` system stream, where the
+/// server stores the configuration of every persistent subscription group.
+#[derive(Debug, Deserialize)]
+pub(crate) struct PersistentSubscriptionConfig {
+    #[serde(rename = "Entries", default)]
+    entries: Vec<PersistentSubscriptionConfigEntry>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "PascalCase", default)]
+struct PersistentSubscriptionConfigEntry {
+    stream: String,
+    group: String,
+    start_from: i64,
+    resolve_link_tos: bool,
+    extra_statistics: bool,
+    message_timeout: u64,
+    max_retry_count: i32,
+    live_buffer_size: i32,
+    read_batch_size: i32,
+    history_buffer_size: i32,
+    #[serde(rename = "CheckPointAfter")]
+    checkpoint_after: u64,
+    #[serde(rename = "MinCheckPointCount")]
+    min_checkpoint_count: i32,
+    #[serde(rename = "MaxCheckPointCount")]
+    max_checkpoint_count: i32,
+    max_subscriber_count: i32,
+    named_consumer_strategy: String,
+}
+
+impl PersistentSubscriptionConfigEntry {
+    /// Checks the group has the given settings. Durations are compared at the
+    /// millisecond precision the server stores them with.
+    fn has_settings(&self, start_from: i64, settings: &PersistentSubscriptionSettings) -> bool {
+        let strategy = settings.named_consumer_strategy.name();
+
+        self.start_from == start_from
+            && self.resolve_link_tos == settings.resolve_link_tos
+            && self.extra_statistics == settings.extra_stats
+            && self.message_timeout == settings.message_timeout.as_millis() as u64
+            && self.max_retry_count == settings.max_retry_count
+            && self.live_buffer_size == settings.live_buffer_size
+            && self.read_batch_size == settings.read_batch_size
+            && self.history_buffer_size == settings.history_buffer_size
+            && self.checkpoint_after == settings.checkpoint_after.as_millis() as u64
+            && self.min_checkpoint_count == settings.min_checkpoint_count
+            && self.max_checkpoint_count == settings.max_checkpoint_count
+            && self.max_subscriber_count == settings.max_subscriber_count
+            && self.named_consumer_strategy.eq_ignore_ascii_case(strategy)
+    }
+
+    /// Typed version of the entry, `None` if the group uses a consumer
+    /// strategy this client doesn't know about.
+    fn to_group_config(&self) -> Option<PersistentSubscriptionGroupConfig> {
+        let named_consumer_strategy =
+            SystemConsumerStrategy::from_name(&self.named_consumer_strategy)?;
+        let start_from = if self.start_from < 0 {
+            StreamPosition::End
+        } else {
+            StreamPosition::Position(self.start_from as u64)
+        };
+
+        let settings = PersistentSubscriptionSettings {
+            resolve_link_tos: self.resolve_link_tos,
+            revision: self.start_from.max(0) as u64,
+            extra_stats: self.extra_statistics,
+            message_timeout: Duration::from_millis(self.message_timeout),
+            max_retry_count: self.max_retry_count,
+            live_buffer_size: self.live_buffer_size,
+            read_batch_size: self.read_batch_size,
+            history_buffer_size: self.history_buffer_size,
+            checkpoint_after: Duration::from_millis(self.checkpoint_after),
+            min_checkpoint_count: self.min_checkpoint_count,
+            max_checkpoint_count: self.max_checkpoint_count,
+            max_subscriber_count: self.max_subscriber_count,
+            named_consumer_strategy,
+        };
+
+        Some(PersistentSubscriptionGroupConfig {
+            stream: self.stream.clone(),
+            group: self.group.clone(),
+            start_from,
+            settings,
+        })
+    }
+}
+
+impl PersistentSubscriptionConfig {
+    fn entry(&self, stream: &str, group: &str) -> Option<&PersistentSubscriptionConfigEntry> {
+        self.entries
+            .iter()
+            .find(|entry| entry.stream == stream && entry.group == group)
+    }
+
+    /// Configured start of a group, `-1` standing for the end of the stream.
+    pub(crate) fn start_from(&self, stream: &str, group: &str) -> Option<StreamPosition<u64>> {
+        let entry = self.entry(stream, group)?;
+
+        let start_from = if entry.start_from < 0 {
+            StreamPosition::End
+        } else {
+            StreamPosition::Position(entry.start_from as u64)
+        };
+
+        Some(start_from)
+    }
+
+    /// Typed configuration of every group. Groups using an unknown consumer
+    /// strategy are left out.
+    pub(crate) fn groups(&self) -> Vec<PersistentSubscriptionGroupConfig> {
+        self.entries
+            .iter()
+            .filter_map(|entry| {
+                let group = entry.to_group_config();
+
+                if group.is_none() {
+                    warn!(
+                        "Skipping persistent subscription {}::{} with unknown consumer strategy {}",
+                        entry.stream, entry.group, entry.named_consumer_strategy
+                    );
+                }
+
+                group
+            })
+            .collect()
+    }
+
+    /// Checks if a group is configured with the given options, `None` if the
+    /// group doesn't exist.
+    pub(crate) fn has_options(
+        &self,
+        stream: &str,
+        group: &str,
+        start: StreamPosition<u64>,
+        settings: &PersistentSubscriptionSettings,
+    ) -> Option<bool> {
+        let entry = self.entry(stream, group)?;
+        let start_from = match start {
+            StreamPosition::Start => 0,
+            StreamPosition::End => -1,
+            StreamPosition::Position(revision) => revision as i64,
+        };
+
+        // Servers prior to 20.10 take the start of a group from its settings.
+        Some(
+            entry.has_settings(start_from, settings)
+                || entry.has_settings(settings.revision as i64, settings),
+        )
+    }
+}
+
+/// Configuration of a persistent subscription group, as stored by the server
+/// in the `$persistentSubscriptionConfig` system stream.
+///
+/// Tooling reconciling the desired configuration of a group with its actual
+/// one can compare both and apply the desired one with
+/// [`crate::Client::apply_persistent_subscription_config`]:
+///
+/// ```no_run
+/// # use eventstore::{
+/// #     Client, PersistentSubscriptionGroupConfig, PersistentSubscriptionSettings,
+/// #     ReadStreamOptions, StreamPosition,
+/// # };
+/// # async fn f(client: Client) -> eventstore::Result<()> {
+/// let desired = PersistentSubscriptionSettings {
+///     max_retry_count: 3,
+///     ..Default::default()
+/// };
+/// let latest = ReadStreamOptions::default().position(StreamPosition::End);
+///
+/// if let Some(actual) = client
+///     .persistent_subscription_config("orders", "billing", &latest)
+///     .await?
+/// {
+///     if actual.settings.max_retry_count != desired.max_retry_count {
+///         let config = PersistentSubscriptionGroupConfig {
+///             settings: PersistentSubscriptionSettings {
+///                 max_retry_count: desired.max_retry_count,
+///                 ..actual.settings
+///             },
+///             ..actual
+///         };
+///
+///         client
+///             .apply_persistent_subscription_config(&config, &Default::default())
+///             .await?;
+///     }
+/// }
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PersistentSubscriptionGroupConfig {
+    /// Stream the group subscribes to.
+    pub stream: String,
+
+    /// Name of the group.
+    pub group: String,
+
+    /// Where the group starts from.
+    pub start_from: StreamPosition<u64>,
+
+    /// Settings of the group. Its `revision` is the start of the group, `0`
+    /// when it starts from the end of the stream.
+    pub settings: PersistentSubscriptionSettings,
+}
+
+impl PersistentSubscriptionGroupConfig {
+    /// Options creating or updating a group with this configuration.
+    pub fn to_options(&self) -> crate::PersistentSubscriptionOptions {
+        crate::PersistentSubscriptionOptions::default()
+            .revision(self.start_from)
+            .settings(self.settings)
+    }
+}
+
+/// What [`crate::Client::ensure_persistent_subscription`] did.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EnsurePersistentSubscriptionResult {
+    /// The group didn't exist and was created.
+    Created,
+
+    /// The group existed with different settings and was updated.
+    Updated,
+
+    /// The group already had the requested settings.
+    Unchanged,
+}
+
+#[cfg(test)]
+mod persistent_checkpoint_tests {
+    use super::{
+        PersistentSubscriptionCheckpoint, PersistentSubscriptionConfig,
+        PersistentSubscriptionSettings, StreamPosition, SystemConsumerStrategy,
+    };
+
+    #[test]
+    fn checkpoint_payload_is_parsed() {
+        assert_eq!(PersistentSubscriptionCheckpoint::parse(b"42"), Some(42));
+        assert_eq!(PersistentSubscriptionCheckpoint::parse(b"\"42\""), Some(42));
+        assert_eq!(PersistentSubscriptionCheckpoint::parse(b"{}"), None);
+    }
+
+    #[test]
+    fn configured_start_is_looked_up() {
+        let config: PersistentSubscriptionConfig = serde_json::from_str(
+            r#"{
+                "Version": "2",
+                "Entries": [
+                    { "Stream": "orders", "Group": "billing", "StartFrom": 10 },
+                    { "Stream": "orders", "Group": "audit", "StartFrom": -1 }
+                ]
+            }"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            config.start_from("orders", "billing"),
+            Some(StreamPosition::Position(10))
+        );
+        assert_eq!(
+            config.start_from("orders", "audit"),
+            Some(StreamPosition::End)
+        );
+        assert_eq!(config.start_from("orders", "missing"), None);
+    }
+
+    #[test]
+    fn configured_settings_are_compared() {
+        let config: PersistentSubscriptionConfig = serde_json::from_str(
+            r#"{
+                "Entries": [{
+                    "Stream": "orders",
+                    "Group": "billing",
+                    "StartFrom": -1,
+                    "ResolveLinkTos": false,
+                    "ExtraStatistics": false,
+                    "MessageTimeout": 30000,
+                    "MaxRetryCount": 10,
+                    "LiveBufferSize": 500,
+                    "ReadBatchSize": 20,
+                    "HistoryBufferSize": 500,
+                    "CheckPointAfter": 2000,
+                    "MinCheckPointCount": 10,
+                    "MaxCheckPointCount": 1000,
+                    "MaxSubscriberCount": 0,
+                    "NamedConsumerStrategy": "RoundRobin"
+                }]
+            }"#,
+        )
+        .unwrap();
+
+        let settings = PersistentSubscriptionSettings::default();
+        let mut changed = settings;
+
+        changed.max_retry_count = 3;
+
+        assert_eq!(
+            config.has_options("orders", "billing", StreamPosition::End, &settings),
+            Some(true)
+        );
+        assert_eq!(
+            config.has_options("orders", "billing", StreamPosition::End, &changed),
+            Some(false)
+        );
+        assert_eq!(
+            config.has_options("orders", "billing", StreamPosition::Position(5), &settings),
+            Some(false)
+        );
+        assert_eq!(
+            config.has_options("orders", "audit", StreamPosition::End, &settings),
+            None
+        );
+    }
+
+    #[test]
+    fn configured_groups_are_typed() {
+        let config: PersistentSubscriptionConfig = serde_json::from_str(
+            r#"{
+                "Entries": [{
+                    "Stream": "orders",
+                    "Group": "billing",
+                    "StartFrom": 12,
+                    "ResolveLinkTos": true,
+                    "ExtraStatistics": false,
+                    "MessageTimeout": 30000,
+                    "MaxRetryCount": 10,
+                    "LiveBufferSize": 500,
+                    "ReadBatchSize": 20,
+                    "HistoryBufferSize": 500,
+                    "CheckPointAfter": 2000,
+                    "MinCheckPointCount": 10,
+                    "MaxCheckPointCount": 1000,
+                    "MaxSubscriberCount": 0,
+                    "NamedConsumerStrategy": "pinned"
+                }, {
+                    "Stream": "orders",
+                    "Group": "custom",
+                    "NamedConsumerStrategy": "MyOwnStrategy"
+                }]
+            }"#,
+        )
+        .unwrap();
+
+        let groups = config.groups();
+
+        assert_eq!(groups.len(), 1);
+        assert_eq!(groups[0].group, "billing");
+        assert_eq!(groups[0].start_from, StreamPosition::Position(12));
+        assert_eq!(
+            groups[0].settings,
+            PersistentSubscriptionSettings {
+                resolve_link_tos: true,
+                revision: 12,
+                named_consumer_strategy: SystemConsumerStrategy::Pinned,
+                ..Default::default()
+            }
+        );
+        assert_eq!(
+            config.has_options(
+                "orders",
+                "billing",
+                groups[0].start_from,
+                &groups[0].settings
+            ),
+            Some(true)
+        );
+    }
+
+    #[test]
+    fn takeover_starts_after_checkpoint() {
+        let checkpointed = PersistentSubscriptionCheckpoint::Checkpointed(9);
+        let never = PersistentSubscriptionCheckpoint::NotCheckpointed {
+            start_from: StreamPosition::End,
+        };
+
+        assert_eq!(checkpointed.start_revision(), Some(10));
+        assert_eq!(never.start_revision(), None);
     }
 }
 
@@ -1173,6 +2499,121 @@ pub enum PersistActionError {
     AccessDenied,
 }
 
+/// Reasons for which the server, or the client, can drop a subscription.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum SubscriptionDropReason {
+    /// The server ended the subscription.
+    Unsubscribed,
+
+    /// The current user is not allowed to read from the subscribed stream or
+    /// persistent subscription.
+    AccessDenied,
+
+    /// The subscribed stream or persistent subscription doesn't exist.
+    NotFound,
+
+    /// The persistent subscription has been deleted while the subscription
+    /// was live.
+    PersistentSubscriptionDeleted,
+
+    /// The persistent subscription already has its maximum number of
+    /// subscribers.
+    SubscriberMaxCountReached,
+
+    /// The client dropped the subscription because its events reached the
+    /// consumer later than the `slowConsumerDropAfter` setting.
+    SlowConsumer,
+}
+
+impl SubscriptionDropReason {
+    /// Decodes why the server dropped a subscription, if the status is
+    /// a subscription drop at all.
+    pub(crate) fn from_status(status: &Status) -> Option<Self> {
+        let exception = status
+            .metadata()
+            .get("exception")
+            .and_then(|e| e.to_str().ok());
+
+        match exception {
+            Some("access-denied") => return Some(SubscriptionDropReason::AccessDenied),
+            Some("stream-not-found") | Some("persistent-subscription-does-not-exist") => {
+                return Some(SubscriptionDropReason::NotFound)
+            }
+            Some("persistent-subscription-deleted") | Some("persistent-subscription-dropped") => {
+                return Some(SubscriptionDropReason::PersistentSubscriptionDeleted)
+            }
+            Some("maximum-subscribers-reached") => {
+                return Some(SubscriptionDropReason::SubscriberMaxCountReached)
+            }
+            _ => {}
+        }
+
+        match status.code() {
+            Code::Unauthenticated | Code::PermissionDenied => {
+                Some(SubscriptionDropReason::AccessDenied)
+            }
+            Code::NotFound => Some(SubscriptionDropReason::NotFound),
+            _ => None,
+        }
+    }
+
+    /// Checks if subscribing again could succeed. Only a server-side
+    /// unsubscription is considered transient.
+    pub fn is_transient(&self) -> bool {
+        matches!(*self, SubscriptionDropReason::Unsubscribed)
+    }
+}
+
+impl std::fmt::Display for SubscriptionDropReason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SubscriptionDropReason::Unsubscribed => write!(f, "Unsubscribed"),
+            SubscriptionDropReason::AccessDenied => write!(f, "AccessDenied"),
+            SubscriptionDropReason::NotFound => write!(f, "NotFound"),
+            SubscriptionDropReason::PersistentSubscriptionDeleted => {
+                write!(f, "PersistentSubscriptionDeleted")
+            }
+            SubscriptionDropReason::SubscriberMaxCountReached => {
+                write!(f, "SubscriberMaxCountReached")
+            }
+            SubscriptionDropReason::SlowConsumer => write!(f, "SlowConsumer"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod drop_reason_tests {
+    use tonic::{Code, Status};
+
+    use super::SubscriptionDropReason;
+
+    #[test]
+    fn drop_reason_from_exception_metadata() {
+        let mut status = Status::new(Code::Unknown, "dropped");
+        status.metadata_mut().insert(
+            "exception",
+            "persistent-subscription-deleted".parse().unwrap(),
+        );
+
+        assert_eq!(
+            SubscriptionDropReason::from_status(&status),
+            Some(SubscriptionDropReason::PersistentSubscriptionDeleted)
+        );
+    }
+
+    #[test]
+    fn drop_reason_from_status_code() {
+        let status = Status::new(Code::PermissionDenied, "denied");
+        assert_eq!(
+            SubscriptionDropReason::from_status(&status),
+            Some(SubscriptionDropReason::AccessDenied)
+        );
+
+        let status = Status::new(Code::Unavailable, "down");
+        assert_eq!(SubscriptionDropReason::from_status(&status), None);
+    }
+}
+
 /// Indicates which order of preferred nodes for connecting to.
 #[derive(Copy, Clone, Eq, PartialEq, Debug, Serialize, Deserialize)]
 pub enum NodePreference {
@@ -1208,6 +2649,88 @@ impl std::fmt::Display for NodePreference {
     }
 }
 
+/// Role of the cluster node a client is connected to.
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub enum NodeRole {
+    Leader,
+    Follower,
+    ReadOnlyReplica,
+}
+
+impl std::fmt::Display for NodeRole {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            NodeRole::Leader => write!(f, "leader"),
+            NodeRole::Follower => write!(f, "follower"),
+            NodeRole::ReadOnlyReplica => write!(f, "read-only replica"),
+        }
+    }
+}
+
+/// Indicates how the client reacts when an operation requiring a leader node
+/// is sent to a node that is not the leader of the cluster.
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Serialize, Deserialize)]
+pub enum NotLeaderBehavior {
+    /// Fails the operation with [`Error::NotLeaderException`] and reconnects
+    /// to the advertised leader in the background, so subsequent operations
+    /// are sent to that leader.
+    Reconnect,
+
+    /// Reconnects to the advertised leader then retries the operation. The
+    /// operation is retried a bounded number of times, waiting between each
+    /// attempt. This is useful when the client is transiently connected to a
+    /// node during a leader election. If the operation still fails after the
+    /// last attempt, it fails with [`Error::NotLeaderException`].
+    Retry,
+
+    /// Fails the operation with [`Error::NotLeaderException`] without
+    /// touching the current connection. It's up to the caller to reconnect to
+    /// the advertised leader.
+    Fail,
+}
+
+impl Default for NotLeaderBehavior {
+    fn default() -> Self {
+        NotLeaderBehavior::Reconnect
+    }
+}
+
+/// Indicates how a subscription reacts when one of the events it receives
+/// can't be decoded.
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Serialize, Deserialize)]
+pub enum DecodeFailurePolicy {
+    /// Ends the subscription with an [`Error::InternalParsingError`].
+    FailOperation,
+
+    /// Logs the failure, skips the event and keeps the subscription going.
+    SkipEvent,
+}
+
+impl Default for DecodeFailurePolicy {
+    fn default() -> Self {
+        DecodeFailurePolicy::FailOperation
+    }
+}
+
+/// Indicates what an append without any event does.
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Serialize, Deserialize)]
+pub enum EmptyAppendBehavior {
+    /// Sends the append to the server, which writes nothing but still checks
+    /// the expected revision. The result holds the current revision of the
+    /// stream, which makes an empty append a way to assert a stream revision.
+    AssertRevision,
+
+    /// Fails the append with [`Error::NoEventsToWrite`] without contacting
+    /// the server.
+    Reject,
+}
+
+impl Default for EmptyAppendBehavior {
+    fn default() -> Self {
+        EmptyAppendBehavior::AssertRevision
+    }
+}
+
 #[derive(Debug)]
 pub(crate) enum Either<A, B> {
     Left(A),
@@ -1217,8 +2740,8 @@ pub(crate) enum Either<A, B> {
 impl<A, B> Either<A, B> {
     pub(crate) fn as_ref(&self) -> Either<&A, &B> {
         match self {
-            Either::Left(a) => Either::Left(&a),
-            Either::Right(b) => Either::Right(&b),
+            Either::Left(a) => Either::Left(a),
+            Either::Right(b) => Either::Right(b),
         }
     }
 }
@@ -1256,25 +2779,133 @@ impl std::fmt::Display for WrongExpectedVersion {
 
 impl std::error::Error for WrongExpectedVersion {}
 
+impl WrongExpectedVersion {
+    /// Decodes the wrong expected version exception the server describes
+    /// through the status metadata, which is how deletes report a version
+    /// conflict.
+    pub(crate) fn from_status(status: &Status) -> Option<Self> {
+        let metadata = status.metadata();
+        let value = |key: &str| metadata.get(key).and_then(|v| v.to_str().ok());
+
+        if value("exception")? != "wrong-expected-version" {
+            return None;
+        }
+
+        let current = match value("actual-version")?.parse::<i64>().ok()? {
+            revision if revision >= 0 => CurrentRevision::Current(revision as u64),
+            _ => CurrentRevision::NoStream,
+        };
+
+        let expected = match value("expected-version")?.parse::<i64>().ok()? {
+            revision if revision >= 0 => ExpectedRevision::Exact(revision as u64),
+            -1 => ExpectedRevision::NoStream,
+            -4 => ExpectedRevision::StreamExists,
+            _ => ExpectedRevision::Any,
+        };
+
+        Some(WrongExpectedVersion { current, expected })
+    }
+}
+
 #[derive(Debug, Clone, Eq, Ord, PartialOrd, PartialEq, Serialize, Deserialize)]
 pub struct Endpoint {
     pub host: String,
     pub port: u32,
 }
 
+/// Failure of a projection management operation.
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum ProjectionError {
+    #[error("Projection {name} doesn't exist")]
+    NotFound { name: String },
+    #[error("Projection {name} already exists")]
+    AlreadyExists { name: String },
+    #[error("Projection {name} is faulted: {reason}")]
+    Faulted { name: String, reason: String },
+    #[error("Projection {name} has an invalid source: {message}")]
+    InvalidSource { name: String, message: String },
+}
+
+impl ProjectionError {
+    /// Name of the projection the operation targeted.
+    pub fn name(&self) -> &str {
+        match self {
+            ProjectionError::NotFound { name }
+            | ProjectionError::AlreadyExists { name }
+            | ProjectionError::Faulted { name, .. }
+            | ProjectionError::InvalidSource { name, .. } => name.as_str(),
+        }
+    }
+
+    /// Turns the error of an operation on the given projection into a
+    /// projection error when it's specific to projections. Transport errors
+    /// are returned as-is.
+    pub(crate) fn from_error(name: &str, error: Error) -> Error {
+        let name = name.to_string();
+        let error = match error {
+            Error::ResourceNotFound => ProjectionError::NotFound { name },
+            Error::ResourceAlreadyExists => ProjectionError::AlreadyExists { name },
+            Error::Grpc(status) if status.code() == Code::InvalidArgument => {
+                ProjectionError::InvalidSource {
+                    name,
+                    message: status.message().to_string(),
+                }
+            }
+            other => return other,
+        };
+
+        Error::Projection(error)
+    }
+}
+
+#[cfg(test)]
+mod projection_error_tests {
+    use tonic::{Code, Status};
+
+    use super::{Error, ProjectionError};
+
+    #[test]
+    fn projection_specific_errors_carry_the_name() {
+        let err = ProjectionError::from_error("foo", Error::ResourceNotFound);
+        assert!(matches!(
+            err,
+            Error::Projection(ProjectionError::NotFound { ref name }) if name == "foo"
+        ));
+
+        let err = ProjectionError::from_error(
+            "foo",
+            Error::Grpc(Status::new(Code::InvalidArgument, "Unexpected token")),
+        );
+        assert!(matches!(
+            err,
+            Error::Projection(ProjectionError::InvalidSource { ref message, .. })
+                if message == "Unexpected token"
+        ));
+    }
+
+    #[test]
+    fn transport_errors_are_kept() {
+        let err = ProjectionError::from_error("foo", Error::ConnectionClosed);
+
+        assert!(matches!(err, Error::ConnectionClosed));
+    }
+}
+
 #[derive(Error, Debug, Clone)]
 /// EventStoreDB command error.
 pub enum Error {
     #[error("Server-side error.")]
-    ServerError(Status),
-    #[error("You tried to execute a command that requires a leader node on a follower node. New leader: ")]
+    ServerError(#[source] Status),
+    #[error("You tried to execute a command that requires a leader node on a follower node. New leader: {0:?}")]
     NotLeaderException(Endpoint),
     #[error("Connection is closed.")]
     ConnectionClosed,
+    #[error("The operation was aborted because the client was dropped.")]
+    Aborted,
     #[error("Unmapped gRPC error: {0}.")]
-    Grpc(Status),
+    Grpc(#[source] Status),
     #[error("gRPC connection error: {0}")]
-    GrpcConnectionError(GrpcConnectionError),
+    GrpcConnectionError(#[source] GrpcConnectionError),
     #[error("Internal parsing error: {0}")]
     InternalParsingError(String),
     #[error("Access denied error")]
@@ -1283,10 +2914,50 @@ pub enum Error {
     ResourceAlreadyExists,
     #[error("The resource you asked for doesn't exist")]
     ResourceNotFound,
+    #[error("The server is too busy to handle the operation")]
+    ServerBusy,
+    #[error("The subscription was dropped by the server: {0}")]
+    SubscriptionDropped(SubscriptionDropReason),
+    #[error("The operation didn't complete before its deadline")]
+    DeadlineExceeded,
+    #[error("Projection error: {0}")]
+    Projection(ProjectionError),
+    #[error("Stream {stream} not found, make sure the {projection} system projection is enabled")]
+    SystemProjectionStreamNotFound { projection: String, stream: String },
+    #[error(
+        "Writing to system stream {stream} is forbidden, see the allowSystemStreamWrites setting"
+    )]
+    SystemStreamWriteForbidden { stream: String },
+    /// `truncate_before` is `i64::MAX` if the stream was deleted and not
+    /// written to since: the server only records where the prior
+    /// incarnation ended once the stream is written to again.
+    #[error("Stream {stream} had a prior incarnation, truncated before event {truncate_before}")]
+    PriorStreamIncarnation {
+        stream: String,
+        truncate_before: u64,
+    },
+    #[error("No events to append to stream {stream}, see the emptyAppend setting")]
+    NoEventsToWrite { stream: String },
+    #[error("Stream {stream} is deleted")]
+    StreamDeleted { stream: String },
+    #[error("Stream {stream} doesn't exist")]
+    StreamNotFound { stream: String },
+    #[error("The append exceeds the maximum append size of the server ({max_size:?} bytes)")]
+    MaxPayloadExceeded { max_size: Option<u32> },
+    #[error("The operation was cancelled")]
+    Cancelled,
+    #[error("Catch-up of stream {stream} kept more than {capacity} live events aside")]
+    CatchUpBufferOverflow { stream: String, capacity: usize },
+    #[error("The server timed out in the {phase} phase of the write")]
+    WriteTimeout { phase: WriteTimeoutPhase },
 }
 
 impl Error {
     pub fn from_grpc(status: Status) -> Self {
+        if let Some(error) = Error::from_exception(&status) {
+            return error;
+        }
+
         let metadata = status.metadata();
         if let Some("not-leader") = metadata.get("exception").and_then(|e| e.to_str().ok()) {
             let endpoint = metadata
@@ -1306,6 +2977,10 @@ impl Error {
             }
         }
 
+        if status.code() == Code::Aborted && status.message() == crate::grpc::ABORTED_MESSAGE {
+            return Error::Aborted;
+        }
+
         if status.code() == Code::Unauthenticated || status.code() == Code::PermissionDenied {
             return Error::AccessDenied;
         }
@@ -1318,6 +2993,16 @@ impl Error {
             return Error::ResourceNotFound;
         }
 
+        if status.code() == Code::Unavailable
+            && status.message().to_lowercase().contains("too busy")
+        {
+            return Error::ServerBusy;
+        }
+
+        if let Some(phase) = write_timeout_phase(&status) {
+            return Error::WriteTimeout { phase };
+        }
+
         if status.code() == Code::Unavailable
             || status.code() == Code::Internal
             || status.code() == Code::DataLoss
@@ -1327,6 +3012,237 @@ impl Error {
 
         Error::Grpc(status)
     }
+
+    /// Decodes the exceptions the server describes through the status
+    /// metadata. Unknown exceptions are left to the status code mapping.
+    fn from_exception(status: &Status) -> Option<Self> {
+        let metadata = status.metadata();
+        let value = |key: &str| metadata.get(key).and_then(|v| v.to_str().ok());
+
+        match value("exception")? {
+            "stream-deleted" => Some(Error::StreamDeleted {
+                stream: value("stream-name").unwrap_or_default().to_string(),
+            }),
+
+            "maximum-append-size-exceeded" => Some(Error::MaxPayloadExceeded {
+                max_size: value("maximum-append-size").and_then(|size| size.parse().ok()),
+            }),
+
+            _ => None,
+        }
+    }
+
+    /// Indicates the operation failed because of a transient condition of
+    /// the server, so sending it again later might succeed. Whether that's
+    /// safe depends on the operation: only idempotent writes should be sent
+    /// again.
+    pub fn is_retryable(&self) -> bool {
+        matches!(
+            self,
+            Error::ServerError(_)
+                | Error::NotLeaderException(_)
+                | Error::ServerBusy
+                | Error::WriteTimeout { .. }
+        )
+    }
+
+    /// Indicates a subscription failing with this error would fail the same
+    /// way if subscribing again from the same position.
+    pub(crate) fn ends_subscription(&self) -> bool {
+        matches!(
+            self,
+            Error::InternalParsingError(_) | Error::StreamDeleted { .. } | Error::AccessDenied
+        )
+    }
+}
+
+/// The server usually reports a write timeout as an aborted "Operation timed
+/// out" status. Some versions name the prepare, commit or forward phase in the
+/// status message instead.
+fn write_timeout_phase(status: &Status) -> Option<WriteTimeoutPhase> {
+    if status.code() != Code::DeadlineExceeded && status.code() != Code::Aborted {
+        return None;
+    }
+
+    let message = status.message().to_lowercase();
+    let compact = message.replace([' ', '-'], "");
+
+    if compact.contains("preparetimeout") {
+        Some(WriteTimeoutPhase::Prepare)
+    } else if compact.contains("committimeout") {
+        Some(WriteTimeoutPhase::Commit)
+    } else if compact.contains("forwardtimeout") {
+        Some(WriteTimeoutPhase::Forward)
+    } else if status.code() == Code::Aborted && message.contains("timed out") {
+        Some(WriteTimeoutPhase::Unknown)
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod exception_tests {
+    use super::{
+        CurrentRevision, Error, ExpectedRevision, WriteTimeoutPhase, WrongExpectedVersion,
+    };
+    use tonic::{Code, Status};
+
+    fn status(code: Code, metadata: &[(&'static str, &'static str)]) -> Status {
+        let mut status = Status::new(code, "failed");
+
+        for (key, value) in metadata {
+            status.metadata_mut().insert(*key, value.parse().unwrap());
+        }
+
+        status
+    }
+
+    #[test]
+    fn structured_exceptions_are_decoded() {
+        let error = Error::from_grpc(status(
+            Code::InvalidArgument,
+            &[
+                ("exception", "maximum-append-size-exceeded"),
+                ("maximum-append-size", "1048576"),
+            ],
+        ));
+
+        assert!(matches!(
+            error,
+            Error::MaxPayloadExceeded {
+                max_size: Some(1_048_576)
+            }
+        ));
+
+        let error = Error::from_grpc(status(
+            Code::FailedPrecondition,
+            &[("exception", "stream-deleted"), ("stream-name", "orders")],
+        ));
+
+        assert!(matches!(error, Error::StreamDeleted { ref stream } if stream == "orders"));
+    }
+
+    #[test]
+    fn write_timeouts_are_decoded_with_their_phase() {
+        let timeout =
+            |message: &'static str| Error::from_grpc(Status::new(Code::DeadlineExceeded, message));
+
+        assert!(matches!(
+            timeout("Commit timeout"),
+            Error::WriteTimeout {
+                phase: WriteTimeoutPhase::Commit
+            }
+        ));
+        assert!(matches!(
+            Error::from_grpc(Status::new(
+                Code::Aborted,
+                "Operation failed: PrepareTimeout"
+            )),
+            Error::WriteTimeout {
+                phase: WriteTimeoutPhase::Prepare
+            }
+        ));
+        assert!(matches!(
+            Error::from_grpc(Status::new(Code::Aborted, "Operation timed out")),
+            Error::WriteTimeout {
+                phase: WriteTimeoutPhase::Unknown
+            }
+        ));
+        assert!(timeout("forward-timeout").is_retryable());
+        assert!(matches!(timeout("Deadline Exceeded"), Error::Grpc(_)));
+        assert!(matches!(
+            Error::from_grpc(Status::aborted(crate::grpc::ABORTED_MESSAGE)),
+            Error::Aborted
+        ));
+    }
+
+    #[test]
+    fn permanent_failures_end_subscriptions() {
+        assert!(Error::InternalParsingError("bad event".to_string()).ends_subscription());
+        assert!(Error::StreamDeleted {
+            stream: "orders".to_string()
+        }
+        .ends_subscription());
+        assert!(Error::AccessDenied.ends_subscription());
+        assert!(!Error::ConnectionClosed.ends_subscription());
+    }
+
+    #[test]
+    fn unknown_exceptions_fall_back_to_status() {
+        let error = Error::from_grpc(status(
+            Code::InvalidArgument,
+            &[("exception", "something-else")],
+        ));
+
+        assert!(matches!(error, Error::Grpc(ref status) if status.message() == "failed"));
+    }
+
+    #[test]
+    fn status_is_kept_as_source() {
+        use std::error::Error as _;
+
+        let error = Error::from_grpc(status(Code::Internal, &[]));
+        let source = error.source().and_then(|e| e.downcast_ref::<Status>());
+
+        assert!(matches!(source, Some(status) if status.message() == "failed"));
+
+        let error = Error::GrpcConnectionError(super::GrpcConnectionError::Grpc(status(
+            Code::Unknown,
+            &[],
+        )));
+        let root = error
+            .source()
+            .and_then(|e| e.source())
+            .and_then(|e| e.downcast_ref::<Status>());
+
+        assert!(matches!(root, Some(status) if status.code() == Code::Unknown));
+    }
+
+    #[test]
+    fn wrong_expected_version_is_decoded() {
+        let conflict = WrongExpectedVersion::from_status(&status(
+            Code::FailedPrecondition,
+            &[
+                ("exception", "wrong-expected-version"),
+                ("stream-name", "orders"),
+                ("expected-version", "-4"),
+                ("actual-version", "-1"),
+            ],
+        ));
+
+        assert_eq!(
+            conflict,
+            Some(WrongExpectedVersion {
+                current: CurrentRevision::NoStream,
+                expected: ExpectedRevision::StreamExists,
+            })
+        );
+
+        let conflict = WrongExpectedVersion::from_status(&status(
+            Code::FailedPrecondition,
+            &[
+                ("exception", "wrong-expected-version"),
+                ("expected-version", "3"),
+                ("actual-version", "7"),
+            ],
+        ));
+
+        assert_eq!(
+            conflict,
+            Some(WrongExpectedVersion {
+                current: CurrentRevision::Current(7),
+                expected: ExpectedRevision::Exact(3),
+            })
+        );
+
+        assert_eq!(
+            WrongExpectedVersion::from_status(&status(
+                Code::FailedPrecondition,
+                &[("exception", "stream-deleted")]
+            )),
+            None
+        );
+    }
 }
 
 #[derive(Error, Debug, Clone)]
@@ -1334,8 +3250,12 @@ impl Error {
 pub enum GrpcConnectionError {
     #[error("Max discovery attempt count reached. count: {0}")]
     MaxDiscoveryAttemptReached(usize),
+    #[error("No connection available after waiting {0:?}")]
+    ConnectionUnavailable(Duration),
+    #[error("Certificate of node {0:?} doesn't match any pinned fingerprint")]
+    CertificatePinMismatch(Endpoint),
     #[error("Unmapped gRPC connection error: {0}.")]
-    Grpc(Status),
+    Grpc(#[source] Status),
 }
 
 pub type Result<A> = std::result::Result<A, Error>;
@@ -1422,6 +3342,7 @@ impl<'a> ToCount<'a> for usize {
 }
 
 /// Get all the stream's events.
+#[derive(Debug, Copy, Clone)]
 pub struct All;
 
 #[async_trait]
@@ -1441,6 +3362,7 @@ impl<'a> ToCount<'a> for All {
 }
 
 /// Get only one stream's event.
+#[derive(Debug, Copy, Clone)]
 pub struct Single;
 
 #[async_trait]
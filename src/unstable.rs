@@ -0,0 +1,65 @@
+//! Low-level access to the gRPC protocol, enabled with the `unstable`
+//! feature.
+//!
+//! **Caveats:** nothing in this module follows semantic versioning. The
+//! messages are generated from the protocol definitions of the server and
+//! change whenever those definitions do, including in patch releases of this
+//! crate. The client doesn't validate raw requests nor interpret raw
+//! responses: a malformed request is rejected by the server at best. Use it
+//! to prototype support for a server feature, then move to the high-level
+//! API once it covers that feature.
+//!
+//! Raw requests are sent with [`crate::Client::execute_raw`], which runs them
+//! against the node the client is connected to and applies the client
+//! reconnection and retry logic.
+//!
+//! ```no_run
+//! use eventstore::unstable::messages::streams;
+//! use eventstore::unstable::messages::streams::streams_client::StreamsClient;
+//! use eventstore::Client;
+//!
+//! # async fn example(client: Client) -> eventstore::Result<()> {
+//! let deleted = client
+//!     .execute_raw(|channel, credentials| async move {
+//!         let req = streams::DeleteReq {
+//!             options: Some(streams::delete_req::Options {
+//!                 stream_identifier: Some(eventstore::unstable::messages::shared::StreamIdentifier {
+//!                     stream_name: b"orders".to_vec(),
+//!                 }),
+//!                 expected_stream_revision: Some(
+//!                     streams::delete_req::options::ExpectedStreamRevision::Any(
+//!                         eventstore::unstable::messages::shared::Empty {},
+//!                     ),
+//!                 ),
+//!             }),
+//!         };
+//!
+//!         let resp = StreamsClient::new(channel)
+//!             .delete(eventstore::unstable::request(req, credentials))
+//!             .await?;
+//!
+//!         Ok(resp.into_inner())
+//!     })
+//!     .await?;
+//! # let _ = deleted;
+//! # Ok(())
+//! # }
+//! ```
+use crate::Credentials;
+use tonic::Request;
+
+/// Protobuf messages and gRPC service clients of the server protocol.
+pub mod messages {
+    pub use crate::event_store::client::persistent;
+    pub use crate::event_store::client::shared;
+    pub use crate::event_store::client::streams;
+}
+
+pub use tonic::transport::Channel;
+pub use tonic::Status;
+
+/// Wraps a message into a request carrying the given credentials, and the
+/// operation identifier when run within [`crate::Client::submit`].
+pub fn request<A>(message: A, credentials: Option<Credentials>) -> Request<A> {
+    crate::commands::new_request(message, credentials)
+}
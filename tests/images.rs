@@ -19,6 +19,7 @@ impl IntoIterator for ESDBArgs {
 }
 
 #[derive(Debug, Clone)]
+#[allow(clippy::upper_case_acronyms)]
 pub struct ESDB {
     tag: String,
     arguments: ESDBArgs,
@@ -92,7 +93,7 @@ impl Default for ESDB {
     fn default() -> Self {
         ESDB {
             tag: DEFAULT_TAG.to_string(),
-            arguments: ESDBArgs::default(),
+            arguments: ESDBArgs,
             env_vars: HashMap::new(),
             vol_vars: HashMap::new(),
         }